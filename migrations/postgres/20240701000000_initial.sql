@@ -0,0 +1,53 @@
+--
+-- Initial schema of the bot, the one of the former `database/script.sql`
+--
+-- The tables are only created when they are missing, so that a database created with that
+-- script is brought up to date by the following migrations.
+--
+
+--
+-- Name: servers; Type: TABLE; Schema: public; Owner: postgres
+--
+
+CREATE TABLE IF NOT EXISTS public.servers (
+    id bigint NOT NULL,
+    ticket_channel_id bigint NOT NULL,
+    unclaimed_category_id bigint NOT NULL,
+    claimed_category_id bigint NOT NULL,
+    ticket_message_id bigint NOT NULL,
+    helper_role_id bigint NOT NULL,
+    moderator_role_id bigint NOT NULL,
+    log_channel_id bigint NOT NULL,
+    CONSTRAINT servers_pkey PRIMARY KEY (id)
+);
+
+
+--
+-- Name: subjects; Type: TABLE; Schema: public; Owner: postgres
+--
+
+CREATE TABLE IF NOT EXISTS public.subjects (
+    id bigint GENERATED ALWAYS AS IDENTITY NOT NULL,
+    server_id bigint NOT NULL,
+    name character varying(100) NOT NULL,
+    channel_id bigint NOT NULL,
+    CONSTRAINT subjects_pkey PRIMARY KEY (id, server_id),
+    CONSTRAINT subjects_server_id_name_key UNIQUE (server_id, name)
+);
+
+
+--
+-- Name: tickets; Type: TABLE; Schema: public; Owner: postgres
+--
+
+CREATE TABLE IF NOT EXISTS public.tickets (
+    ticket_id serial NOT NULL,
+    channel_id bigint NOT NULL,
+    author_id bigint NOT NULL,
+    subject_id bigint,
+    is_open boolean DEFAULT true,
+    server_id bigint NOT NULL,
+    CONSTRAINT tickets_pkey PRIMARY KEY (ticket_id),
+    CONSTRAINT tickets_servers_id_fk FOREIGN KEY (server_id) REFERENCES public.servers(id),
+    CONSTRAINT tickets_subjects_id_server_id_fk FOREIGN KEY (subject_id, server_id) REFERENCES public.subjects(id, server_id)
+);
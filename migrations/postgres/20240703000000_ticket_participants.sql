@@ -0,0 +1,12 @@
+--
+-- Extra participants of the tickets, added by the staff
+--
+
+CREATE TABLE ticket_participants (
+    ticket_id integer NOT NULL,
+    user_id bigint NOT NULL,
+    added_by bigint NOT NULL,
+    added_at timestamp with time zone DEFAULT now() NOT NULL,
+    CONSTRAINT ticket_participants_pkey PRIMARY KEY (ticket_id, user_id),
+    CONSTRAINT ticket_participants_tickets_ticket_id_fk FOREIGN KEY (ticket_id) REFERENCES tickets(ticket_id) ON DELETE CASCADE
+);
@@ -0,0 +1,10 @@
+--
+-- Closing of the tickets
+--
+-- The closed tickets are kept, with who closed them, when and why.
+--
+
+ALTER TABLE tickets
+    ADD COLUMN closed_at timestamp with time zone,
+    ADD COLUMN closed_by bigint,
+    ADD COLUMN close_reason character varying(512);
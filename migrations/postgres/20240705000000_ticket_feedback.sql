@@ -0,0 +1,21 @@
+--
+-- Satisfaction ratings of the tickets
+--
+-- The tickets remember the helper who claimed them, whom the rating of the author is about.
+--
+
+ALTER TABLE tickets
+    ADD COLUMN claimed_by bigint,
+    ADD COLUMN claimed_at timestamp with time zone;
+
+CREATE TABLE ticket_feedback (
+    ticket_id integer NOT NULL,
+    server_id bigint NOT NULL,
+    helper_id bigint,
+    rating smallint NOT NULL,
+    comment character varying(1024),
+    created_at timestamp with time zone DEFAULT now() NOT NULL,
+    CONSTRAINT ticket_feedback_pkey PRIMARY KEY (ticket_id),
+    CONSTRAINT ticket_feedback_rating_check CHECK (rating >= 1 AND rating <= 5),
+    CONSTRAINT ticket_feedback_tickets_ticket_id_fk FOREIGN KEY (ticket_id) REFERENCES tickets(ticket_id) ON DELETE CASCADE
+);
@@ -0,0 +1,11 @@
+--
+-- Tickets as private threads
+--
+-- A server opens its tickets as channels or as threads, and each ticket remembers which one it
+-- is, so that the tickets opened before a change of mode keep working.
+--
+
+ALTER TABLE servers ADD COLUMN ticket_mode character varying(16) DEFAULT 'channel' NOT NULL
+    CONSTRAINT servers_ticket_mode_check CHECK (ticket_mode IN ('channel', 'thread'));
+
+ALTER TABLE tickets ADD COLUMN location_kind character varying(16) DEFAULT 'channel' NOT NULL;
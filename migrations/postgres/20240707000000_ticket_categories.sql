@@ -0,0 +1,17 @@
+--
+-- Overflow categories of the tickets
+--
+-- A category holds at most 50 channels: the categories created when one is full are kept in
+-- order, for the unclaimed and the claimed tickets.
+--
+
+CREATE TABLE ticket_categories (
+    category_id bigint NOT NULL,
+    server_id bigint NOT NULL,
+    state character varying(16) NOT NULL,
+    "position" smallint NOT NULL,
+    CONSTRAINT ticket_categories_pkey PRIMARY KEY (category_id),
+    CONSTRAINT ticket_categories_server_id_state_position_key UNIQUE (server_id, state, "position"),
+    CONSTRAINT ticket_categories_state_check CHECK (state IN ('unclaimed', 'claimed')),
+    CONSTRAINT ticket_categories_servers_id_fk FOREIGN KEY (server_id) REFERENCES servers(id) ON DELETE CASCADE
+);
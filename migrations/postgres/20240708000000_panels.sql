@@ -0,0 +1,37 @@
+--
+-- Ticket panels
+--
+-- A server can have several messages opening tickets, each with its own subjects and category.
+-- The message of each server becomes its `default` panel.
+--
+
+CREATE TABLE panels (
+    id integer GENERATED ALWAYS AS IDENTITY NOT NULL,
+    server_id bigint NOT NULL,
+    name character varying(100) NOT NULL,
+    channel_id bigint NOT NULL,
+    message_id bigint NOT NULL,
+    title character varying(256) NOT NULL,
+    description character varying(2048) NOT NULL,
+    button_label character varying(80) NOT NULL,
+    category_id bigint,
+    CONSTRAINT panels_pkey PRIMARY KEY (id),
+    CONSTRAINT panels_server_id_name_key UNIQUE (server_id, name),
+    CONSTRAINT panels_channel_id_message_id_key UNIQUE (channel_id, message_id),
+    CONSTRAINT panels_servers_id_fk FOREIGN KEY (server_id) REFERENCES servers(id) ON DELETE CASCADE
+);
+
+CREATE TABLE panel_subjects (
+    panel_id integer NOT NULL,
+    subject_id bigint NOT NULL,
+    server_id bigint NOT NULL,
+    CONSTRAINT panel_subjects_pkey PRIMARY KEY (panel_id, subject_id),
+    CONSTRAINT panel_subjects_panels_id_fk FOREIGN KEY (panel_id) REFERENCES panels(id) ON DELETE CASCADE,
+    CONSTRAINT panel_subjects_subjects_id_server_id_fk FOREIGN KEY (subject_id, server_id) REFERENCES subjects(id, server_id) ON DELETE CASCADE
+);
+
+INSERT INTO panels (server_id, name, channel_id, message_id, title, description, button_label)
+SELECT id, 'default', ticket_channel_id, ticket_message_id, 'Open a ticket', '', 'Open a ticket'
+FROM servers;
+
+ALTER TABLE servers DROP COLUMN ticket_message_id;
@@ -0,0 +1,11 @@
+--
+-- Message templates of the servers
+--
+
+CREATE TABLE templates (
+    server_id bigint NOT NULL,
+    kind character varying(32) NOT NULL,
+    content character varying(1024) NOT NULL,
+    CONSTRAINT templates_pkey PRIMARY KEY (server_id, kind),
+    CONSTRAINT templates_servers_id_fk FOREIGN KEY (server_id) REFERENCES servers(id) ON DELETE CASCADE
+);
@@ -0,0 +1,9 @@
+--
+-- Priority of the tickets, chosen when they are opened or set by the staff
+--
+-- The greeting message is kept so that its colour follows the priority.
+--
+
+ALTER TABLE tickets
+    ADD COLUMN priority TEXT DEFAULT 'normal' NOT NULL CHECK (priority IN ('low', 'normal', 'high')),
+    ADD COLUMN greeting_message_id BIGINT;
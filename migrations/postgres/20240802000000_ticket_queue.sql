@@ -0,0 +1,10 @@
+--
+-- Queue of the unclaimed tickets
+--
+-- The tickets remember when they were opened, to show how long they have been waiting, and a
+-- subject can be restricted to the helpers holding a role.
+--
+
+ALTER TABLE tickets ADD COLUMN opened_at timestamp with time zone DEFAULT now() NOT NULL;
+
+ALTER TABLE subjects ADD COLUMN helper_role_id BIGINT;
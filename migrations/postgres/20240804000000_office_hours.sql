@@ -0,0 +1,19 @@
+--
+-- Office hours of the servers and of their subjects
+--
+-- A schedule is stored as written, for example `mon-fri 09:00-17:00, sat 10:00-12:00`, and read
+-- in the time zone of the server (an offset from UTC). Servers and subjects without a schedule
+-- are always open, the schedule of a subject narrows the one of its server.
+--
+
+ALTER TABLE servers ADD COLUMN office_hours TEXT;
+
+ALTER TABLE servers ADD COLUMN time_zone TEXT DEFAULT '+00:00' NOT NULL;
+
+ALTER TABLE servers ADD COLUMN closed_mode TEXT DEFAULT 'refuse' NOT NULL
+    CHECK (closed_mode IN ('refuse', 'pending'));
+
+ALTER TABLE subjects ADD COLUMN office_hours TEXT;
+
+-- Tickets accepted outside office hours, announced to the helpers when they start
+ALTER TABLE tickets ADD COLUMN pending BOOLEAN DEFAULT false NOT NULL;
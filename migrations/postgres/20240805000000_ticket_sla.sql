@@ -0,0 +1,23 @@
+--
+-- Service level reminders of the tickets
+--
+-- The thresholds are in minutes, a reminder is disabled when its threshold is not set.
+--
+
+ALTER TABLE servers ADD COLUMN sla_unclaimed_minutes BIGINT;
+
+ALTER TABLE servers ADD COLUMN sla_escalation_minutes BIGINT;
+
+ALTER TABLE servers ADD COLUMN sla_reply_minutes BIGINT;
+
+-- The last messages of the author and of the staff in the ticket
+ALTER TABLE tickets ADD COLUMN last_author_message_at timestamp with time zone;
+
+ALTER TABLE tickets ADD COLUMN last_staff_message_at timestamp with time zone;
+
+-- When each reminder was sent, so that it is sent once
+ALTER TABLE tickets ADD COLUMN helpers_pinged_at timestamp with time zone;
+
+ALTER TABLE tickets ADD COLUMN moderators_pinged_at timestamp with time zone;
+
+ALTER TABLE tickets ADD COLUMN helper_reminded_at timestamp with time zone;
@@ -0,0 +1,10 @@
+--
+-- Activity of the tickets
+--
+-- The first answer of the staff is kept for the statistics, and a server can close the tickets
+-- in which nobody wrote for a while. The threshold is in minutes, never when it is not set.
+--
+
+ALTER TABLE tickets ADD COLUMN first_response_at timestamp with time zone;
+
+ALTER TABLE servers ADD COLUMN inactivity_close_minutes BIGINT;
@@ -0,0 +1,16 @@
+--
+-- Overflow of the panel categories
+--
+-- A panel can place its tickets in its own category, which overflows like the categories of the
+-- server. Each overflow category remembers the category it extends.
+--
+
+ALTER TABLE ticket_categories ADD COLUMN base_category_id BIGINT;
+
+UPDATE ticket_categories SET base_category_id = (
+    SELECT CASE ticket_categories.state
+        WHEN 'unclaimed' THEN s.unclaimed_category_id
+        ELSE s.claimed_category_id
+    END
+    FROM servers s WHERE s.id = ticket_categories.server_id
+);
@@ -0,0 +1,8 @@
+--
+-- Release of the tickets held pending for the office hours
+--
+-- The reminders of a released ticket are measured from its release rather than its opening, the
+-- time it waited for the office hours is not the time the helpers took.
+--
+
+ALTER TABLE tickets ADD COLUMN released_at timestamp with time zone;
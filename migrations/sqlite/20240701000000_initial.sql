@@ -0,0 +1,50 @@
+--
+-- Initial schema of the bot, for SQLite
+--
+-- Mirrors the PostgreSQL schema: the Discord IDs are stored in 64-bit integers and the
+-- timestamps are stored as UTC text by CURRENT_TIMESTAMP.
+--
+
+--
+-- Name: servers; Type: TABLE
+--
+
+CREATE TABLE servers (
+    id BIGINT NOT NULL PRIMARY KEY,
+    ticket_channel_id BIGINT NOT NULL,
+    unclaimed_category_id BIGINT NOT NULL,
+    claimed_category_id BIGINT NOT NULL,
+    ticket_message_id BIGINT NOT NULL,
+    helper_role_id BIGINT NOT NULL,
+    moderator_role_id BIGINT NOT NULL,
+    log_channel_id BIGINT NOT NULL
+);
+
+
+--
+-- Name: subjects; Type: TABLE
+--
+
+CREATE TABLE subjects (
+    id INTEGER PRIMARY KEY,
+    server_id BIGINT NOT NULL,
+    name TEXT NOT NULL,
+    channel_id BIGINT NOT NULL,
+    UNIQUE (id, server_id),
+    UNIQUE (server_id, name)
+);
+
+
+--
+-- Name: tickets; Type: TABLE
+--
+
+CREATE TABLE tickets (
+    ticket_id INTEGER PRIMARY KEY,
+    channel_id BIGINT NOT NULL,
+    author_id BIGINT NOT NULL,
+    subject_id BIGINT,
+    is_open BOOLEAN DEFAULT TRUE,
+    server_id BIGINT NOT NULL REFERENCES servers (id),
+    FOREIGN KEY (subject_id, server_id) REFERENCES subjects (id, server_id)
+);
@@ -0,0 +1,7 @@
+--
+-- Tickets opened by the staff on behalf of a member
+--
+-- The staff member who opened a ticket on behalf of its author, empty for the other tickets.
+--
+
+ALTER TABLE tickets ADD COLUMN opened_by BIGINT;
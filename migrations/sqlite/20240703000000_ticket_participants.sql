@@ -0,0 +1,11 @@
+--
+-- Extra participants of the tickets, added by the staff
+--
+
+CREATE TABLE ticket_participants (
+    ticket_id INTEGER NOT NULL REFERENCES tickets (ticket_id) ON DELETE CASCADE,
+    user_id BIGINT NOT NULL,
+    added_by BIGINT NOT NULL,
+    added_at DATETIME DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    PRIMARY KEY (ticket_id, user_id)
+);
@@ -0,0 +1,9 @@
+--
+-- Closing of the tickets
+--
+-- The closed tickets are kept, with who closed them, when and why.
+--
+
+ALTER TABLE tickets ADD COLUMN closed_at DATETIME;
+ALTER TABLE tickets ADD COLUMN closed_by BIGINT;
+ALTER TABLE tickets ADD COLUMN close_reason TEXT;
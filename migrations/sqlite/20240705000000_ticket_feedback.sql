@@ -0,0 +1,17 @@
+--
+-- Satisfaction ratings of the tickets
+--
+-- The tickets remember the helper who claimed them, whom the rating of the author is about.
+--
+
+ALTER TABLE tickets ADD COLUMN claimed_by BIGINT;
+ALTER TABLE tickets ADD COLUMN claimed_at DATETIME;
+
+CREATE TABLE ticket_feedback (
+    ticket_id INTEGER NOT NULL PRIMARY KEY REFERENCES tickets (ticket_id) ON DELETE CASCADE,
+    server_id BIGINT NOT NULL,
+    helper_id BIGINT,
+    rating SMALLINT NOT NULL CHECK (rating >= 1 AND rating <= 5),
+    comment TEXT,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP NOT NULL
+);
@@ -0,0 +1,10 @@
+--
+-- Tickets as private threads
+--
+-- A server opens its tickets as channels or as threads, and each ticket remembers which one it
+-- is, so that the tickets opened before a change of mode keep working.
+--
+
+ALTER TABLE servers
+    ADD COLUMN ticket_mode TEXT DEFAULT 'channel' NOT NULL CHECK (ticket_mode IN ('channel', 'thread'));
+ALTER TABLE tickets ADD COLUMN location_kind TEXT DEFAULT 'channel' NOT NULL;
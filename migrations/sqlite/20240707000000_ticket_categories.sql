@@ -0,0 +1,14 @@
+--
+-- Overflow categories of the tickets
+--
+-- A category holds at most 50 channels: the categories created when one is full are kept in
+-- order, for the unclaimed and the claimed tickets.
+--
+
+CREATE TABLE ticket_categories (
+    category_id BIGINT NOT NULL PRIMARY KEY,
+    server_id BIGINT NOT NULL REFERENCES servers (id) ON DELETE CASCADE,
+    state TEXT NOT NULL CHECK (state IN ('unclaimed', 'claimed')),
+    position SMALLINT NOT NULL,
+    UNIQUE (server_id, state, position)
+);
@@ -0,0 +1,34 @@
+--
+-- Ticket panels
+--
+-- A server can have several messages opening tickets, each with its own subjects and category.
+-- The message of each server becomes its `default` panel.
+--
+
+CREATE TABLE panels (
+    id INTEGER PRIMARY KEY,
+    server_id BIGINT NOT NULL REFERENCES servers (id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    channel_id BIGINT NOT NULL,
+    message_id BIGINT NOT NULL,
+    title TEXT NOT NULL,
+    description TEXT NOT NULL,
+    button_label TEXT NOT NULL,
+    category_id BIGINT,
+    UNIQUE (server_id, name),
+    UNIQUE (channel_id, message_id)
+);
+
+CREATE TABLE panel_subjects (
+    panel_id INTEGER NOT NULL REFERENCES panels (id) ON DELETE CASCADE,
+    subject_id BIGINT NOT NULL,
+    server_id BIGINT NOT NULL,
+    PRIMARY KEY (panel_id, subject_id),
+    FOREIGN KEY (subject_id, server_id) REFERENCES subjects (id, server_id) ON DELETE CASCADE
+);
+
+INSERT INTO panels (server_id, name, channel_id, message_id, title, description, button_label)
+SELECT id, 'default', ticket_channel_id, ticket_message_id, 'Open a ticket', '', 'Open a ticket'
+FROM servers;
+
+ALTER TABLE servers DROP COLUMN ticket_message_id;
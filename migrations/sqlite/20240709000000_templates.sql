@@ -0,0 +1,10 @@
+--
+-- Message templates of the servers
+--
+
+CREATE TABLE templates (
+    server_id BIGINT NOT NULL REFERENCES servers (id) ON DELETE CASCADE,
+    kind TEXT NOT NULL,
+    content TEXT NOT NULL,
+    PRIMARY KEY (server_id, kind)
+);
@@ -0,0 +1,5 @@
+--
+-- Language of the servers, for the messages that are not an answer to a member
+--
+
+ALTER TABLE servers ADD COLUMN locale TEXT DEFAULT 'en' NOT NULL;
@@ -0,0 +1,5 @@
+--
+-- Prefix of the prefix commands of the servers
+--
+
+ALTER TABLE servers ADD COLUMN prefix TEXT DEFAULT '$' NOT NULL;
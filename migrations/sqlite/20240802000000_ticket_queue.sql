@@ -0,0 +1,14 @@
+--
+-- Queue of the unclaimed tickets
+--
+-- The tickets remember when they were opened, to show how long they have been waiting, and a
+-- subject can be restricted to the helpers holding a role.
+--
+-- SQLite cannot add a column defaulting to CURRENT_TIMESTAMP, the tickets are stamped by the
+-- query storing them instead.
+--
+
+ALTER TABLE tickets ADD COLUMN opened_at DATETIME;
+UPDATE tickets SET opened_at = CURRENT_TIMESTAMP;
+
+ALTER TABLE subjects ADD COLUMN helper_role_id BIGINT;
@@ -0,0 +1,24 @@
+--
+-- Automatic assignment of the tickets to the helpers on duty
+--
+-- The mode of a subject, when set, overrides the one of its server.
+--
+
+ALTER TABLE servers ADD COLUMN assign_mode TEXT DEFAULT 'manual' NOT NULL
+    CHECK (assign_mode IN ('manual', 'round_robin', 'least_busy'));
+
+ALTER TABLE subjects ADD COLUMN assign_mode TEXT
+    CHECK (assign_mode IN ('manual', 'round_robin', 'least_busy'));
+
+
+--
+-- Name: helper_duty; Type: TABLE
+--
+
+CREATE TABLE helper_duty (
+    server_id BIGINT NOT NULL REFERENCES servers (id) ON DELETE CASCADE,
+    user_id BIGINT NOT NULL,
+    on_duty BOOLEAN NOT NULL,
+    last_assigned_at DATETIME,
+    PRIMARY KEY (server_id, user_id)
+);
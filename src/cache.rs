@@ -0,0 +1,134 @@
+//! This module caches the configuration of the servers, read on every message and reaction
+//!
+//! The configuration of a server is loaded the first time it is needed and dropped whenever it
+//! changes (setup, reset, config commands, panels), to be loaded again on its next use. The
+//! channels of the tickets are kept up to date in place, as tickets are opened and closed all
+//! the time.
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard,
+    },
+};
+
+use poise::serenity_prelude::{ChannelId, GuildId, MessageId};
+
+use crate::{
+    database::{Pool, Server, ServerRepo, TicketRepo},
+    handler::{Error, DEFAULT_PREFIX},
+    tickets::panels::get_panel_messages,
+};
+
+/// The configuration of a server, as cached
+#[derive(Clone, Debug, Default)]
+pub struct ServerConfig {
+    /// The settings of the server, `None` if it is not set up
+    pub server: Option<Server>,
+    /// The messages displaying a panel, the only ones reactions can open a ticket from
+    pub panel_messages: HashSet<MessageId>,
+    /// The channels (or threads) of the open tickets, the only ones whose messages are tracked
+    pub ticket_channels: HashSet<ChannelId>,
+}
+
+impl ServerConfig {
+    pub fn is_setup(&self) -> bool {
+        self.server.is_some()
+    }
+
+    /// Returns the prefix of the prefix commands in the server
+    pub fn prefix(&self) -> &str {
+        self.server
+            .as_ref()
+            .map_or(DEFAULT_PREFIX, |server| &server.prefix)
+    }
+}
+
+/// The cached configuration of every server seen since the start of the bot
+#[derive(Default)]
+pub struct ConfigCache {
+    servers: RwLock<HashMap<GuildId, Arc<ServerConfig>>>,
+    /// Incremented on every invalidation, so that a configuration loaded meanwhile is not kept
+    generation: AtomicU64,
+}
+
+impl ConfigCache {
+    /// Returns the configuration of the server, loading it if it is not cached
+    pub async fn get(&self, pool: &Pool, guild_id: GuildId) -> Result<Arc<ServerConfig>, Error> {
+        if let Some(config) = self.read().get(&guild_id) {
+            return Ok(Arc::clone(config));
+        }
+
+        let generation = self.generation.load(Ordering::Acquire);
+
+        let mut conn = pool.acquire().await?;
+        let server = ServerRepo::find(&mut conn, guild_id).await?;
+        let (panel_messages, ticket_channels) = match server {
+            Some(_) => (
+                get_panel_messages(&mut conn, guild_id).await?,
+                TicketRepo::open_channels(&mut conn, guild_id)
+                    .await?
+                    .into_iter()
+                    .collect(),
+            ),
+            None => (HashSet::new(), HashSet::new()),
+        };
+        let config = Arc::new(ServerConfig {
+            server,
+            panel_messages,
+            ticket_channels,
+        });
+
+        let mut servers = self.write();
+        if self.generation.load(Ordering::Acquire) == generation {
+            servers.insert(guild_id, Arc::clone(&config));
+        }
+
+        Ok(config)
+    }
+
+    /// Drops the configuration of the server, it must be called after every change of it
+    pub fn invalidate(&self, guild_id: GuildId) {
+        let mut servers = self.write();
+        self.generation.fetch_add(1, Ordering::AcqRel);
+        servers.remove(&guild_id);
+    }
+
+    /// Adds the channel of a ticket opened in the server
+    pub fn add_ticket_channel(&self, guild_id: GuildId, channel_id: ChannelId) {
+        self.update_ticket_channels(guild_id, |channels| {
+            channels.insert(channel_id);
+        });
+    }
+
+    /// Removes the channel of a ticket closed in the server
+    pub fn remove_ticket_channel(&self, guild_id: GuildId, channel_id: ChannelId) {
+        self.update_ticket_channels(guild_id, |channels| {
+            channels.remove(&channel_id);
+        });
+    }
+
+    /// Updates the ticket channels of the server if it is cached, without reloading the rest
+    fn update_ticket_channels(
+        &self,
+        guild_id: GuildId,
+        update: impl FnOnce(&mut HashSet<ChannelId>),
+    ) {
+        let mut servers = self.write();
+        // A configuration loaded meanwhile may have read the channels before the change
+        self.generation.fetch_add(1, Ordering::AcqRel);
+        if let Some(config) = servers.get_mut(&guild_id) {
+            update(&mut Arc::make_mut(config).ticket_channels);
+        }
+    }
+
+    // A panic while holding the lock cannot leave the map in an invalid state
+    fn read(&self) -> RwLockReadGuard<'_, HashMap<GuildId, Arc<ServerConfig>>> {
+        self.servers.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, HashMap<GuildId, Arc<ServerConfig>>> {
+        self.servers.write().unwrap_or_else(PoisonError::into_inner)
+    }
+}
@@ -1,68 +1,54 @@
-use std::env;
+//! This module regroups the access to the database
+//!
+//! Each table used by the handlers has a typed model and a repository, so that the rest of the bot
+//! never deals with raw rows or with the storage of the Discord IDs.
+//!
+//! The queries themselves are written once per backend behind the [`Storage`] trait: PostgreSQL by
+//! default, or an SQLite file with the `sqlite` feature.
 
-use poise::serenity_prelude::GuildId;
-use sqlx::{
-    postgres::{PgConnectOptions, PgPoolOptions},
-    PgConnection,
-};
+use sqlx::{migrate::Migrator, pool::PoolOptions};
 
-use crate::handler::Error;
+use crate::config::DatabaseConfig;
 
-fn get_connection() -> Result<PgConnectOptions, String> {
-    Ok(PgConnectOptions::new()
-        .host(&env::var("DB_HOST").map_err(|error| error.to_string())?)
-        .port(
-            env::var("DB_PORT")
-                .map_err(|error| error.to_string())?
-                .parse()
-                .map_err(|_| "Failed to parse port".to_string())?,
-        )
-        .username(&env::var("DB_USER").map_err(|error| error.to_string())?)
-        .password(&env::var("DB_PASSWORD").map_err(|error| error.to_string())?)
-        .database(&env::var("DB_NAME").map_err(|error| error.to_string())?))
-}
+// Crate modules
+#[cfg(not(feature = "sqlite"))]
+mod postgres;
+mod server;
+mod snowflake;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+mod stats;
+mod storage;
+mod subject;
+mod ticket;
 
-pub async fn get_database_pool() -> Result<sqlx::PgPool, String> {
-    let connection = get_connection()?;
-    PgPoolOptions::new()
-        .max_connections(5)
-        .connect_with(connection)
-        .await
-        .map_err(|error| error.to_string())
-}
+pub use server::{NewServer, Server, ServerRepo};
+pub use snowflake::Snowflake;
+pub use stats::{HelperStats, StatsRepo, SubjectStats, TicketTotals};
+pub(crate) use storage::Storage;
+pub use subject::{Subject, SubjectRepo};
+pub use ticket::{NewTicket, QueuedTicket, Ticket, TicketRepo};
 
-pub async fn is_server_setup(pool: &mut PgConnection, guild_id: GuildId) -> Result<bool, Error> {
-    Ok(
-        sqlx::query!("SELECT * FROM servers WHERE id = $1", guild_id.get() as i64)
-            .fetch_optional(&mut *pool)
-            .await?
-            .is_some(),
-    )
-}
+/// The database the bot is built for
+#[cfg(not(feature = "sqlite"))]
+pub type Backend = sqlx::Postgres;
+#[cfg(feature = "sqlite")]
+pub type Backend = sqlx::Sqlite;
 
-/// Represents a subject
-#[derive(Clone, Debug, PartialEq, Eq, Default)]
-pub struct Subject {
-    pub id: Option<u64>,
-    pub name: String,
-}
+pub type Pool = sqlx::Pool<Backend>;
+pub type Connection = <Backend as sqlx::Database>::Connection;
+pub type ConnectOptions = <Connection as sqlx::Connection>::Options;
+
+/// The migrations of the schema of the backend
+#[cfg(not(feature = "sqlite"))]
+pub static MIGRATOR: Migrator = sqlx::migrate!("./migrations/postgres");
+#[cfg(feature = "sqlite")]
+pub static MIGRATOR: Migrator = sqlx::migrate!("./migrations/sqlite");
 
-pub async fn get_subjects(
-    pool: &mut PgConnection,
-    guild_id: GuildId,
-) -> Result<Vec<Subject>, Error> {
-    info!("Getting subjects for guild {}", guild_id);
-    let rows = sqlx::query!(
-        "SELECT id, name FROM subjects WHERE server_id = $1",
-        guild_id.get() as i64
-    )
-    .fetch_all(&mut *pool)
-    .await?;
-    Ok(rows
-        .into_iter()
-        .map(|row| Subject {
-            id: Some(row.id as u64),
-            name: row.name,
-        })
-        .collect())
+pub async fn get_database_pool(config: &DatabaseConfig) -> Result<Pool, String> {
+    PoolOptions::new()
+        .max_connections(config.pool_size)
+        .connect_with(config.connect_options.clone())
+        .await
+        .map_err(|error| error.to_string())
 }
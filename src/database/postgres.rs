@@ -0,0 +1,1264 @@
+//! The queries of the PostgreSQL backend, the default one
+
+use std::time::Duration;
+
+use poise::serenity_prelude::{ChannelId, GuildId, MessageId, RoleId, UserId};
+use sqlx::PgConnection;
+
+use crate::{
+    handler::Error,
+    i18n::Locale,
+    tickets::{
+        assign::AssignMode,
+        categories::CategoryState,
+        location::TicketMode,
+        office_hours::{ClosedMode, Schedule, ServerTimeZone},
+        panels::{NewPanel, Panel},
+        priority::Priority,
+        sla::{self, Reminder, SlaThresholds},
+        templates::TemplateKind,
+    },
+};
+
+use super::{
+    server::ServerRow,
+    ticket::{QueuedTicketRow, TicketRow},
+    HelperStats, NewServer, NewTicket, QueuedTicket, Server, Snowflake, Storage, Subject,
+    SubjectStats, Ticket, TicketTotals,
+};
+
+impl Storage for PgConnection {
+    async fn find_server(&mut self, guild_id: GuildId) -> Result<Option<Server>, Error> {
+        sqlx::query_as!(
+            ServerRow,
+            "SELECT * FROM servers WHERE id = $1",
+            guild_id.to_db()
+        )
+        .fetch_optional(self)
+        .await?
+        .map(Server::try_from)
+        .transpose()
+    }
+
+    async fn server_exists(&mut self, guild_id: GuildId) -> Result<bool, Error> {
+        let exists = sqlx::query_scalar!(
+            r#"SELECT EXISTS(SELECT 1 FROM servers WHERE id = $1) AS "exists!""#,
+            guild_id.to_db()
+        )
+        .fetch_one(self)
+        .await?;
+
+        Ok(exists)
+    }
+
+    async fn server_prefix(&mut self, guild_id: GuildId) -> Result<Option<String>, Error> {
+        let prefix =
+            sqlx::query_scalar!("SELECT prefix FROM servers WHERE id = $1", guild_id.to_db())
+                .fetch_optional(self)
+                .await?;
+
+        Ok(prefix)
+    }
+
+    async fn insert_server(&mut self, server: &NewServer) -> Result<(), Error> {
+        sqlx::query!(
+            "INSERT INTO servers (
+                id,
+                ticket_channel_id,
+                unclaimed_category_id,
+                claimed_category_id,
+                log_channel_id,
+                helper_role_id,
+                moderator_role_id
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7)",
+            server.id.to_db(),
+            server.ticket_channel_id.to_db(),
+            server.unclaimed_category_id.to_db(),
+            server.claimed_category_id.to_db(),
+            server.log_channel_id.to_db(),
+            server.helper_role_id.to_db(),
+            server.moderator_role_id.to_db()
+        )
+        .execute(self)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn delete_server(&mut self, guild_id: GuildId) -> Result<Option<Server>, Error> {
+        sqlx::query_as!(
+            ServerRow,
+            "DELETE FROM servers WHERE id = $1 RETURNING *",
+            guild_id.to_db()
+        )
+        .fetch_optional(self)
+        .await?
+        .map(Server::try_from)
+        .transpose()
+    }
+
+    async fn set_server_prefix(&mut self, guild_id: GuildId, prefix: &str) -> Result<(), Error> {
+        sqlx::query!(
+            "UPDATE servers SET prefix = $2 WHERE id = $1",
+            guild_id.to_db(),
+            prefix
+        )
+        .execute(self)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn set_server_locale(&mut self, guild_id: GuildId, locale: Locale) -> Result<(), Error> {
+        sqlx::query!(
+            "UPDATE servers SET locale = $2 WHERE id = $1",
+            guild_id.to_db(),
+            locale.as_str()
+        )
+        .execute(self)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn set_server_ticket_mode(
+        &mut self,
+        guild_id: GuildId,
+        mode: TicketMode,
+    ) -> Result<(), Error> {
+        sqlx::query!(
+            "UPDATE servers SET ticket_mode = $2 WHERE id = $1",
+            guild_id.to_db(),
+            mode.as_str()
+        )
+        .execute(self)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn set_server_assign_mode(
+        &mut self,
+        guild_id: GuildId,
+        mode: AssignMode,
+    ) -> Result<(), Error> {
+        sqlx::query!(
+            "UPDATE servers SET assign_mode = $2 WHERE id = $1",
+            guild_id.to_db(),
+            mode.as_str()
+        )
+        .execute(self)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn set_server_office_hours(
+        &mut self,
+        guild_id: GuildId,
+        office_hours: Option<&Schedule>,
+    ) -> Result<(), Error> {
+        sqlx::query!(
+            "UPDATE servers SET office_hours = $2 WHERE id = $1",
+            guild_id.to_db(),
+            office_hours.map(Schedule::as_str)
+        )
+        .execute(self)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn set_server_time_zone(
+        &mut self,
+        guild_id: GuildId,
+        time_zone: ServerTimeZone,
+    ) -> Result<(), Error> {
+        sqlx::query!(
+            "UPDATE servers SET time_zone = $2 WHERE id = $1",
+            guild_id.to_db(),
+            time_zone.to_string()
+        )
+        .execute(self)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn set_server_closed_mode(
+        &mut self,
+        guild_id: GuildId,
+        mode: ClosedMode,
+    ) -> Result<(), Error> {
+        sqlx::query!(
+            "UPDATE servers SET closed_mode = $2 WHERE id = $1",
+            guild_id.to_db(),
+            mode.as_str()
+        )
+        .execute(self)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn scheduled_servers(&mut self) -> Result<Vec<Server>, Error> {
+        sqlx::query_as!(
+            ServerRow,
+            "SELECT * FROM servers WHERE office_hours IS NOT NULL"
+        )
+        .fetch_all(self)
+        .await?
+        .into_iter()
+        .map(Server::try_from)
+        .collect()
+    }
+
+    async fn set_server_sla(
+        &mut self,
+        guild_id: GuildId,
+        sla: &SlaThresholds,
+    ) -> Result<(), Error> {
+        sqlx::query!(
+            "UPDATE servers
+            SET sla_unclaimed_minutes = $2, sla_escalation_minutes = $3, sla_reply_minutes = $4
+            WHERE id = $1",
+            guild_id.to_db(),
+            sla::to_minutes(sla.unclaimed),
+            sla::to_minutes(sla.escalation),
+            sla::to_minutes(sla.reply)
+        )
+        .execute(self)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn set_server_inactivity_close(
+        &mut self,
+        guild_id: GuildId,
+        threshold: Option<Duration>,
+    ) -> Result<(), Error> {
+        sqlx::query!(
+            "UPDATE servers SET inactivity_close_minutes = $2 WHERE id = $1",
+            guild_id.to_db(),
+            sla::to_minutes(threshold)
+        )
+        .execute(self)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn subjects(&mut self, guild_id: GuildId) -> Result<Vec<Subject>, Error> {
+        let rows = sqlx::query!(
+            "SELECT id, name FROM subjects WHERE server_id = $1",
+            guild_id.to_db()
+        )
+        .fetch_all(self)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| Subject {
+                id: Some(row.id as u64),
+                name: row.name,
+            })
+            .collect())
+    }
+
+    async fn subject(&mut self, guild_id: GuildId, id: u64) -> Result<Option<Subject>, Error> {
+        let row = sqlx::query!(
+            "SELECT id, name FROM subjects WHERE server_id = $1 AND id = $2",
+            guild_id.to_db(),
+            id as i64
+        )
+        .fetch_optional(self)
+        .await?;
+
+        Ok(row.map(|row| Subject {
+            id: Some(row.id as u64),
+            name: row.name,
+        }))
+    }
+
+    async fn subject_exists(&mut self, guild_id: GuildId, name: &str) -> Result<bool, Error> {
+        let exists = sqlx::query_scalar!(
+            r#"SELECT EXISTS(SELECT 1 FROM subjects WHERE server_id = $1 AND name = $2) AS "exists!""#,
+            guild_id.to_db(),
+            name
+        )
+        .fetch_one(self)
+        .await?;
+
+        Ok(exists)
+    }
+
+    async fn insert_subject(
+        &mut self,
+        guild_id: GuildId,
+        name: &str,
+        channel_id: ChannelId,
+    ) -> Result<(), Error> {
+        sqlx::query!(
+            "INSERT INTO subjects (name, server_id, channel_id) VALUES ($1, $2, $3)",
+            name,
+            guild_id.to_db(),
+            channel_id.to_db()
+        )
+        .execute(self)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn set_subject_helper_role(
+        &mut self,
+        guild_id: GuildId,
+        name: &str,
+        role_id: Option<RoleId>,
+    ) -> Result<bool, Error> {
+        let updated = sqlx::query!(
+            "UPDATE subjects SET helper_role_id = $3 WHERE server_id = $1 AND name = $2",
+            guild_id.to_db(),
+            name,
+            role_id.map(Snowflake::to_db)
+        )
+        .execute(self)
+        .await?
+        .rows_affected();
+
+        Ok(updated > 0)
+    }
+
+    async fn subject_helper_role(
+        &mut self,
+        guild_id: GuildId,
+        id: u64,
+    ) -> Result<Option<RoleId>, Error> {
+        let role_id = sqlx::query_scalar!(
+            "SELECT helper_role_id FROM subjects WHERE server_id = $1 AND id = $2",
+            guild_id.to_db(),
+            id as i64
+        )
+        .fetch_optional(self)
+        .await?
+        .flatten();
+
+        Ok(role_id.map(RoleId::from_db))
+    }
+
+    async fn set_subject_assign_mode(
+        &mut self,
+        guild_id: GuildId,
+        name: &str,
+        mode: Option<AssignMode>,
+    ) -> Result<bool, Error> {
+        let updated = sqlx::query!(
+            "UPDATE subjects SET assign_mode = $3 WHERE server_id = $1 AND name = $2",
+            guild_id.to_db(),
+            name,
+            mode.map(AssignMode::as_str)
+        )
+        .execute(self)
+        .await?
+        .rows_affected();
+
+        Ok(updated > 0)
+    }
+
+    async fn subject_assign_mode(
+        &mut self,
+        guild_id: GuildId,
+        id: u64,
+    ) -> Result<Option<AssignMode>, Error> {
+        let mode = sqlx::query_scalar!(
+            "SELECT assign_mode FROM subjects WHERE server_id = $1 AND id = $2",
+            guild_id.to_db(),
+            id as i64
+        )
+        .fetch_optional(self)
+        .await?
+        .flatten();
+
+        Ok(mode.map(|mode| mode.parse()).transpose()?)
+    }
+
+    async fn set_subject_office_hours(
+        &mut self,
+        guild_id: GuildId,
+        name: &str,
+        office_hours: Option<&Schedule>,
+    ) -> Result<bool, Error> {
+        let updated = sqlx::query!(
+            "UPDATE subjects SET office_hours = $3 WHERE server_id = $1 AND name = $2",
+            guild_id.to_db(),
+            name,
+            office_hours.map(Schedule::as_str)
+        )
+        .execute(self)
+        .await?
+        .rows_affected();
+
+        Ok(updated > 0)
+    }
+
+    async fn subject_office_hours(
+        &mut self,
+        guild_id: GuildId,
+        id: u64,
+    ) -> Result<Option<Schedule>, Error> {
+        let office_hours = sqlx::query_scalar!(
+            "SELECT office_hours FROM subjects WHERE server_id = $1 AND id = $2",
+            guild_id.to_db(),
+            id as i64
+        )
+        .fetch_optional(self)
+        .await?
+        .flatten();
+
+        Ok(office_hours.map(|hours| hours.parse()).transpose()?)
+    }
+
+    async fn delete_subject(&mut self, guild_id: GuildId, name: &str) -> Result<bool, Error> {
+        let deleted = sqlx::query!(
+            "DELETE FROM subjects WHERE server_id = $1 AND name = $2",
+            guild_id.to_db(),
+            name
+        )
+        .execute(self)
+        .await?
+        .rows_affected();
+
+        Ok(deleted > 0)
+    }
+
+    async fn ticket_by_channel(&mut self, channel_id: ChannelId) -> Result<Option<Ticket>, Error> {
+        sqlx::query_as!(
+            TicketRow,
+            "SELECT ticket_id, channel_id, location_kind, server_id, author_id, subject_id, is_open,
+                opened_by, claimed_by, priority, greeting_message_id
+            FROM tickets WHERE channel_id = $1",
+            channel_id.to_db()
+        )
+        .fetch_optional(self)
+        .await?
+        .map(Ticket::try_from)
+        .transpose()
+    }
+
+    async fn insert_ticket(&mut self, ticket: &NewTicket) -> Result<i32, Error> {
+        let id = sqlx::query_scalar!(
+            "INSERT INTO tickets (channel_id, location_kind, server_id, subject_id, author_id, opened_by,
+                priority, greeting_message_id, pending)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            RETURNING ticket_id",
+            ticket.location.id().to_db(),
+            ticket.location.mode().as_str(),
+            ticket.guild_id.to_db(),
+            ticket.subject_id.map(|id| id as i64),
+            ticket.author_id.to_db(),
+            ticket.opened_by.map(Snowflake::to_db),
+            ticket.priority.as_str(),
+            ticket.greeting_message_id.map(Snowflake::to_db),
+            ticket.pending
+        )
+        .fetch_one(self)
+        .await?;
+
+        Ok(id)
+    }
+
+    async fn claim_ticket(
+        &mut self,
+        channel_id: ChannelId,
+        helper_id: UserId,
+    ) -> Result<bool, Error> {
+        let result = sqlx::query!(
+            "UPDATE tickets SET claimed_by = $2, claimed_at = now()
+            WHERE channel_id = $1 AND claimed_by IS NULL AND is_open",
+            channel_id.to_db(),
+            helper_id.to_db()
+        )
+        .execute(self)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn ticket_queue(&mut self, guild_id: GuildId) -> Result<Vec<QueuedTicket>, Error> {
+        let rows = sqlx::query_as!(
+            QueuedTicketRow,
+            r#"SELECT t.ticket_id, t.channel_id, t.location_kind, t.priority, s.name AS "subject?",
+                EXTRACT(EPOCH FROM now() - t.opened_at)::bigint AS "waiting_secs!"
+            FROM tickets t
+            LEFT JOIN subjects s ON s.id = t.subject_id AND s.server_id = t.server_id
+            WHERE t.server_id = $1 AND t.is_open AND NOT t.pending AND t.claimed_by IS NULL
+            ORDER BY CASE t.priority WHEN 'high' THEN 0 WHEN 'normal' THEN 1 ELSE 2 END,
+                CASE WHEN t.first_response_at IS NULL THEN 0 ELSE 1 END,
+                t.ticket_id"#,
+            guild_id.to_db()
+        )
+        .fetch_all(self)
+        .await?;
+
+        rows.into_iter().map(QueuedTicket::try_from).collect()
+    }
+
+    async fn claim_next_ticket(
+        &mut self,
+        guild_id: GuildId,
+        helper_id: UserId,
+        role_ids: &[RoleId],
+    ) -> Result<Option<Ticket>, Error> {
+        let role_ids: Vec<i64> = role_ids.iter().map(|id| id.to_db()).collect();
+
+        // The tickets being claimed by another helper are locked, and skipped
+        sqlx::query_as!(
+            TicketRow,
+            "UPDATE tickets SET claimed_by = $2, claimed_at = now()
+            WHERE claimed_by IS NULL AND ticket_id = (
+                SELECT t.ticket_id FROM tickets t
+                LEFT JOIN subjects s ON s.id = t.subject_id AND s.server_id = t.server_id
+                WHERE t.server_id = $1 AND t.is_open AND NOT t.pending AND t.claimed_by IS NULL
+                AND (s.helper_role_id IS NULL OR s.helper_role_id = ANY($3))
+                ORDER BY CASE t.priority WHEN 'high' THEN 0 WHEN 'normal' THEN 1 ELSE 2 END,
+                    CASE WHEN t.first_response_at IS NULL THEN 0 ELSE 1 END,
+                    t.ticket_id
+                LIMIT 1
+                FOR UPDATE OF t SKIP LOCKED
+            )
+            RETURNING ticket_id, channel_id, location_kind, server_id, author_id, subject_id,
+                is_open, opened_by, claimed_by, priority, greeting_message_id",
+            guild_id.to_db(),
+            helper_id.to_db(),
+            &role_ids
+        )
+        .fetch_optional(self)
+        .await?
+        .map(Ticket::try_from)
+        .transpose()
+    }
+
+    async fn set_ticket_priority(
+        &mut self,
+        ticket_id: i32,
+        priority: Priority,
+    ) -> Result<(), Error> {
+        sqlx::query!(
+            "UPDATE tickets SET priority = $2 WHERE ticket_id = $1",
+            ticket_id,
+            priority.as_str()
+        )
+        .execute(self)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn open_ticket_priorities(
+        &mut self,
+        guild_id: GuildId,
+    ) -> Result<Vec<(ChannelId, Priority)>, Error> {
+        let rows = sqlx::query!(
+            "SELECT channel_id, priority FROM tickets WHERE server_id = $1 AND is_open
+            ORDER BY ticket_id",
+            guild_id.to_db()
+        )
+        .fetch_all(self)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| Ok((ChannelId::from_db(row.channel_id), row.priority.parse()?)))
+            .collect()
+    }
+
+    async fn open_ticket_channels(&mut self, guild_id: GuildId) -> Result<Vec<ChannelId>, Error> {
+        let channel_ids = sqlx::query_scalar!(
+            "SELECT channel_id FROM tickets WHERE server_id = $1 AND is_open",
+            guild_id.to_db()
+        )
+        .fetch_all(self)
+        .await?;
+
+        Ok(channel_ids.into_iter().map(ChannelId::from_db).collect())
+    }
+
+    async fn record_ticket_message(
+        &mut self,
+        ticket_id: i32,
+        from_staff: bool,
+    ) -> Result<(), Error> {
+        if from_staff {
+            sqlx::query!(
+                "UPDATE tickets SET last_staff_message_at = now(),
+                    first_response_at = COALESCE(first_response_at, now())
+                WHERE ticket_id = $1",
+                ticket_id
+            )
+            .execute(self)
+            .await?;
+        } else {
+            sqlx::query!(
+                "UPDATE tickets SET last_author_message_at = now() WHERE ticket_id = $1",
+                ticket_id
+            )
+            .execute(self)
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn inactive_tickets(&mut self) -> Result<Vec<Ticket>, Error> {
+        sqlx::query_as!(
+            TicketRow,
+            "SELECT t.ticket_id, t.channel_id, t.location_kind, t.server_id, t.author_id,
+                t.subject_id, t.is_open, t.opened_by, t.claimed_by, t.priority,
+                t.greeting_message_id
+            FROM tickets t JOIN servers s ON s.id = t.server_id
+            WHERE t.is_open AND NOT t.pending
+                AND GREATEST(t.opened_at, t.released_at, t.last_author_message_at,
+                    t.last_staff_message_at)
+                    <= now() - s.inactivity_close_minutes * interval '1 minute'
+            ORDER BY t.ticket_id"
+        )
+        .fetch_all(self)
+        .await?
+        .into_iter()
+        .map(Ticket::try_from)
+        .collect()
+    }
+
+    async fn pending_tickets(&mut self) -> Result<Vec<Ticket>, Error> {
+        sqlx::query_as!(
+            TicketRow,
+            "SELECT ticket_id, channel_id, location_kind, server_id, author_id, subject_id, is_open,
+                opened_by, claimed_by, priority, greeting_message_id
+            FROM tickets WHERE pending AND is_open ORDER BY ticket_id"
+        )
+        .fetch_all(self)
+        .await?
+        .into_iter()
+        .map(Ticket::try_from)
+        .collect()
+    }
+
+    async fn release_pending_ticket(&mut self, ticket_id: i32) -> Result<(), Error> {
+        sqlx::query!(
+            "UPDATE tickets SET pending = false, released_at = now(), helpers_pinged_at = now()
+            WHERE ticket_id = $1",
+            ticket_id
+        )
+        .execute(self)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn overdue_tickets(&mut self, reminder: Reminder) -> Result<Vec<Ticket>, Error> {
+        let rows = match reminder {
+            Reminder::Unclaimed => {
+                sqlx::query_as!(
+                    TicketRow,
+                    "SELECT t.ticket_id, t.channel_id, t.location_kind, t.server_id, t.author_id,
+                        t.subject_id, t.is_open, t.opened_by, t.claimed_by, t.priority,
+                        t.greeting_message_id
+                    FROM tickets t JOIN servers s ON s.id = t.server_id
+                    WHERE t.is_open AND NOT t.pending AND t.claimed_by IS NULL
+                        AND t.helpers_pinged_at IS NULL AND t.first_response_at IS NULL
+                        AND COALESCE(t.released_at, t.opened_at)
+                            <= now() - s.sla_unclaimed_minutes * interval '1 minute'
+                    ORDER BY t.ticket_id"
+                )
+                .fetch_all(self)
+                .await?
+            }
+            Reminder::Escalation => {
+                sqlx::query_as!(
+                    TicketRow,
+                    "SELECT t.ticket_id, t.channel_id, t.location_kind, t.server_id, t.author_id,
+                        t.subject_id, t.is_open, t.opened_by, t.claimed_by, t.priority,
+                        t.greeting_message_id
+                    FROM tickets t JOIN servers s ON s.id = t.server_id
+                    WHERE t.is_open AND NOT t.pending AND t.claimed_by IS NULL
+                        AND t.moderators_pinged_at IS NULL AND t.first_response_at IS NULL
+                        AND COALESCE(t.released_at, t.opened_at)
+                            <= now() - s.sla_escalation_minutes * interval '1 minute'
+                    ORDER BY t.ticket_id"
+                )
+                .fetch_all(self)
+                .await?
+            }
+            Reminder::Unanswered => {
+                sqlx::query_as!(
+                    TicketRow,
+                    "SELECT t.ticket_id, t.channel_id, t.location_kind, t.server_id, t.author_id,
+                        t.subject_id, t.is_open, t.opened_by, t.claimed_by, t.priority,
+                        t.greeting_message_id
+                    FROM tickets t JOIN servers s ON s.id = t.server_id
+                    WHERE t.is_open AND t.claimed_by IS NOT NULL
+                        AND t.last_author_message_at <= now() - s.sla_reply_minutes * interval '1 minute'
+                        AND (t.last_staff_message_at IS NULL
+                            OR t.last_staff_message_at < t.last_author_message_at)
+                        AND (t.helper_reminded_at IS NULL
+                            OR t.helper_reminded_at < t.last_author_message_at)
+                    ORDER BY t.ticket_id"
+                )
+                .fetch_all(self)
+                .await?
+            }
+        };
+
+        rows.into_iter().map(Ticket::try_from).collect()
+    }
+
+    async fn record_reminder(&mut self, ticket_id: i32, reminder: Reminder) -> Result<(), Error> {
+        match reminder {
+            Reminder::Unclaimed => {
+                sqlx::query!(
+                    "UPDATE tickets SET helpers_pinged_at = now() WHERE ticket_id = $1",
+                    ticket_id
+                )
+                .execute(self)
+                .await?
+            }
+            Reminder::Escalation => {
+                sqlx::query!(
+                    "UPDATE tickets SET moderators_pinged_at = now() WHERE ticket_id = $1",
+                    ticket_id
+                )
+                .execute(self)
+                .await?
+            }
+            Reminder::Unanswered => {
+                sqlx::query!(
+                    "UPDATE tickets SET helper_reminded_at = now() WHERE ticket_id = $1",
+                    ticket_id
+                )
+                .execute(self)
+                .await?
+            }
+        };
+
+        Ok(())
+    }
+
+    async fn close_ticket(
+        &mut self,
+        ticket_id: i32,
+        closed_by: UserId,
+        reason: &str,
+    ) -> Result<bool, Error> {
+        let result = sqlx::query!(
+            "UPDATE tickets SET is_open = false, closed_at = now(), closed_by = $2, close_reason = $3
+            WHERE ticket_id = $1 AND is_open",
+            ticket_id,
+            closed_by.to_db(),
+            reason
+        )
+        .execute(self)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn set_duty(
+        &mut self,
+        guild_id: GuildId,
+        helper_id: UserId,
+        on_duty: bool,
+    ) -> Result<(), Error> {
+        sqlx::query!(
+            "INSERT INTO helper_duty (server_id, user_id, on_duty) VALUES ($1, $2, $3)
+            ON CONFLICT (server_id, user_id) DO UPDATE SET on_duty = excluded.on_duty",
+            guild_id.to_db(),
+            helper_id.to_db(),
+            on_duty
+        )
+        .execute(self)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn duty_helpers(
+        &mut self,
+        guild_id: GuildId,
+        mode: AssignMode,
+    ) -> Result<Vec<UserId>, Error> {
+        let helpers = sqlx::query_scalar!(
+            "SELECT d.user_id FROM helper_duty d
+            WHERE d.server_id = $1 AND d.on_duty
+            ORDER BY
+                CASE WHEN $2 = 'least_busy' THEN (
+                    SELECT COUNT(*) FROM tickets t
+                    WHERE t.server_id = d.server_id AND t.claimed_by = d.user_id AND t.is_open
+                ) ELSE 0 END,
+                d.last_assigned_at NULLS FIRST,
+                d.user_id",
+            guild_id.to_db(),
+            mode.as_str()
+        )
+        .fetch_all(self)
+        .await?;
+
+        Ok(helpers.into_iter().map(UserId::from_db).collect())
+    }
+
+    async fn record_assignment(
+        &mut self,
+        guild_id: GuildId,
+        helper_id: UserId,
+    ) -> Result<(), Error> {
+        sqlx::query!(
+            "UPDATE helper_duty SET last_assigned_at = now() WHERE server_id = $1 AND user_id = $2",
+            guild_id.to_db(),
+            helper_id.to_db()
+        )
+        .execute(self)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn ticket_totals(&mut self, guild_id: GuildId) -> Result<TicketTotals, Error> {
+        let totals = sqlx::query_as!(
+            TicketTotals,
+            r#"SELECT
+                COUNT(*) AS "total!",
+                COUNT(*) FILTER (WHERE is_open) AS "open!",
+                COUNT(*) FILTER (WHERE claimed_by IS NOT NULL) AS "claimed!",
+                (SELECT AVG(rating)::float8 FROM ticket_feedback WHERE server_id = $1) AS average_rating,
+                (SELECT COUNT(*) FROM ticket_feedback WHERE server_id = $1) AS "ratings!",
+                AVG(EXTRACT(EPOCH FROM first_response_at - opened_at))::float8
+                    AS average_first_response
+            FROM tickets
+            WHERE server_id = $1"#,
+            guild_id.to_db()
+        )
+        .fetch_one(self)
+        .await?;
+
+        Ok(totals)
+    }
+
+    async fn subject_stats(&mut self, guild_id: GuildId) -> Result<Vec<SubjectStats>, Error> {
+        let rows = sqlx::query!(
+            r#"SELECT
+                s.name,
+                s.channel_id,
+                COUNT(DISTINCT t.ticket_id) FILTER (WHERE t.is_open) AS "open_tickets!",
+                COUNT(DISTINCT t.ticket_id) AS "total_tickets!",
+                AVG(f.rating)::float8 AS average_rating,
+                COUNT(f.rating) AS "ratings!"
+            FROM subjects s
+            LEFT JOIN tickets t ON t.subject_id = s.id AND t.server_id = s.server_id
+            LEFT JOIN ticket_feedback f ON f.ticket_id = t.ticket_id
+            WHERE s.server_id = $1
+            GROUP BY s.id, s.server_id
+            ORDER BY s.name"#,
+            guild_id.to_db()
+        )
+        .fetch_all(self)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| SubjectStats {
+                name: row.name,
+                channel_id: ChannelId::from_db(row.channel_id),
+                open_tickets: row.open_tickets,
+                total_tickets: row.total_tickets,
+                average_rating: row.average_rating,
+                ratings: row.ratings,
+            })
+            .collect())
+    }
+
+    async fn helper_stats(&mut self, guild_id: GuildId) -> Result<Vec<HelperStats>, Error> {
+        let rows = sqlx::query!(
+            r#"SELECT
+                t.claimed_by AS "helper_id!",
+                COUNT(*) AS "claimed!",
+                COUNT(*) FILTER (WHERE NOT t.is_open) AS "closed!",
+                AVG(f.rating)::float8 AS average_rating,
+                COUNT(f.rating) AS "ratings!"
+            FROM tickets t
+            LEFT JOIN ticket_feedback f ON f.ticket_id = t.ticket_id
+            WHERE t.server_id = $1 AND t.claimed_by IS NOT NULL
+            GROUP BY t.claimed_by
+            ORDER BY 2 DESC, 4 DESC NULLS LAST"#,
+            guild_id.to_db()
+        )
+        .fetch_all(self)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| HelperStats {
+                helper_id: UserId::from_db(row.helper_id),
+                claimed: row.claimed,
+                closed: row.closed,
+                average_rating: row.average_rating,
+                ratings: row.ratings,
+            })
+            .collect())
+    }
+
+    async fn insert_panel(&mut self, guild_id: GuildId, panel: &NewPanel) -> Result<i32, Error> {
+        let id = sqlx::query_scalar!(
+            "INSERT INTO panels
+                (server_id, name, channel_id, message_id, title, description, button_label, category_id)
+            VALUES ($1, $2, $3, 0, $4, $5, $6, $7)
+            RETURNING id",
+            guild_id.to_db(),
+            panel.name,
+            panel.channel_id.to_db(),
+            panel.title,
+            panel.description,
+            panel.button_label,
+            panel.category_id.map(Snowflake::to_db)
+        )
+        .fetch_one(self)
+        .await?;
+
+        Ok(id)
+    }
+
+    async fn set_panel_message(
+        &mut self,
+        panel_id: i32,
+        message_id: MessageId,
+    ) -> Result<(), Error> {
+        sqlx::query!(
+            "UPDATE panels SET message_id = $2 WHERE id = $1",
+            panel_id,
+            message_id.to_db()
+        )
+        .execute(self)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn panel(&mut self, panel_id: i32) -> Result<Option<Panel>, Error> {
+        let row = sqlx::query!(
+            r#"SELECT p.*, COALESCE(ARRAY_AGG(ps.subject_id) FILTER (WHERE ps.subject_id IS NOT NULL), '{}') AS "subject_ids!"
+            FROM panels p
+            LEFT JOIN panel_subjects ps ON ps.panel_id = p.id
+            WHERE p.id = $1
+            GROUP BY p.id"#,
+            panel_id
+        )
+        .fetch_optional(self)
+        .await?;
+
+        Ok(row.map(|row| Panel {
+            id: row.id,
+            guild_id: GuildId::from_db(row.server_id),
+            name: row.name,
+            channel_id: ChannelId::from_db(row.channel_id),
+            message_id: MessageId::from_db(row.message_id),
+            title: row.title,
+            description: row.description,
+            button_label: row.button_label,
+            category_id: row.category_id.map(ChannelId::from_db),
+            subject_ids: row.subject_ids.into_iter().map(|id| id as u64).collect(),
+        }))
+    }
+
+    async fn panel_id_by_message(
+        &mut self,
+        channel_id: ChannelId,
+        message_id: MessageId,
+    ) -> Result<Option<i32>, Error> {
+        let id = sqlx::query_scalar!(
+            "SELECT id FROM panels WHERE channel_id = $1 AND message_id = $2",
+            channel_id.to_db(),
+            message_id.to_db()
+        )
+        .fetch_optional(self)
+        .await?;
+
+        Ok(id)
+    }
+
+    async fn panel_id_by_name(
+        &mut self,
+        guild_id: GuildId,
+        name: &str,
+    ) -> Result<Option<i32>, Error> {
+        let id = sqlx::query_scalar!(
+            "SELECT id FROM panels WHERE server_id = $1 AND name = $2",
+            guild_id.to_db(),
+            name
+        )
+        .fetch_optional(self)
+        .await?;
+
+        Ok(id)
+    }
+
+    async fn panel_ids(&mut self, guild_id: GuildId) -> Result<Vec<i32>, Error> {
+        let ids = sqlx::query_scalar!(
+            "SELECT id FROM panels WHERE server_id = $1 ORDER BY name",
+            guild_id.to_db()
+        )
+        .fetch_all(self)
+        .await?;
+
+        Ok(ids)
+    }
+
+    async fn panel_messages(&mut self, guild_id: GuildId) -> Result<Vec<MessageId>, Error> {
+        let message_ids = sqlx::query_scalar!(
+            "SELECT message_id FROM panels WHERE server_id = $1",
+            guild_id.to_db()
+        )
+        .fetch_all(self)
+        .await?;
+
+        Ok(message_ids.into_iter().map(MessageId::from_db).collect())
+    }
+
+    async fn update_panel(&mut self, panel: &Panel) -> Result<(), Error> {
+        sqlx::query!(
+            "UPDATE panels SET title = $2, description = $3, button_label = $4, category_id = $5
+            WHERE id = $1",
+            panel.id,
+            panel.title,
+            panel.description,
+            panel.button_label,
+            panel.category_id.map(Snowflake::to_db)
+        )
+        .execute(self)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn delete_panel(&mut self, panel_id: i32) -> Result<(), Error> {
+        sqlx::query!("DELETE FROM panels WHERE id = $1", panel_id)
+            .execute(self)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn set_panel_subjects(
+        &mut self,
+        panel: &Panel,
+        subject_ids: &[u64],
+    ) -> Result<(), Error> {
+        sqlx::query!("DELETE FROM panel_subjects WHERE panel_id = $1", panel.id)
+            .execute(&mut *self)
+            .await?;
+
+        for subject_id in subject_ids {
+            sqlx::query!(
+                "INSERT INTO panel_subjects (panel_id, subject_id, server_id) VALUES ($1, $2, $3)",
+                panel.id,
+                *subject_id as i64,
+                panel.guild_id.to_db()
+            )
+            .execute(&mut *self)
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn template(
+        &mut self,
+        guild_id: GuildId,
+        kind: TemplateKind,
+    ) -> Result<Option<String>, Error> {
+        let content = sqlx::query_scalar!(
+            "SELECT content FROM templates WHERE server_id = $1 AND kind = $2",
+            guild_id.to_db(),
+            kind.as_str()
+        )
+        .fetch_optional(self)
+        .await?;
+
+        Ok(content)
+    }
+
+    async fn set_template(
+        &mut self,
+        guild_id: GuildId,
+        kind: TemplateKind,
+        content: &str,
+    ) -> Result<(), Error> {
+        sqlx::query!(
+            "INSERT INTO templates (server_id, kind, content) VALUES ($1, $2, $3)
+            ON CONFLICT (server_id, kind) DO UPDATE SET content = EXCLUDED.content",
+            guild_id.to_db(),
+            kind.as_str(),
+            content
+        )
+        .execute(self)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn delete_template(
+        &mut self,
+        guild_id: GuildId,
+        kind: TemplateKind,
+    ) -> Result<(), Error> {
+        sqlx::query!(
+            "DELETE FROM templates WHERE server_id = $1 AND kind = $2",
+            guild_id.to_db(),
+            kind.as_str()
+        )
+        .execute(self)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn rate_ticket(
+        &mut self,
+        ticket_id: i32,
+        author_id: UserId,
+        rating: i16,
+    ) -> Result<bool, Error> {
+        let stored = sqlx::query!(
+            "INSERT INTO ticket_feedback (ticket_id, server_id, helper_id, rating)
+            SELECT ticket_id, server_id, claimed_by, $3 FROM tickets
+            WHERE ticket_id = $1 AND author_id = $2 AND NOT is_open
+            ON CONFLICT (ticket_id) DO UPDATE SET rating = EXCLUDED.rating",
+            ticket_id,
+            author_id.to_db(),
+            rating
+        )
+        .execute(self)
+        .await?
+        .rows_affected();
+
+        Ok(stored > 0)
+    }
+
+    async fn comment_ticket(
+        &mut self,
+        ticket_id: i32,
+        author_id: UserId,
+        comment: &str,
+    ) -> Result<(), Error> {
+        sqlx::query!(
+            "UPDATE ticket_feedback SET comment = $3
+            WHERE ticket_id = $1
+            AND EXISTS (SELECT 1 FROM tickets WHERE ticket_id = $1 AND author_id = $2)",
+            ticket_id,
+            author_id.to_db(),
+            comment
+        )
+        .execute(self)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn insert_category(
+        &mut self,
+        category_id: ChannelId,
+        guild_id: GuildId,
+        state: CategoryState,
+        base_id: ChannelId,
+        position: i16,
+    ) -> Result<(), Error> {
+        sqlx::query!(
+            "INSERT INTO ticket_categories (category_id, server_id, state, base_category_id, position)
+            VALUES ($1, $2, $3, $4, $5)",
+            category_id.to_db(),
+            guild_id.to_db(),
+            state.as_str(),
+            base_id.to_db(),
+            position
+        )
+        .execute(self)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn delete_category(&mut self, category_id: ChannelId) -> Result<(), Error> {
+        sqlx::query!(
+            "DELETE FROM ticket_categories WHERE category_id = $1",
+            category_id.to_db()
+        )
+        .execute(self)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn categories(
+        &mut self,
+        guild_id: GuildId,
+        state: CategoryState,
+        base_id: Option<ChannelId>,
+    ) -> Result<Vec<(ChannelId, i16)>, Error> {
+        let rows = sqlx::query!(
+            "SELECT category_id, position FROM ticket_categories
+            WHERE server_id = $1 AND state = $2 AND ($3::bigint IS NULL OR base_category_id = $3)
+            ORDER BY position",
+            guild_id.to_db(),
+            state.as_str(),
+            base_id.map(ChannelId::to_db)
+        )
+        .fetch_all(self)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| (ChannelId::from_db(row.category_id), row.position))
+            .collect())
+    }
+
+    async fn add_participant(
+        &mut self,
+        ticket_id: i32,
+        user_id: UserId,
+        added_by: UserId,
+    ) -> Result<bool, Error> {
+        let inserted = sqlx::query!(
+            "INSERT INTO ticket_participants (ticket_id, user_id, added_by) VALUES ($1, $2, $3)
+            ON CONFLICT DO NOTHING",
+            ticket_id,
+            user_id.to_db(),
+            added_by.to_db()
+        )
+        .execute(self)
+        .await?
+        .rows_affected();
+
+        Ok(inserted > 0)
+    }
+
+    async fn remove_participant(&mut self, ticket_id: i32, user_id: UserId) -> Result<bool, Error> {
+        let removed = sqlx::query!(
+            "DELETE FROM ticket_participants WHERE ticket_id = $1 AND user_id = $2",
+            ticket_id,
+            user_id.to_db()
+        )
+        .execute(self)
+        .await?
+        .rows_affected();
+
+        Ok(removed > 0)
+    }
+
+    async fn participants(&mut self, ticket_id: i32) -> Result<Vec<UserId>, Error> {
+        let user_ids = sqlx::query_scalar!(
+            "SELECT user_id FROM ticket_participants WHERE ticket_id = $1 ORDER BY added_at",
+            ticket_id
+        )
+        .fetch_all(self)
+        .await?;
+
+        Ok(user_ids.into_iter().map(UserId::from_db).collect())
+    }
+}
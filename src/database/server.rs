@@ -0,0 +1,216 @@
+//! The servers the bot is set up in
+
+use std::time::Duration;
+
+use poise::serenity_prelude::{ChannelId, GuildId, RoleId};
+
+use crate::{
+    handler::{Error, DEFAULT_PREFIX},
+    i18n::Locale,
+    tickets::{
+        assign::AssignMode,
+        location::TicketMode,
+        office_hours::{ClosedMode, Schedule, ServerTimeZone},
+        sla::{self, SlaThresholds},
+    },
+};
+
+use super::{Connection, Snowflake, Storage};
+
+/// The settings of a server, chosen during the setup and with the config commands
+#[derive(Clone, Debug)]
+pub struct Server {
+    pub id: GuildId,
+    pub ticket_channel_id: ChannelId,
+    pub unclaimed_category_id: ChannelId,
+    pub claimed_category_id: ChannelId,
+    pub helper_role_id: RoleId,
+    pub moderator_role_id: RoleId,
+    pub log_channel_id: ChannelId,
+    pub ticket_mode: TicketMode,
+    pub locale: Locale,
+    pub prefix: String,
+    pub assign_mode: AssignMode,
+    /// When the tickets are staffed, always if `None`
+    pub office_hours: Option<Schedule>,
+    /// The time zone the office hours are read in
+    pub time_zone: ServerTimeZone,
+    pub closed_mode: ClosedMode,
+    pub sla: SlaThresholds,
+    /// How long a ticket can go without any message before it is closed, never if `None`
+    pub inactivity_close: Option<Duration>,
+}
+
+/// The settings of a server being set up, the others keep their default value
+pub struct NewServer {
+    pub id: GuildId,
+    pub ticket_channel_id: ChannelId,
+    pub unclaimed_category_id: ChannelId,
+    pub claimed_category_id: ChannelId,
+    pub helper_role_id: RoleId,
+    pub moderator_role_id: RoleId,
+    pub log_channel_id: ChannelId,
+}
+
+/// A row of the `servers` table, as read by the backends
+pub(super) struct ServerRow {
+    pub(super) id: i64,
+    pub(super) ticket_channel_id: i64,
+    pub(super) unclaimed_category_id: i64,
+    pub(super) claimed_category_id: i64,
+    pub(super) helper_role_id: i64,
+    pub(super) moderator_role_id: i64,
+    pub(super) log_channel_id: i64,
+    pub(super) ticket_mode: String,
+    pub(super) locale: String,
+    pub(super) prefix: String,
+    pub(super) assign_mode: String,
+    pub(super) office_hours: Option<String>,
+    pub(super) time_zone: String,
+    pub(super) closed_mode: String,
+    pub(super) sla_unclaimed_minutes: Option<i64>,
+    pub(super) sla_escalation_minutes: Option<i64>,
+    pub(super) sla_reply_minutes: Option<i64>,
+    pub(super) inactivity_close_minutes: Option<i64>,
+}
+
+impl TryFrom<ServerRow> for Server {
+    type Error = Error;
+
+    fn try_from(row: ServerRow) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: GuildId::from_db(row.id),
+            ticket_channel_id: ChannelId::from_db(row.ticket_channel_id),
+            unclaimed_category_id: ChannelId::from_db(row.unclaimed_category_id),
+            claimed_category_id: ChannelId::from_db(row.claimed_category_id),
+            helper_role_id: RoleId::from_db(row.helper_role_id),
+            moderator_role_id: RoleId::from_db(row.moderator_role_id),
+            log_channel_id: ChannelId::from_db(row.log_channel_id),
+            ticket_mode: row.ticket_mode.parse()?,
+            locale: row.locale.parse().unwrap_or_default(),
+            prefix: row.prefix,
+            assign_mode: row.assign_mode.parse()?,
+            office_hours: row.office_hours.map(|hours| hours.parse()).transpose()?,
+            time_zone: row.time_zone.parse()?,
+            closed_mode: row.closed_mode.parse()?,
+            sla: SlaThresholds {
+                unclaimed: sla::from_minutes(row.sla_unclaimed_minutes),
+                escalation: sla::from_minutes(row.sla_escalation_minutes),
+                reply: sla::from_minutes(row.sla_reply_minutes),
+            },
+            inactivity_close: sla::from_minutes(row.inactivity_close_minutes),
+        })
+    }
+}
+
+/// Access to the `servers` table
+pub struct ServerRepo;
+
+impl ServerRepo {
+    /// Returns the server, `None` if it is not set up
+    pub async fn find(conn: &mut Connection, guild_id: GuildId) -> Result<Option<Server>, Error> {
+        conn.find_server(guild_id).await
+    }
+
+    /// Returns the server, failing if it is not set up
+    pub async fn get(conn: &mut Connection, guild_id: GuildId) -> Result<Server, Error> {
+        Self::find(conn, guild_id).await?.ok_or(Error::NotSetUp)
+    }
+
+    /// Returns whether the server is set up
+    pub async fn exists(conn: &mut Connection, guild_id: GuildId) -> Result<bool, Error> {
+        conn.server_exists(guild_id).await
+    }
+
+    /// Returns the prefix of the prefix commands in the server, the default prefix if it is not
+    /// set up
+    pub async fn prefix(conn: &mut Connection, guild_id: GuildId) -> Result<String, Error> {
+        let prefix = conn.server_prefix(guild_id).await?;
+        Ok(prefix.unwrap_or_else(|| DEFAULT_PREFIX.to_string()))
+    }
+
+    /// Saves a server being set up
+    pub async fn insert(conn: &mut Connection, server: &NewServer) -> Result<(), Error> {
+        conn.insert_server(server).await
+    }
+
+    /// Deletes a server and returns its last settings, `None` if it was not set up
+    pub async fn delete(conn: &mut Connection, guild_id: GuildId) -> Result<Option<Server>, Error> {
+        conn.delete_server(guild_id).await
+    }
+
+    pub async fn set_prefix(
+        conn: &mut Connection,
+        guild_id: GuildId,
+        prefix: &str,
+    ) -> Result<(), Error> {
+        conn.set_server_prefix(guild_id, prefix).await
+    }
+
+    pub async fn set_locale(
+        conn: &mut Connection,
+        guild_id: GuildId,
+        locale: Locale,
+    ) -> Result<(), Error> {
+        conn.set_server_locale(guild_id, locale).await
+    }
+
+    pub async fn set_ticket_mode(
+        conn: &mut Connection,
+        guild_id: GuildId,
+        mode: TicketMode,
+    ) -> Result<(), Error> {
+        conn.set_server_ticket_mode(guild_id, mode).await
+    }
+
+    pub async fn set_assign_mode(
+        conn: &mut Connection,
+        guild_id: GuildId,
+        mode: AssignMode,
+    ) -> Result<(), Error> {
+        conn.set_server_assign_mode(guild_id, mode).await
+    }
+
+    /// Changes the office hours of the server, `None` to always accept tickets
+    pub async fn set_office_hours(
+        conn: &mut Connection,
+        guild_id: GuildId,
+        office_hours: Option<&Schedule>,
+    ) -> Result<(), Error> {
+        conn.set_server_office_hours(guild_id, office_hours).await
+    }
+
+    pub async fn set_time_zone(
+        conn: &mut Connection,
+        guild_id: GuildId,
+        time_zone: ServerTimeZone,
+    ) -> Result<(), Error> {
+        conn.set_server_time_zone(guild_id, time_zone).await
+    }
+
+    pub async fn set_closed_mode(
+        conn: &mut Connection,
+        guild_id: GuildId,
+        mode: ClosedMode,
+    ) -> Result<(), Error> {
+        conn.set_server_closed_mode(guild_id, mode).await
+    }
+
+    /// Changes the delays after which the reminders of the tickets are sent
+    pub async fn set_sla(
+        conn: &mut Connection,
+        guild_id: GuildId,
+        sla: &SlaThresholds,
+    ) -> Result<(), Error> {
+        conn.set_server_sla(guild_id, sla).await
+    }
+
+    /// Changes how long a ticket can go without any message, `None` to never close it
+    pub async fn set_inactivity_close(
+        conn: &mut Connection,
+        guild_id: GuildId,
+        threshold: Option<Duration>,
+    ) -> Result<(), Error> {
+        conn.set_server_inactivity_close(guild_id, threshold).await
+    }
+}
@@ -0,0 +1,44 @@
+//! Storage of the Discord IDs (snowflakes) in the `bigint` columns of the database
+
+use poise::serenity_prelude::{ChannelId, GuildId, MessageId, RoleId, UserId};
+
+/// A Discord ID, stored as a signed integer since Postgres has no unsigned type
+///
+/// Snowflakes fit in 63 bits, so the conversion never loses information.
+pub trait Snowflake: Sized {
+    /// Returns the ID as stored in the database
+    fn to_db(self) -> i64;
+
+    /// Builds the ID from its database representation
+    fn from_db(id: i64) -> Self;
+}
+
+macro_rules! impl_snowflake {
+    ($($id:ty),*) => {
+        $(
+            impl Snowflake for $id {
+                fn to_db(self) -> i64 {
+                    self.get() as i64
+                }
+
+                fn from_db(id: i64) -> Self {
+                    Self::new(id as u64)
+                }
+            }
+        )*
+    };
+}
+
+impl_snowflake!(ChannelId, GuildId, MessageId, RoleId, UserId);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snowflake_round_trip() {
+        let id = UserId::new(1_234_567_890_123_456_789);
+        assert_eq!(id.to_db(), 1_234_567_890_123_456_789);
+        assert_eq!(UserId::from_db(id.to_db()), id);
+    }
+}
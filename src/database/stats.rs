@@ -0,0 +1,62 @@
+//! Statistics computed over the tickets of a server and their ratings
+
+use poise::serenity_prelude::{ChannelId, GuildId, UserId};
+
+use crate::handler::Error;
+
+use super::{Connection, Storage};
+
+/// The ticket counts of a server
+pub struct TicketTotals {
+    pub total: i64,
+    pub open: i64,
+    pub claimed: i64,
+    pub average_rating: Option<f64>,
+    pub ratings: i64,
+    /// The average time before the first answer of the staff, in seconds
+    pub average_first_response: Option<f64>,
+}
+
+/// The tickets opened about a subject
+pub struct SubjectStats {
+    pub name: String,
+    pub channel_id: ChannelId,
+    pub open_tickets: i64,
+    pub total_tickets: i64,
+    pub average_rating: Option<f64>,
+    pub ratings: i64,
+}
+
+/// The tickets claimed by a helper
+pub struct HelperStats {
+    pub helper_id: UserId,
+    pub claimed: i64,
+    pub closed: i64,
+    pub average_rating: Option<f64>,
+    pub ratings: i64,
+}
+
+/// Access to the statistics
+pub struct StatsRepo;
+
+impl StatsRepo {
+    pub async fn totals(conn: &mut Connection, guild_id: GuildId) -> Result<TicketTotals, Error> {
+        conn.ticket_totals(guild_id).await
+    }
+
+    /// Returns the statistics of every subject of the server, sorted by name
+    pub async fn subjects(
+        conn: &mut Connection,
+        guild_id: GuildId,
+    ) -> Result<Vec<SubjectStats>, Error> {
+        conn.subject_stats(guild_id).await
+    }
+
+    /// Returns the statistics of the helpers who claimed a ticket, the most active first
+    pub async fn helpers(
+        conn: &mut Connection,
+        guild_id: GuildId,
+    ) -> Result<Vec<HelperStats>, Error> {
+        conn.helper_stats(guild_id).await
+    }
+}
@@ -0,0 +1,310 @@
+//! The queries a storage backend has to answer
+//!
+//! Each backend implements [`Storage`] on its connection type, so that the repositories and the
+//! ticket logic above them are shared by every backend.
+
+use std::time::Duration;
+
+use poise::serenity_prelude::{ChannelId, GuildId, MessageId, RoleId, UserId};
+
+use crate::{
+    handler::Error,
+    i18n::Locale,
+    tickets::{
+        assign::AssignMode,
+        categories::CategoryState,
+        location::TicketMode,
+        office_hours::{ClosedMode, Schedule, ServerTimeZone},
+        panels::{NewPanel, Panel},
+        priority::Priority,
+        sla::{Reminder, SlaThresholds},
+        templates::TemplateKind,
+    },
+};
+
+use super::{
+    HelperStats, NewServer, NewTicket, QueuedTicket, Server, Subject, SubjectStats, Ticket,
+    TicketTotals,
+};
+
+/// The queries of the bot, answered by a connection to the database
+pub(crate) trait Storage {
+    // Servers
+
+    async fn find_server(&mut self, guild_id: GuildId) -> Result<Option<Server>, Error>;
+    async fn server_exists(&mut self, guild_id: GuildId) -> Result<bool, Error>;
+    async fn server_prefix(&mut self, guild_id: GuildId) -> Result<Option<String>, Error>;
+    async fn insert_server(&mut self, server: &NewServer) -> Result<(), Error>;
+    /// Deletes the server and returns its last settings
+    async fn delete_server(&mut self, guild_id: GuildId) -> Result<Option<Server>, Error>;
+    async fn set_server_prefix(&mut self, guild_id: GuildId, prefix: &str) -> Result<(), Error>;
+    async fn set_server_locale(&mut self, guild_id: GuildId, locale: Locale) -> Result<(), Error>;
+    async fn set_server_ticket_mode(
+        &mut self,
+        guild_id: GuildId,
+        mode: TicketMode,
+    ) -> Result<(), Error>;
+    async fn set_server_assign_mode(
+        &mut self,
+        guild_id: GuildId,
+        mode: AssignMode,
+    ) -> Result<(), Error>;
+    async fn set_server_office_hours(
+        &mut self,
+        guild_id: GuildId,
+        office_hours: Option<&Schedule>,
+    ) -> Result<(), Error>;
+    async fn set_server_time_zone(
+        &mut self,
+        guild_id: GuildId,
+        time_zone: ServerTimeZone,
+    ) -> Result<(), Error>;
+    async fn set_server_closed_mode(
+        &mut self,
+        guild_id: GuildId,
+        mode: ClosedMode,
+    ) -> Result<(), Error>;
+    /// Returns the servers with office hours
+    async fn scheduled_servers(&mut self) -> Result<Vec<Server>, Error>;
+    async fn set_server_sla(&mut self, guild_id: GuildId, sla: &SlaThresholds)
+        -> Result<(), Error>;
+    async fn set_server_inactivity_close(
+        &mut self,
+        guild_id: GuildId,
+        threshold: Option<Duration>,
+    ) -> Result<(), Error>;
+
+    // Subjects
+
+    async fn subjects(&mut self, guild_id: GuildId) -> Result<Vec<Subject>, Error>;
+    async fn subject(&mut self, guild_id: GuildId, id: u64) -> Result<Option<Subject>, Error>;
+    async fn subject_exists(&mut self, guild_id: GuildId, name: &str) -> Result<bool, Error>;
+    async fn insert_subject(
+        &mut self,
+        guild_id: GuildId,
+        name: &str,
+        channel_id: ChannelId,
+    ) -> Result<(), Error>;
+    /// Sets the role required to take the tickets of the subject, returns whether it exists
+    async fn set_subject_helper_role(
+        &mut self,
+        guild_id: GuildId,
+        name: &str,
+        role_id: Option<RoleId>,
+    ) -> Result<bool, Error>;
+    /// Returns the role required to take the tickets of the subject
+    async fn subject_helper_role(
+        &mut self,
+        guild_id: GuildId,
+        id: u64,
+    ) -> Result<Option<RoleId>, Error>;
+    /// Sets the assign mode overriding the one of the server, returns whether the subject exists
+    async fn set_subject_assign_mode(
+        &mut self,
+        guild_id: GuildId,
+        name: &str,
+        mode: Option<AssignMode>,
+    ) -> Result<bool, Error>;
+    /// Returns the assign mode of the subject, `None` when it follows the server
+    async fn subject_assign_mode(
+        &mut self,
+        guild_id: GuildId,
+        id: u64,
+    ) -> Result<Option<AssignMode>, Error>;
+    /// Sets the office hours narrowing the ones of the server, returns whether the subject exists
+    async fn set_subject_office_hours(
+        &mut self,
+        guild_id: GuildId,
+        name: &str,
+        office_hours: Option<&Schedule>,
+    ) -> Result<bool, Error>;
+    async fn subject_office_hours(
+        &mut self,
+        guild_id: GuildId,
+        id: u64,
+    ) -> Result<Option<Schedule>, Error>;
+    /// Deletes the subject and returns whether it existed
+    async fn delete_subject(&mut self, guild_id: GuildId, name: &str) -> Result<bool, Error>;
+
+    // Tickets
+
+    async fn ticket_by_channel(&mut self, channel_id: ChannelId) -> Result<Option<Ticket>, Error>;
+    /// Stores the ticket and returns its ID
+    async fn insert_ticket(&mut self, ticket: &NewTicket) -> Result<i32, Error>;
+    /// Claims the ticket if it is open and unclaimed, returns whether it was claimed
+    async fn claim_ticket(
+        &mut self,
+        channel_id: ChannelId,
+        helper_id: UserId,
+    ) -> Result<bool, Error>;
+    /// Returns the unclaimed tickets, the most urgent first, then the ones
+    /// no staff member answered yet, then the oldest first
+    ///
+    /// The tickets pending until the office hours start are left out, as in `claim_next_ticket`.
+    async fn ticket_queue(&mut self, guild_id: GuildId) -> Result<Vec<QueuedTicket>, Error>;
+    /// Claims the first ticket of the queue whose subject the helper can take, without ever
+    /// giving the same ticket to two helpers
+    async fn claim_next_ticket(
+        &mut self,
+        guild_id: GuildId,
+        helper_id: UserId,
+        role_ids: &[RoleId],
+    ) -> Result<Option<Ticket>, Error>;
+    async fn set_ticket_priority(
+        &mut self,
+        ticket_id: i32,
+        priority: Priority,
+    ) -> Result<(), Error>;
+    /// Returns the priority of the open tickets of the server by channel, the oldest first
+    async fn open_ticket_priorities(
+        &mut self,
+        guild_id: GuildId,
+    ) -> Result<Vec<(ChannelId, Priority)>, Error>;
+    async fn open_ticket_channels(&mut self, guild_id: GuildId) -> Result<Vec<ChannelId>, Error>;
+    /// Records the time of the message, and the first answer of the staff
+    async fn record_ticket_message(
+        &mut self,
+        ticket_id: i32,
+        from_staff: bool,
+    ) -> Result<(), Error>;
+    /// Returns the open tickets of every server without any message for too long, the oldest first
+    async fn inactive_tickets(&mut self) -> Result<Vec<Ticket>, Error>;
+    /// Returns the open tickets of every server waiting for their office hours
+    async fn pending_tickets(&mut self) -> Result<Vec<Ticket>, Error>;
+    /// Releases the ticket, whose helpers are pinged at the same time
+    async fn release_pending_ticket(&mut self, ticket_id: i32) -> Result<(), Error>;
+    /// Returns the open tickets of every server due for the reminder, the oldest first
+    async fn overdue_tickets(&mut self, reminder: Reminder) -> Result<Vec<Ticket>, Error>;
+    /// Records that the reminder was just sent in the ticket
+    async fn record_reminder(&mut self, ticket_id: i32, reminder: Reminder) -> Result<(), Error>;
+    async fn close_ticket(
+        &mut self,
+        ticket_id: i32,
+        closed_by: UserId,
+        reason: &str,
+    ) -> Result<bool, Error>;
+
+    // Duty
+
+    async fn set_duty(
+        &mut self,
+        guild_id: GuildId,
+        helper_id: UserId,
+        on_duty: bool,
+    ) -> Result<(), Error>;
+    /// Returns the helpers on duty, the next one to assign a ticket to first
+    async fn duty_helpers(
+        &mut self,
+        guild_id: GuildId,
+        mode: AssignMode,
+    ) -> Result<Vec<UserId>, Error>;
+    /// Records that a ticket was just assigned to the helper
+    async fn record_assignment(
+        &mut self,
+        guild_id: GuildId,
+        helper_id: UserId,
+    ) -> Result<(), Error>;
+
+    // Statistics
+
+    async fn ticket_totals(&mut self, guild_id: GuildId) -> Result<TicketTotals, Error>;
+    /// Returns the statistics of every subject, sorted by name
+    async fn subject_stats(&mut self, guild_id: GuildId) -> Result<Vec<SubjectStats>, Error>;
+    /// Returns the statistics of the helpers, the most active first
+    async fn helper_stats(&mut self, guild_id: GuildId) -> Result<Vec<HelperStats>, Error>;
+
+    // Panels
+
+    /// Stores the panel, before its message is sent, and returns its ID
+    async fn insert_panel(&mut self, guild_id: GuildId, panel: &NewPanel) -> Result<i32, Error>;
+    async fn set_panel_message(
+        &mut self,
+        panel_id: i32,
+        message_id: MessageId,
+    ) -> Result<(), Error>;
+    async fn panel(&mut self, panel_id: i32) -> Result<Option<Panel>, Error>;
+    async fn panel_id_by_message(
+        &mut self,
+        channel_id: ChannelId,
+        message_id: MessageId,
+    ) -> Result<Option<i32>, Error>;
+    async fn panel_id_by_name(
+        &mut self,
+        guild_id: GuildId,
+        name: &str,
+    ) -> Result<Option<i32>, Error>;
+    /// Returns the IDs of the panels of the server, sorted by name
+    async fn panel_ids(&mut self, guild_id: GuildId) -> Result<Vec<i32>, Error>;
+    async fn panel_messages(&mut self, guild_id: GuildId) -> Result<Vec<MessageId>, Error>;
+    async fn update_panel(&mut self, panel: &Panel) -> Result<(), Error>;
+    async fn delete_panel(&mut self, panel_id: i32) -> Result<(), Error>;
+    async fn set_panel_subjects(&mut self, panel: &Panel, subject_ids: &[u64])
+        -> Result<(), Error>;
+
+    // Templates
+
+    async fn template(
+        &mut self,
+        guild_id: GuildId,
+        kind: TemplateKind,
+    ) -> Result<Option<String>, Error>;
+    async fn set_template(
+        &mut self,
+        guild_id: GuildId,
+        kind: TemplateKind,
+        content: &str,
+    ) -> Result<(), Error>;
+    async fn delete_template(&mut self, guild_id: GuildId, kind: TemplateKind)
+        -> Result<(), Error>;
+
+    // Feedback
+
+    /// Stores the rating of a closed ticket by its author, returns whether it was stored
+    async fn rate_ticket(
+        &mut self,
+        ticket_id: i32,
+        author_id: UserId,
+        rating: i16,
+    ) -> Result<bool, Error>;
+    async fn comment_ticket(
+        &mut self,
+        ticket_id: i32,
+        author_id: UserId,
+        comment: &str,
+    ) -> Result<(), Error>;
+
+    // Overflow categories
+
+    /// Stores an overflow category, extending the given base category
+    async fn insert_category(
+        &mut self,
+        category_id: ChannelId,
+        guild_id: GuildId,
+        state: CategoryState,
+        base_id: ChannelId,
+        position: i16,
+    ) -> Result<(), Error>;
+    async fn delete_category(&mut self, category_id: ChannelId) -> Result<(), Error>;
+    /// Returns the overflow categories of the given state with their position, in order,
+    /// only the ones extending `base_id` when it is given
+    async fn categories(
+        &mut self,
+        guild_id: GuildId,
+        state: CategoryState,
+        base_id: Option<ChannelId>,
+    ) -> Result<Vec<(ChannelId, i16)>, Error>;
+
+    // Participants
+
+    /// Adds a participant to the ticket, returns whether they were not already part of it
+    async fn add_participant(
+        &mut self,
+        ticket_id: i32,
+        user_id: UserId,
+        added_by: UserId,
+    ) -> Result<bool, Error>;
+    /// Removes a participant from the ticket, returns whether they were part of it
+    async fn remove_participant(&mut self, ticket_id: i32, user_id: UserId) -> Result<bool, Error>;
+    /// Returns the participants of the ticket, in the order they were added
+    async fn participants(&mut self, ticket_id: i32) -> Result<Vec<UserId>, Error>;
+}
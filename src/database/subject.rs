@@ -0,0 +1,99 @@
+//! The subjects members choose from when opening a ticket
+
+use poise::serenity_prelude::{ChannelId, GuildId, RoleId};
+
+use crate::{
+    handler::Error,
+    tickets::{assign::AssignMode, office_hours::Schedule},
+};
+
+use super::{Connection, Storage};
+
+/// Represents a subject
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub struct Subject {
+    /// The ID of the subject, `None` for the default subject offered when nothing matches
+    pub id: Option<u64>,
+    pub name: String,
+}
+
+/// Access to the `subjects` table
+pub struct SubjectRepo;
+
+impl SubjectRepo {
+    /// Returns every subject of the server
+    pub async fn all(conn: &mut Connection, guild_id: GuildId) -> Result<Vec<Subject>, Error> {
+        conn.subjects(guild_id).await
+    }
+
+    /// Returns the subject of the server with the given ID
+    pub async fn get(
+        conn: &mut Connection,
+        guild_id: GuildId,
+        id: u64,
+    ) -> Result<Option<Subject>, Error> {
+        conn.subject(guild_id, id).await
+    }
+
+    /// Returns whether the server has a subject with the given name
+    pub async fn exists(
+        conn: &mut Connection,
+        guild_id: GuildId,
+        name: &str,
+    ) -> Result<bool, Error> {
+        conn.subject_exists(guild_id, name).await
+    }
+
+    /// Adds a subject linked to a channel
+    pub async fn insert(
+        conn: &mut Connection,
+        guild_id: GuildId,
+        name: &str,
+        channel_id: ChannelId,
+    ) -> Result<(), Error> {
+        conn.insert_subject(guild_id, name, channel_id).await
+    }
+
+    /// Restricts the tickets of the subject to the helpers holding the role, or to every helper
+    /// with `None`, returns whether the subject exists
+    pub async fn set_helper_role(
+        conn: &mut Connection,
+        guild_id: GuildId,
+        name: &str,
+        role_id: Option<RoleId>,
+    ) -> Result<bool, Error> {
+        conn.set_subject_helper_role(guild_id, name, role_id).await
+    }
+
+    /// Overrides how the tickets of the subject are assigned, or follows the server with `None`,
+    /// returns whether the subject exists
+    pub async fn set_assign_mode(
+        conn: &mut Connection,
+        guild_id: GuildId,
+        name: &str,
+        mode: Option<AssignMode>,
+    ) -> Result<bool, Error> {
+        conn.set_subject_assign_mode(guild_id, name, mode).await
+    }
+
+    /// Narrows the office hours of the server for the subject, or follows them with `None`,
+    /// returns whether the subject exists
+    pub async fn set_office_hours(
+        conn: &mut Connection,
+        guild_id: GuildId,
+        name: &str,
+        office_hours: Option<&Schedule>,
+    ) -> Result<bool, Error> {
+        conn.set_subject_office_hours(guild_id, name, office_hours)
+            .await
+    }
+
+    /// Deletes the subject with the given name, returns whether it existed
+    pub async fn delete(
+        conn: &mut Connection,
+        guild_id: GuildId,
+        name: &str,
+    ) -> Result<bool, Error> {
+        conn.delete_subject(guild_id, name).await
+    }
+}
@@ -0,0 +1,216 @@
+//! The tickets, open or closed
+
+use std::time::Duration;
+
+use poise::serenity_prelude::{ChannelId, GuildId, MessageId, RoleId, UserId};
+
+use crate::{
+    handler::Error,
+    tickets::{location::TicketLocation, priority::Priority},
+};
+
+use super::{Connection, Snowflake, Storage};
+
+/// A ticket, as stored when its subject is chosen
+#[derive(Clone, Debug)]
+pub struct Ticket {
+    pub id: i32,
+    pub location: TicketLocation,
+    pub guild_id: GuildId,
+    pub author_id: UserId,
+    pub subject_id: Option<u64>,
+    pub is_open: bool,
+    /// The staff member who opened the ticket on behalf of its author
+    pub opened_by: Option<UserId>,
+    pub claimed_by: Option<UserId>,
+    pub priority: Priority,
+    /// The message greeting the author, coloured after the priority
+    pub greeting_message_id: Option<MessageId>,
+}
+
+/// A ticket about to be stored
+pub struct NewTicket {
+    pub location: TicketLocation,
+    pub guild_id: GuildId,
+    pub author_id: UserId,
+    pub subject_id: Option<u64>,
+    pub opened_by: Option<UserId>,
+    pub priority: Priority,
+    pub greeting_message_id: Option<MessageId>,
+    /// Whether the ticket was opened outside office hours, and waits for them to start
+    pub pending: bool,
+}
+
+/// An unclaimed ticket, waiting in the queue of the helpers
+#[derive(Clone, Debug)]
+pub struct QueuedTicket {
+    pub id: i32,
+    pub location: TicketLocation,
+    pub priority: Priority,
+    /// The name of the subject, `None` for the default subject
+    pub subject: Option<String>,
+    /// How long the ticket has been open
+    pub waiting: Duration,
+}
+
+/// A row of the `tickets` table, as read by the backends
+pub(super) struct TicketRow {
+    pub(super) ticket_id: i32,
+    pub(super) channel_id: i64,
+    pub(super) location_kind: String,
+    pub(super) server_id: i64,
+    pub(super) author_id: i64,
+    pub(super) subject_id: Option<i64>,
+    pub(super) is_open: Option<bool>,
+    pub(super) opened_by: Option<i64>,
+    pub(super) claimed_by: Option<i64>,
+    pub(super) priority: String,
+    pub(super) greeting_message_id: Option<i64>,
+}
+
+impl TryFrom<TicketRow> for Ticket {
+    type Error = Error;
+
+    fn try_from(row: TicketRow) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: row.ticket_id,
+            location: TicketLocation::from_db(&row.location_kind, row.channel_id)?,
+            guild_id: GuildId::from_db(row.server_id),
+            author_id: UserId::from_db(row.author_id),
+            subject_id: row.subject_id.map(|id| id as u64),
+            is_open: row.is_open.unwrap_or_default(),
+            opened_by: row.opened_by.map(UserId::from_db),
+            claimed_by: row.claimed_by.map(UserId::from_db),
+            priority: row.priority.parse()?,
+            greeting_message_id: row.greeting_message_id.map(MessageId::from_db),
+        })
+    }
+}
+
+/// A row of the queue, as read by the backends
+pub(super) struct QueuedTicketRow {
+    pub(super) ticket_id: i32,
+    pub(super) channel_id: i64,
+    pub(super) location_kind: String,
+    pub(super) priority: String,
+    pub(super) subject: Option<String>,
+    pub(super) waiting_secs: i64,
+}
+
+impl TryFrom<QueuedTicketRow> for QueuedTicket {
+    type Error = Error;
+
+    fn try_from(row: QueuedTicketRow) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: row.ticket_id,
+            location: TicketLocation::from_db(&row.location_kind, row.channel_id)?,
+            priority: row.priority.parse()?,
+            subject: row.subject,
+            // The clock of the database may be slightly ahead of the one of the ticket
+            waiting: Duration::from_secs(row.waiting_secs.max(0) as u64),
+        })
+    }
+}
+
+/// Access to the `tickets` table
+pub struct TicketRepo;
+
+impl TicketRepo {
+    /// Returns the ticket living in the channel (or thread), whether it is open or not
+    pub async fn by_channel(
+        conn: &mut Connection,
+        channel_id: ChannelId,
+    ) -> Result<Option<Ticket>, Error> {
+        conn.ticket_by_channel(channel_id).await
+    }
+
+    /// Stores a ticket and returns its ID
+    pub async fn insert(conn: &mut Connection, ticket: &NewTicket) -> Result<i32, Error> {
+        conn.insert_ticket(ticket).await
+    }
+
+    /// Records the helper who claimed the ticket of the channel
+    ///
+    /// Returns `false` when the ticket is closed or was already claimed, by this helper or another.
+    pub async fn claim(
+        conn: &mut Connection,
+        channel_id: ChannelId,
+        helper_id: UserId,
+    ) -> Result<bool, Error> {
+        conn.claim_ticket(channel_id, helper_id).await
+    }
+
+    /// Returns the unclaimed tickets of the server, the most urgent first, then the ones
+    /// no staff member answered yet, then the oldest first
+    ///
+    /// The tickets pending until the office hours start are not listed, nor handed out.
+    pub async fn queue(
+        conn: &mut Connection,
+        guild_id: GuildId,
+    ) -> Result<Vec<QueuedTicket>, Error> {
+        conn.ticket_queue(guild_id).await
+    }
+
+    /// Claims the first ticket of the queue the helper can take, given their roles
+    ///
+    /// The ticket is locked while it is claimed, so that two helpers never get the same one.
+    pub async fn claim_next(
+        conn: &mut Connection,
+        guild_id: GuildId,
+        helper_id: UserId,
+        role_ids: &[RoleId],
+    ) -> Result<Option<Ticket>, Error> {
+        conn.claim_next_ticket(guild_id, helper_id, role_ids).await
+    }
+
+    /// Changes the priority of a ticket
+    pub async fn set_priority(
+        conn: &mut Connection,
+        ticket_id: i32,
+        priority: Priority,
+    ) -> Result<(), Error> {
+        conn.set_ticket_priority(ticket_id, priority).await
+    }
+
+    /// Returns the priority of the open tickets of the server by channel, the oldest first
+    pub async fn open_priorities(
+        conn: &mut Connection,
+        guild_id: GuildId,
+    ) -> Result<Vec<(ChannelId, Priority)>, Error> {
+        conn.open_ticket_priorities(guild_id).await
+    }
+
+    /// Returns the channels (or threads) of the open tickets of the server
+    pub async fn open_channels(
+        conn: &mut Connection,
+        guild_id: GuildId,
+    ) -> Result<Vec<ChannelId>, Error> {
+        conn.open_ticket_channels(guild_id).await
+    }
+
+    /// Records a message written in the ticket by its author, or by a staff member
+    pub async fn record_message(
+        conn: &mut Connection,
+        ticket_id: i32,
+        from_staff: bool,
+    ) -> Result<(), Error> {
+        conn.record_ticket_message(ticket_id, from_staff).await
+    }
+
+    /// Returns the open tickets of every server in which nobody wrote for too long
+    pub async fn inactive(conn: &mut Connection) -> Result<Vec<Ticket>, Error> {
+        conn.inactive_tickets().await
+    }
+
+    /// Records the closing of a ticket
+    ///
+    /// Returns `false` when the ticket was already closed.
+    pub async fn close(
+        conn: &mut Connection,
+        ticket_id: i32,
+        closed_by: UserId,
+        reason: &str,
+    ) -> Result<bool, Error> {
+        conn.close_ticket(ticket_id, closed_by, reason).await
+    }
+}
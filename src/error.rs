@@ -0,0 +1,173 @@
+//! This module defines the errors of the bot and how they are reported
+//!
+//! Mistakes of the users and permission denials are answered to the user, while failures of
+//! Discord or of the database are logged and reported in the log channel of the server.
+
+use std::fmt;
+
+use poise::serenity_prelude::{self as serenity, CreateEmbed, CreateMessage, GuildId, Http};
+
+use crate::{
+    database::{Connection, ServerRepo},
+    i18n::{tr, Locale},
+};
+
+/// An error of the bot
+#[derive(Debug)]
+pub enum Error {
+    /// The user made a mistake (wrong channel, invalid input, ...)
+    User(String),
+    /// The user is not allowed to do what they asked
+    Permission(String),
+    /// The server the user asked about is not set up
+    NotSetUp,
+    /// A request to Discord failed, boxed as it is much larger than the other variants
+    Discord(Box<serenity::Error>),
+    /// A query to the database failed
+    Database(sqlx::Error),
+    /// Any other failure of the bot
+    Other(String),
+}
+
+impl Error {
+    /// Creates an error caused by a mistake of the user
+    pub fn user(message: impl Into<String>) -> Self {
+        Self::User(message.into())
+    }
+
+    /// Creates an error caused by a lack of permissions
+    pub fn permission(message: impl Into<String>) -> Self {
+        Self::Permission(message.into())
+    }
+
+    /// Returns whether the error should be shown to the user as is
+    pub fn is_user_facing(&self) -> bool {
+        matches!(self, Self::User(_) | Self::Permission(_) | Self::NotSetUp)
+    }
+
+    /// Returns the message shown to the user, translated when it is not already
+    pub fn user_message(&self, locale: Locale) -> String {
+        match self {
+            Self::NotSetUp => tr(locale, "errors.not_set_up"),
+            error => error.to_string(),
+        }
+    }
+
+    /// Returns whether the error was caused by a user who cannot receive direct messages
+    pub fn is_dm_closed(&self) -> bool {
+        // 50007: Cannot send messages to this user
+        let Self::Discord(error) = self else {
+            return false;
+        };
+
+        matches!(
+            error.as_ref(),
+            serenity::Error::Http(serenity::HttpError::UnsuccessfulRequest(response))
+                if response.error.code == 50007
+        )
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::User(message) | Self::Permission(message) | Self::Other(message) => {
+                f.write_str(message)
+            }
+            Self::NotSetUp => f.write_str("This server is not set up"),
+            Self::Discord(error) => write!(f, "Discord error: {error}"),
+            Self::Database(error) => write!(f, "Database error: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Discord(error) => Some(error.as_ref()),
+            Self::Database(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<serenity::Error> for Error {
+    fn from(error: serenity::Error) -> Self {
+        Self::Discord(Box::new(error))
+    }
+}
+
+impl From<sqlx::Error> for Error {
+    fn from(error: sqlx::Error) -> Self {
+        Self::Database(error)
+    }
+}
+
+impl From<std::fmt::Error> for Error {
+    fn from(error: std::fmt::Error) -> Self {
+        Self::Other(error.to_string())
+    }
+}
+
+impl From<String> for Error {
+    fn from(message: String) -> Self {
+        Self::Other(message)
+    }
+}
+
+impl From<&str> for Error {
+    fn from(message: &str) -> Self {
+        Self::Other(message.to_string())
+    }
+}
+
+/// Reports an internal error in the log channel of the server
+///
+/// Failing to report is only logged, as there is nowhere else to report it.
+pub async fn alert_log_channel(
+    http: &Http,
+    pool: &mut Connection,
+    guild_id: GuildId,
+    context: &str,
+    error: &Error,
+) {
+    let server = match ServerRepo::find(pool, guild_id).await {
+        Ok(Some(server)) => server,
+        Ok(None) => return,
+        Err(error) => {
+            error!("Failed to get the log channel of guild {guild_id}: {error}");
+            return;
+        }
+    };
+
+    let embed = CreateEmbed::new()
+        .title(format!("⚠️ {}", tr(server.locale, "errors.alert_title")))
+        .field(tr(server.locale, "errors.context_field"), context, false)
+        .field(
+            tr(server.locale, "errors.error_field"),
+            error.to_string(),
+            false,
+        )
+        .color(serenity::Colour::RED);
+
+    if let Err(error) = server
+        .log_channel_id
+        .send_message(http, CreateMessage::new().embed(embed))
+        .await
+    {
+        error!("Failed to report an error in guild {guild_id}: {error}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_user_facing_errors() {
+        assert!(Error::user("Invalid channel").is_user_facing());
+        assert!(Error::permission("Not allowed").is_user_facing());
+        assert!(Error::NotSetUp.is_user_facing());
+        assert!(!Error::from("Failed to get guild ID").is_user_facing());
+    }
+}
@@ -1,14 +1,34 @@
-use poise::{Framework, FrameworkOptions};
+use std::{sync::Arc, time::Instant};
+
+use poise::{CreateReply, Framework, FrameworkOptions};
+
+use crate::{
+    cache::ConfigCache,
+    config::Timeouts,
+    database::Pool,
+    error,
+    i18n::{get_locale, tr},
+    metrics::Metrics,
+    shutdown::Shutdown,
+};
 
 mod commands;
 mod events;
 
 // Types used by all command functions
-pub type Error = Box<dyn std::error::Error + Send + Sync>;
+pub use crate::error::Error;
 pub type Context<'a> = poise::Context<'a, Data, Error>;
 
+/// Prefix of the prefix commands when a server did not choose one
+pub const DEFAULT_PREFIX: &str = "$";
+
+#[derive(Clone)]
 pub struct Data {
-    pub pool: sqlx::Pool<sqlx::Postgres>,
+    pub pool: Pool,
+    pub shutdown: Arc<Shutdown>,
+    pub timeouts: Timeouts,
+    pub metrics: Arc<Metrics>,
+    pub cache: Arc<ConfigCache>,
 }
 
 impl Data {}
@@ -17,13 +37,52 @@ async fn on_error(error: poise::FrameworkError<'_, Data, Error>) {
     match error {
         poise::FrameworkError::Setup { error, .. } => panic!("Failed to start bot: {error:?}"),
         poise::FrameworkError::Command { error, ctx, .. } => {
-            println!("Error in command `{}`: {:?}", ctx.command().name, error,);
+            record_latency(ctx).await;
+            report_command_error(ctx, error).await;
         }
         error => {
             if let Err(e) = poise::builtins::on_error(error).await {
-                println!("Error while handling error: {e}");
+                error!("Error while handling error: {e}");
+            }
+        }
+    }
+}
+
+/// Reports the error of a command: mistakes are answered to the user, failures are logged
+/// and reported in the log channel of the server
+async fn report_command_error(ctx: Context<'_>, error: Error) {
+    let command = &ctx.command().qualified_name;
+    let locale = get_locale(ctx).await;
+
+    let content = if error.is_user_facing() {
+        format!("❌ - {}", error.user_message(locale))
+    } else {
+        error!("Error in command `{command}`: {error}");
+        if let Some(guild_id) = ctx.guild_id() {
+            if let Ok(mut pool) = ctx.data().pool.acquire().await {
+                let context = format!("Command `{command}`");
+                error::alert_log_channel(ctx.http(), &mut pool, guild_id, &context, &error).await;
             }
         }
+        format!("❌ - {}", tr(locale, "errors.internal"))
+    };
+
+    let reply = CreateReply::default().content(content).ephemeral(true);
+    if let Err(e) = ctx.send(reply).await {
+        error!("Failed to report error of command `{command}`: {e}");
+    }
+}
+
+/// Records how long the command took, from the start time stored by `pre_command`
+async fn record_latency(ctx: Context<'_>) {
+    let started = ctx
+        .invocation_data::<Instant>()
+        .await
+        .map(|started| *started);
+    if let Some(started) = started {
+        ctx.data()
+            .metrics
+            .command_finished(&ctx.command().qualified_name, started.elapsed());
     }
 }
 
@@ -34,7 +93,8 @@ fn get_functions() -> FrameworkOptions<Data, Error> {
             Box::pin(events::event_handler(ctx, event, framework, data))
         },
         prefix_options: poise::PrefixFrameworkOptions {
-            prefix: Some("$".to_string()),
+            dynamic_prefix: Some(|ctx| Box::pin(get_prefix(ctx))),
+            mention_as_prefix: true,
             edit_tracker: None,
             ..Default::default()
         },
@@ -42,25 +102,37 @@ fn get_functions() -> FrameworkOptions<Data, Error> {
         pre_command: |ctx| {
             Box::pin(async move {
                 info!("Executing command {}...", ctx.command().qualified_name);
+                ctx.set_invocation_data(Instant::now()).await;
             })
         },
         post_command: |ctx| {
             Box::pin(async move {
                 info!("Executed command {}", ctx.command().qualified_name);
+                record_latency(ctx).await;
             })
         },
         ..Default::default()
     }
 }
 
-pub fn get_framework(pool: sqlx::Pool<sqlx::Postgres>) -> poise::Framework<Data, Error> {
+/// Returns the prefix chosen by the server the message was sent in
+async fn get_prefix(ctx: poise::PartialContext<'_, Data, Error>) -> Result<Option<String>, Error> {
+    let Some(guild_id) = ctx.guild_id else {
+        return Ok(Some(DEFAULT_PREFIX.to_string()));
+    };
+
+    let config = ctx.data.cache.get(&ctx.data.pool, guild_id).await?;
+    Ok(Some(config.prefix().to_string()))
+}
+
+pub fn get_framework(data: Data) -> poise::Framework<Data, Error> {
     Framework::builder()
         .options(get_functions())
         .setup(move |ctx, ready, framework| {
             Box::pin(async move {
                 println!("Logged in as {}", ready.user.name);
                 poise::builtins::register_globally(ctx, &framework.options().commands).await?;
-                Ok(Data { pool })
+                Ok(data)
             })
         })
         .build()
@@ -1,33 +1,53 @@
 //! This module regroups the commands supported by the discord bot.
 
-use crate::database::is_server_setup;
+use crate::i18n::localize_commands;
 
 use super::{Context as MyContext, Data, Error as MyError};
 use poise::{serenity_prelude::Error, Command, Context, CreateReply, ReplyHandle};
 
+pub mod panel;
 pub mod server;
+pub mod stats;
 pub mod subject;
+pub mod template;
 pub mod ticket;
 
 /// Get all the commands supported by the bot
 pub fn get() -> Vec<Command<Data, super::Error>> {
-    vec![
+    let mut commands = vec![
+        panel::panel(),
+        server::config::config(),
         server::reset::reset(),
         server::setup::setup(),
+        stats::leaderboard::leaderboard(),
+        stats::overview::stats(),
         subject::add::add_slash(),
         subject::add::add_prefix(),
+        subject::assign::assign(),
+        subject::hours::hours(),
         subject::list::list(),
         subject::remove::remove(),
+        subject::role::role(),
+        template::template(),
         ticket::claim::claim(),
         ticket::close::close(),
-    ]
+        ticket::close_request::close_request(),
+        ticket::duty::duty(),
+        ticket::next::next(),
+        ticket::queue::queue(),
+        ticket::ticket(),
+    ];
+
+    localize_commands(&mut commands);
+
+    commands
 }
 
 /// Helper function to check if the server is set up
 async fn check_server_setup(ctx: MyContext<'_>) -> Result<bool, MyError> {
-    let mut pool = ctx.data().pool.acquire().await?;
     let guild_id = ctx.guild_id().ok_or("Failed to get guild ID")?;
-    is_server_setup(&mut pool, guild_id).await
+    let config = ctx.data().cache.get(&ctx.data().pool, guild_id).await?;
+    Ok(config.is_setup())
 }
 
 /// Helper trait to send simple messages (text only)
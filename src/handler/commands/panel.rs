@@ -0,0 +1,91 @@
+//! This module regroups commands related to the ticket panels.
+
+use crate::{
+    database::SubjectRepo,
+    handler::{Context, Error},
+    i18n::{get_locale, tr_args},
+    tickets::panels::get_panels,
+};
+use poise::{command, serenity_prelude::GuildId};
+
+pub mod create;
+pub mod delete;
+pub mod edit;
+pub mod list;
+
+/// Manages the panels members use to open tickets
+#[command(
+    slash_command,
+    prefix_command,
+    subcommands("create::create", "edit::edit", "delete::delete", "list::list"),
+    subcommand_required,
+    required_permissions = "MANAGE_CHANNELS",
+    guild_only
+)]
+pub async fn panel(_: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Autocompletes a panel name of the current server
+pub async fn autocomplete_panel(ctx: Context<'_>, partial: &str) -> Vec<String> {
+    let Some(guild_id) = ctx.guild_id() else {
+        return Vec::new();
+    };
+
+    let Ok(mut pool) = ctx.data().pool.acquire().await else {
+        return Vec::new();
+    };
+
+    let Ok(panels) = get_panels(&mut pool, guild_id).await else {
+        return Vec::new();
+    };
+
+    let partial = partial.to_lowercase();
+    panels
+        .into_iter()
+        .map(|panel| panel.name)
+        .filter(|name| name.to_lowercase().contains(&partial))
+        .collect()
+}
+
+/// Resolves a comma-separated list of subject names to their IDs
+async fn parse_subjects(
+    ctx: Context<'_>,
+    guild_id: GuildId,
+    names: &str,
+) -> Result<Vec<u64>, Error> {
+    let locale = get_locale(ctx).await;
+    let mut pool = ctx.data().pool.acquire().await?;
+    let subjects = SubjectRepo::all(&mut pool, guild_id).await?;
+
+    split_names(names)
+        .map(|name| {
+            subjects
+                .iter()
+                .find(|subject| subject.name == name)
+                .and_then(|subject| subject.id)
+                .ok_or_else(|| {
+                    tr_args(locale, "panel.subject_not_found", &[("subject", name)]).into()
+                })
+        })
+        .collect()
+}
+
+/// Splits a comma-separated list of names, ignoring empty entries
+fn split_names(names: &str) -> impl Iterator<Item = &str> {
+    names
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_names() {
+        let names: Vec<&str> = split_names(" Billing , Bug report,,").collect();
+        assert_eq!(names, vec!["Billing", "Bug report"]);
+    }
+}
@@ -0,0 +1,105 @@
+use crate::{
+    handler::{commands::check_server_setup, Context, Error},
+    i18n::{get_locale, get_server_locale, tr},
+    tickets::{
+        panels::{create_panel, get_panel_by_name, NewPanel},
+        templates::{render_template, TemplateKind},
+    },
+};
+use poise::{
+    command,
+    serenity_prelude::{ChannelType, GuildChannel},
+};
+
+use super::parse_subjects;
+
+/// Creates a panel and sends it in a channel
+#[command(
+    slash_command,
+    prefix_command,
+    required_permissions = "MANAGE_CHANNELS",
+    check = "check_server_setup",
+    guild_only
+)]
+#[allow(clippy::too_many_arguments)]
+pub async fn create(
+    ctx: Context<'_>,
+    #[description = "The name of the panel"] name: String,
+    #[description = "The channel the panel is sent in"]
+    #[channel_types("Text")]
+    channel: GuildChannel,
+    #[description = "The subjects that can be chosen (comma-separated), every subject if empty"]
+    subjects: Option<String>,
+    #[description = "The category the tickets are created in"]
+    #[channel_types("Category")]
+    category: Option<GuildChannel>,
+    #[description = "The title of the panel"] title: Option<String>,
+    #[description = "The description of the panel"] description: Option<String>,
+    #[description = "The label of the button"] button_label: Option<String>,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or("Failed to get guild ID")?;
+    let guild = guild_id.to_partial_guild(ctx.http()).await?;
+    let reply_locale = get_locale(ctx).await;
+
+    if channel.kind != ChannelType::Text {
+        ctx.reply(format!(
+            "❌ - {}",
+            tr(reply_locale, "panel.not_text_channel")
+        ))
+        .await?;
+        return Ok(());
+    }
+
+    let mut pool = ctx.data().pool.acquire().await?;
+    if get_panel_by_name(&mut pool, guild_id, &name)
+        .await?
+        .is_some()
+    {
+        ctx.reply(format!("❌ - {}", tr(reply_locale, "panel.already_exists")))
+            .await?;
+        return Ok(());
+    }
+
+    let subject_ids = match subjects {
+        Some(subjects) => match parse_subjects(ctx, guild_id, &subjects).await {
+            Ok(subject_ids) => subject_ids,
+            Err(error) => {
+                ctx.reply(format!("❌ - {error}")).await?;
+                return Ok(());
+            }
+        },
+        None => Vec::new(),
+    };
+
+    let locale = get_server_locale(&mut pool, guild_id).await;
+    let description = match description {
+        Some(description) => description,
+        None => {
+            render_template(
+                &mut pool,
+                guild_id,
+                TemplateKind::Panel,
+                locale,
+                &[("server", &guild.name)],
+            )
+            .await?
+        }
+    };
+
+    let panel = NewPanel {
+        name,
+        channel_id: channel.id,
+        title: title.unwrap_or_else(|| tr(locale, "panel.default_title")),
+        description,
+        button_label: button_label.unwrap_or_else(|| tr(locale, "panel.default_button_label")),
+        category_id: category.map(|category| category.id),
+        subject_ids,
+    };
+
+    create_panel(ctx.http(), &mut pool, &guild, panel).await?;
+    ctx.data().cache.invalidate(guild_id);
+
+    ctx.reply("✅").await?;
+
+    Ok(())
+}
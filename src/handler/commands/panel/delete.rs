@@ -0,0 +1,53 @@
+use crate::{
+    handler::{commands::check_server_setup, Context, Error},
+    i18n::{get_locale, tr},
+    tickets::panels::{delete_panel, get_panel_by_name},
+};
+use poise::command;
+
+use super::autocomplete_panel;
+
+/// Deletes a panel and its message (open tickets are not affected)
+#[command(
+    slash_command,
+    prefix_command,
+    required_permissions = "MANAGE_CHANNELS",
+    check = "check_server_setup",
+    guild_only
+)]
+pub async fn delete(
+    ctx: Context<'_>,
+    #[description = "The panel to delete"]
+    #[autocomplete = "autocomplete_panel"]
+    #[rest]
+    name: String,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or("Failed to get guild ID")?;
+    let locale = get_locale(ctx).await;
+    let mut pool = ctx.data().pool.acquire().await?;
+
+    let Some(panel) = get_panel_by_name(&mut pool, guild_id, &name).await? else {
+        ctx.reply(format!("❌ - {}", tr(locale, "panel.not_found")))
+            .await?;
+        return Ok(());
+    };
+
+    // The message may already have been deleted by hand
+    if let Err(error) = panel
+        .channel_id
+        .delete_message(ctx.http(), panel.message_id)
+        .await
+    {
+        warn!(
+            "Failed to delete the message of panel {}: {error}",
+            panel.id
+        );
+    }
+
+    delete_panel(&mut pool, panel.id).await?;
+    ctx.data().cache.invalidate(guild_id);
+
+    ctx.reply("✅").await?;
+
+    Ok(())
+}
@@ -0,0 +1,84 @@
+use crate::{
+    database::ServerRepo,
+    handler::{commands::check_server_setup, Context, Error},
+    i18n::{get_locale, tr},
+    tickets::{
+        office_hours::panel_notice,
+        panels::{get_panel_by_name, set_panel_subjects, update_panel},
+    },
+};
+use chrono::Utc;
+use poise::{command, serenity_prelude::GuildChannel};
+
+use super::{autocomplete_panel, parse_subjects};
+
+/// Changes the settings of a panel and updates its message
+#[command(
+    slash_command,
+    prefix_command,
+    required_permissions = "MANAGE_CHANNELS",
+    check = "check_server_setup",
+    guild_only
+)]
+pub async fn edit(
+    ctx: Context<'_>,
+    #[description = "The panel to edit"]
+    #[autocomplete = "autocomplete_panel"]
+    name: String,
+    #[description = "The subjects that can be chosen (comma-separated), every subject if empty"]
+    subjects: Option<String>,
+    #[description = "The category the tickets are created in"]
+    #[channel_types("Category")]
+    category: Option<GuildChannel>,
+    #[description = "The title of the panel"] title: Option<String>,
+    #[description = "The description of the panel"] description: Option<String>,
+    #[description = "The label of the button"] button_label: Option<String>,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or("Failed to get guild ID")?;
+    let guild = guild_id.to_partial_guild(ctx.http()).await?;
+    let locale = get_locale(ctx).await;
+    let mut pool = ctx.data().pool.acquire().await?;
+
+    let Some(mut panel) = get_panel_by_name(&mut pool, guild_id, &name).await? else {
+        ctx.reply(format!("❌ - {}", tr(locale, "panel.not_found")))
+            .await?;
+        return Ok(());
+    };
+
+    if let Some(subjects) = subjects {
+        let subject_ids = match parse_subjects(ctx, guild_id, &subjects).await {
+            Ok(subject_ids) => subject_ids,
+            Err(error) => {
+                ctx.reply(format!("❌ - {error}")).await?;
+                return Ok(());
+            }
+        };
+        set_panel_subjects(&mut pool, &panel, &subject_ids).await?;
+        panel.subject_ids = subject_ids;
+    }
+
+    if let Some(category) = category {
+        panel.category_id = Some(category.id);
+    }
+    if let Some(title) = title {
+        panel.title = title;
+    }
+    if let Some(description) = description {
+        panel.description = description;
+    }
+    if let Some(button_label) = button_label {
+        panel.button_label = button_label;
+    }
+
+    update_panel(&mut pool, &panel).await?;
+
+    let server = ServerRepo::get(&mut pool, guild_id).await?;
+    let closed_notice = panel_notice(&server, Utc::now());
+    panel
+        .update_message(ctx.http(), &guild, closed_notice.as_deref())
+        .await?;
+
+    ctx.reply("✅").await?;
+
+    Ok(())
+}
@@ -0,0 +1,81 @@
+use crate::{
+    database::SubjectRepo,
+    handler::{commands::check_server_setup, Context, Error},
+    helper::{
+        embed::Custom,
+        pagination::{chunk_pages, paginate},
+    },
+    i18n::{get_locale, tr, tr_args},
+    tickets::panels::get_panels,
+};
+use poise::{command, serenity_prelude::CreateEmbed};
+
+/// Number of panels displayed on a single page
+const PANELS_PER_PAGE: usize = 10;
+
+/// Lists the panels of the server
+#[command(
+    slash_command,
+    prefix_command,
+    required_permissions = "MANAGE_CHANNELS",
+    check = "check_server_setup",
+    guild_only
+)]
+pub async fn list(ctx: Context<'_>) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or("Failed to get guild ID")?;
+    let guild = guild_id.to_partial_guild(ctx.http()).await?;
+    let locale = get_locale(ctx).await;
+    let mut pool = ctx.data().pool.acquire().await?;
+
+    let panels = get_panels(&mut pool, guild_id).await?;
+    let subjects = SubjectRepo::all(&mut pool, guild_id).await?;
+
+    if panels.is_empty() {
+        ctx.reply(format!("❌ - {}", tr(locale, "panel.none_found")))
+            .await?;
+        return Ok(());
+    }
+
+    let fields: Vec<(String, String)> = panels
+        .into_iter()
+        .map(|panel| {
+            let subject_names = if panel.subject_ids.is_empty() {
+                tr(locale, "panel.all_subjects")
+            } else {
+                subjects
+                    .iter()
+                    .filter(|subject| subject.id.is_some_and(|id| panel.subject_ids.contains(&id)))
+                    .map(|subject| subject.name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            };
+            let category = panel.category_id.map_or_else(
+                || tr(locale, "panel.default_category"),
+                |id| format!("<#{id}>"),
+            );
+            let value = tr_args(
+                locale,
+                "panel.list_entry",
+                &[
+                    ("channel", &format!("<#{}>", panel.channel_id)),
+                    ("category", &category),
+                    ("subjects", &subject_names),
+                ],
+            );
+            (panel.name, value)
+        })
+        .collect();
+
+    let total = fields.len().to_string();
+    let pages = chunk_pages(fields, PANELS_PER_PAGE)
+        .into_iter()
+        .map(|page| {
+            CreateEmbed::default_bot_embed(&guild)
+                .title(tr(locale, "panel.list_title"))
+                .description(tr_args(locale, "panel.list_count", &[("count", &total)]))
+                .fields(page.into_iter().map(|(name, value)| (name, value, false)))
+        })
+        .collect();
+
+    paginate(ctx, pages, true).await
+}
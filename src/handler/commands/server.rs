@@ -1,4 +1,5 @@
 //! This module regroups commands related to the global server settings.
 
+pub mod config;
 pub mod reset;
 pub mod setup;
@@ -0,0 +1,36 @@
+//! Config command used to change the settings of the bot in a server
+
+use crate::handler::{commands::check_server_setup, Context, Error};
+use poise::command;
+
+mod assign_mode;
+mod inactivity;
+mod locale;
+mod office_hours;
+mod prefix;
+mod sla;
+mod ticket_mode;
+
+/// Changes the settings of the bot in a server
+#[command(
+    slash_command,
+    prefix_command,
+    subcommands(
+        "ticket_mode::ticket_mode",
+        "locale::locale",
+        "prefix::prefix",
+        "assign_mode::assign_mode",
+        "office_hours::office_hours",
+        "office_hours::time_zone",
+        "office_hours::closed_mode",
+        "sla::sla",
+        "inactivity::inactivity"
+    ),
+    subcommand_required,
+    required_permissions = "ADMINISTRATOR",
+    check = "check_server_setup",
+    guild_only
+)]
+pub async fn config(_: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
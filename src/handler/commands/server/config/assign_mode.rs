@@ -0,0 +1,40 @@
+//! Setting of the way new tickets are given to the helpers
+
+use crate::{
+    database::ServerRepo,
+    handler::{commands::check_server_setup, Context, Error},
+    i18n::{get_locale, tr_args},
+    tickets::assign::AssignMode,
+};
+use poise::command;
+
+/// Changes how new tickets are given to the helpers (subjects can override it)
+#[command(
+    slash_command,
+    prefix_command,
+    rename = "assignmode",
+    required_permissions = "ADMINISTRATOR",
+    check = "check_server_setup",
+    guild_only
+)]
+pub async fn assign_mode(
+    ctx: Context<'_>,
+    #[description = "Let the helpers claim the tickets, or assign them to the helpers on duty"]
+    mode: AssignMode,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or("Failed to get guild ID")?;
+    let mut pool = ctx.data().pool.acquire().await?;
+
+    ServerRepo::set_assign_mode(&mut pool, guild_id, mode).await?;
+    ctx.data().cache.invalidate(guild_id);
+
+    let locale = get_locale(ctx).await;
+    let reply = tr_args(
+        locale,
+        "config.assign_mode_set",
+        &[("mode", &mode.to_string())],
+    );
+    ctx.reply(format!("✅ - {reply}")).await?;
+
+    Ok(())
+}
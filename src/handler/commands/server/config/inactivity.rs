@@ -0,0 +1,47 @@
+//! Setting of the closing of the tickets in which nobody wrote for a while
+
+use std::time::Duration;
+
+use crate::{
+    database::ServerRepo,
+    handler::{commands::check_server_setup, Context, Error},
+    i18n::{get_locale, tr, tr_args},
+    tickets::format_wait,
+};
+use poise::command;
+
+/// Changes after how many minutes without any message a ticket is closed
+#[command(
+    slash_command,
+    prefix_command,
+    required_permissions = "ADMINISTRATOR",
+    check = "check_server_setup",
+    guild_only
+)]
+pub async fn inactivity(
+    ctx: Context<'_>,
+    #[description = "Minutes without any message before a ticket is closed, never if omitted"]
+    #[min = 1]
+    minutes: Option<u32>,
+) -> Result<(), Error> {
+    let threshold = minutes.map(|minutes| Duration::from_secs(minutes as u64 * 60));
+
+    let guild_id = ctx.guild_id().ok_or("Failed to get guild ID")?;
+    let mut pool = ctx.data().pool.acquire().await?;
+
+    ServerRepo::set_inactivity_close(&mut pool, guild_id, threshold).await?;
+    ctx.data().cache.invalidate(guild_id);
+
+    let locale = get_locale(ctx).await;
+    let reply = match threshold {
+        Some(threshold) => tr_args(
+            locale,
+            "config.inactivity_set",
+            &[("wait", &format_wait(threshold, locale))],
+        ),
+        None => tr(locale, "config.inactivity_removed"),
+    };
+    ctx.reply(format!("✅ - {reply}")).await?;
+
+    Ok(())
+}
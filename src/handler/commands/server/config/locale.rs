@@ -0,0 +1,36 @@
+//! Setting of the default language of the bot messages
+
+use crate::{
+    database::ServerRepo,
+    handler::{commands::check_server_setup, Context, Error},
+    i18n::{get_locale, tr_args, Locale},
+};
+use poise::command;
+
+/// Changes the default language of the bot (users keep their own Discord language)
+#[command(
+    slash_command,
+    prefix_command,
+    required_permissions = "ADMINISTRATOR",
+    check = "check_server_setup",
+    guild_only
+)]
+pub async fn locale(
+    ctx: Context<'_>,
+    #[description = "The language used when the language of a user is unknown"] locale: Locale,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or("Failed to get guild ID")?;
+    let mut pool = ctx.data().pool.acquire().await?;
+
+    ServerRepo::set_locale(&mut pool, guild_id, locale).await?;
+    ctx.data().cache.invalidate(guild_id);
+
+    let reply = tr_args(
+        get_locale(ctx).await,
+        "config.locale_set",
+        &[("locale", locale.as_str())],
+    );
+    ctx.reply(format!("✅ - {reply}")).await?;
+
+    Ok(())
+}
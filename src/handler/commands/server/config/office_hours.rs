@@ -0,0 +1,123 @@
+//! Settings of the office hours, outside of which members cannot open tickets (or only as pending)
+
+use crate::{
+    database::ServerRepo,
+    handler::{commands::check_server_setup, Context, Error},
+    i18n::{get_locale, tr, tr_args},
+    tickets::office_hours::{refresh_panels, ClosedMode, Schedule, ServerTimeZone},
+};
+use chrono::Utc;
+use poise::command;
+
+/// Changes when the helpers staff the tickets
+#[command(
+    slash_command,
+    prefix_command,
+    rename = "officehours",
+    required_permissions = "ADMINISTRATOR",
+    check = "check_server_setup",
+    guild_only
+)]
+pub async fn office_hours(
+    ctx: Context<'_>,
+    #[description = "The weekly hours, such as `mon-fri 09:00-17:00, sat 10:00-12:00`, always if omitted"]
+    #[rest]
+    schedule: Option<String>,
+) -> Result<(), Error> {
+    let schedule = schedule
+        .map(|schedule| schedule.parse::<Schedule>())
+        .transpose()
+        .map_err(Error::user)?;
+
+    let guild_id = ctx.guild_id().ok_or("Failed to get guild ID")?;
+    let mut pool = ctx.data().pool.acquire().await?;
+
+    ServerRepo::set_office_hours(&mut pool, guild_id, schedule.as_ref()).await?;
+    update_panels(ctx).await?;
+
+    let locale = get_locale(ctx).await;
+    let reply = match schedule {
+        Some(schedule) => tr_args(
+            locale,
+            "config.office_hours_set",
+            &[("schedule", &schedule.to_string())],
+        ),
+        None => tr(locale, "config.office_hours_removed"),
+    };
+    ctx.reply(format!("✅ - {reply}")).await?;
+
+    Ok(())
+}
+
+/// Changes the time zone the office hours are read in
+#[command(
+    slash_command,
+    prefix_command,
+    rename = "timezone",
+    required_permissions = "ADMINISTRATOR",
+    check = "check_server_setup",
+    guild_only
+)]
+pub async fn time_zone(
+    ctx: Context<'_>,
+    #[description = "A region such as `Europe/Paris` (with daylight saving time) or an offset such as `+02:00`"]
+    time_zone: String,
+) -> Result<(), Error> {
+    let time_zone = time_zone.parse::<ServerTimeZone>().map_err(Error::user)?;
+
+    let guild_id = ctx.guild_id().ok_or("Failed to get guild ID")?;
+    let mut pool = ctx.data().pool.acquire().await?;
+
+    ServerRepo::set_time_zone(&mut pool, guild_id, time_zone).await?;
+    update_panels(ctx).await?;
+
+    let locale = get_locale(ctx).await;
+    let reply = tr_args(
+        locale,
+        "config.time_zone_set",
+        &[("time_zone", &time_zone.to_string())],
+    );
+    ctx.reply(format!("✅ - {reply}")).await?;
+
+    Ok(())
+}
+
+/// Changes what happens to the tickets opened outside office hours
+#[command(
+    slash_command,
+    prefix_command,
+    rename = "closedmode",
+    required_permissions = "ADMINISTRATOR",
+    check = "check_server_setup",
+    guild_only
+)]
+pub async fn closed_mode(
+    ctx: Context<'_>,
+    #[description = "Refuse the tickets, or accept them as pending until the office hours start"]
+    mode: ClosedMode,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or("Failed to get guild ID")?;
+    let mut pool = ctx.data().pool.acquire().await?;
+
+    ServerRepo::set_closed_mode(&mut pool, guild_id, mode).await?;
+    ctx.data().cache.invalidate(guild_id);
+
+    let key = match mode {
+        ClosedMode::Refuse => "config.closed_mode_refuse",
+        ClosedMode::Pending => "config.closed_mode_pending",
+    };
+    let locale = get_locale(ctx).await;
+    ctx.reply(format!("✅ - {}", tr(locale, key))).await?;
+
+    Ok(())
+}
+
+/// Shows the new office hours on the panels of the server
+async fn update_panels(ctx: Context<'_>) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or("Failed to get guild ID")?;
+    let mut pool = ctx.data().pool.acquire().await?;
+
+    ctx.data().cache.invalidate(guild_id);
+    let server = ServerRepo::get(&mut pool, guild_id).await?;
+    refresh_panels(ctx.http(), &mut pool, &server, Utc::now()).await
+}
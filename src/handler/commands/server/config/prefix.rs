@@ -0,0 +1,69 @@
+//! Setting of the prefix of the prefix commands
+
+use crate::{
+    database::ServerRepo,
+    handler::{commands::check_server_setup, Context, Error},
+    i18n::{get_locale, tr, tr_args, Locale},
+};
+use poise::command;
+
+/// Maximum length of a prefix
+const MAX_PREFIX_LENGTH: usize = 8;
+
+/// Changes the prefix of the prefix commands (mentioning the bot always works)
+#[command(
+    slash_command,
+    prefix_command,
+    required_permissions = "ADMINISTRATOR",
+    check = "check_server_setup",
+    guild_only
+)]
+pub async fn prefix(
+    ctx: Context<'_>,
+    #[description = "The new prefix, such as `!` or `t.`"] prefix: String,
+) -> Result<(), Error> {
+    let locale = get_locale(ctx).await;
+    validate_prefix(&prefix, locale).map_err(Error::user)?;
+
+    let guild_id = ctx.guild_id().ok_or("Failed to get guild ID")?;
+    let mut pool = ctx.data().pool.acquire().await?;
+
+    ServerRepo::set_prefix(&mut pool, guild_id, &prefix).await?;
+    ctx.data().cache.invalidate(guild_id);
+
+    let reply = tr_args(locale, "config.prefix_set", &[("prefix", &prefix)]);
+    ctx.reply(format!("✅ - {reply}")).await?;
+
+    Ok(())
+}
+
+/// Checks that a prefix can be typed at the start of a message
+fn validate_prefix(prefix: &str, locale: Locale) -> Result<(), String> {
+    if prefix.is_empty() || prefix.chars().count() > MAX_PREFIX_LENGTH {
+        return Err(tr_args(
+            locale,
+            "config.prefix_length",
+            &[("max", &MAX_PREFIX_LENGTH.to_string())],
+        ));
+    }
+
+    if prefix.chars().any(char::is_whitespace) || prefix.contains('`') {
+        return Err(tr(locale, "config.prefix_characters"));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_prefix() {
+        assert!(validate_prefix("!", Locale::En).is_ok());
+        assert!(validate_prefix("t.", Locale::En).is_ok());
+        assert!(validate_prefix("", Locale::En).is_err());
+        assert!(validate_prefix("a b", Locale::En).is_err());
+        assert!(validate_prefix("toolongprefix", Locale::En).is_err());
+    }
+}
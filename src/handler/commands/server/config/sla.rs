@@ -0,0 +1,74 @@
+//! Settings of the reminders sent in the tickets left waiting
+
+use std::time::Duration;
+
+use crate::{
+    database::ServerRepo,
+    handler::{commands::check_server_setup, Context, Error},
+    i18n::{get_locale, tr, tr_args},
+    tickets::{format_wait, sla::SlaThresholds},
+};
+use poise::command;
+
+/// Changes after how many minutes the staff is reminded of the waiting tickets
+#[command(
+    slash_command,
+    prefix_command,
+    required_permissions = "ADMINISTRATOR",
+    check = "check_server_setup",
+    guild_only
+)]
+pub async fn sla(
+    ctx: Context<'_>,
+    #[description = "Minutes before the helpers are pinged in an unclaimed ticket, never if omitted"]
+    #[min = 1]
+    unclaimed: Option<u32>,
+    #[description = "Minutes before the moderators are pinged in an unclaimed ticket, never if omitted"]
+    #[min = 1]
+    escalation: Option<u32>,
+    #[description = "Minutes before a helper is reminded of an unanswered message, never if omitted"]
+    #[min = 1]
+    reply: Option<u32>,
+) -> Result<(), Error> {
+    let minutes =
+        |minutes: Option<u32>| minutes.map(|minutes| Duration::from_secs(minutes as u64 * 60));
+    let locale = get_locale(ctx).await;
+    let sla = SlaThresholds {
+        unclaimed: minutes(unclaimed),
+        escalation: minutes(escalation),
+        reply: minutes(reply),
+    };
+
+    if let (Some(unclaimed), Some(escalation)) = (sla.unclaimed, sla.escalation) {
+        if escalation <= unclaimed {
+            return Err(Error::user(tr(locale, "config.sla_order")));
+        }
+    }
+
+    let guild_id = ctx.guild_id().ok_or("Failed to get guild ID")?;
+    let mut pool = ctx.data().pool.acquire().await?;
+
+    ServerRepo::set_sla(&mut pool, guild_id, &sla).await?;
+    ctx.data().cache.invalidate(guild_id);
+
+    let describe = |threshold: Option<Duration>| match threshold {
+        Some(threshold) => tr_args(
+            locale,
+            "config.sla_after",
+            &[("wait", &format_wait(threshold, locale))],
+        ),
+        None => tr(locale, "config.sla_never"),
+    };
+    let reply = tr_args(
+        locale,
+        "config.sla_set",
+        &[
+            ("unclaimed", &describe(sla.unclaimed)),
+            ("escalation", &describe(sla.escalation)),
+            ("reply", &describe(sla.reply)),
+        ],
+    );
+    ctx.reply(format!("✅ - {reply}")).await?;
+
+    Ok(())
+}
@@ -0,0 +1,55 @@
+//! Setting of the way tickets are created (channels or private threads)
+
+use crate::{
+    database::ServerRepo,
+    handler::{commands::check_server_setup, Context, Error},
+    i18n::{get_locale, tr_args},
+    tickets::{
+        location::{allow_thread_management, TicketMode},
+        panels::get_panels,
+    },
+};
+use poise::command;
+
+/// Changes how new tickets are created (existing tickets are not moved)
+#[command(
+    slash_command,
+    prefix_command,
+    rename = "ticketmode",
+    required_permissions = "ADMINISTRATOR",
+    check = "check_server_setup",
+    guild_only
+)]
+pub async fn ticket_mode(
+    ctx: Context<'_>,
+    #[description = "Create tickets as channels or as private threads of the ticket channel"]
+    mode: TicketMode,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or("Failed to get guild ID")?;
+    let mut pool = ctx.data().pool.acquire().await?;
+
+    ServerRepo::set_ticket_mode(&mut pool, guild_id, mode).await?;
+    ctx.data().cache.invalidate(guild_id);
+    let server = ServerRepo::get(&mut pool, guild_id).await?;
+
+    if mode == TicketMode::Thread {
+        // The threads are created in the channel of their panel, or the default ticket channel
+        let mut channel_ids = vec![server.ticket_channel_id];
+        for panel in get_panels(&mut pool, guild_id).await? {
+            if !channel_ids.contains(&panel.channel_id) {
+                channel_ids.push(panel.channel_id);
+            }
+        }
+
+        let role_ids = [server.helper_role_id, server.moderator_role_id];
+        for channel_id in channel_ids {
+            allow_thread_management(ctx.http(), channel_id, &role_ids).await?;
+        }
+    }
+
+    let locale = get_locale(ctx).await;
+    let reply = tr_args(locale, "config.ticket_mode_set", &[("mode", mode.as_str())]);
+    ctx.reply(format!("✅ - {reply}")).await?;
+
+    Ok(())
+}
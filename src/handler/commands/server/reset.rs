@@ -1,14 +1,18 @@
 //! Setup command used to set up the bot in a server
 
-use crate::handler::{
-    commands::{check_server_setup, SimpleMessage},
-    Context, Error,
+use crate::{
+    database::ServerRepo,
+    handler::{
+        commands::{check_server_setup, SimpleMessage},
+        Context, Error,
+    },
+    i18n::{get_locale, tr, tr_args, Locale},
+    tickets::categories::{get_overflow_categories, CategoryState},
 };
 use poise::{
     command,
-    serenity_prelude::{ChannelId, CreateEmbed, CreateMessage, ReactionType, RoleId},
+    serenity_prelude::{CreateEmbed, CreateMessage, ReactionType},
 };
-use std::time::Duration;
 
 /// Reset the bot in a server
 #[command(
@@ -26,12 +30,15 @@ pub async fn reset(ctx: Context<'_>) -> Result<(), Error> {
         .ok_or("Failed to get guild ID")?
         .to_partial_guild(ctx.http())
         .await?;
+    let locale = get_locale(ctx).await;
     let mut pool = ctx.data().pool.acquire().await?;
 
     // Ask for reset confirmation
 
-    ctx.send_simple_message(&format!(
-        "Are you sure you want to reset the server? Type `{CONFIRM_MESSAGE}` to confirm"
+    ctx.send_simple_message(&tr_args(
+        locale,
+        "reset.confirm",
+        &[("confirm", CONFIRM_MESSAGE)],
     ))
     .await?;
 
@@ -39,60 +46,78 @@ pub async fn reset(ctx: Context<'_>) -> Result<(), Error> {
         .author()
         .await_reply(ctx)
         .await
-        .ok_or("Timeout reached")?;
+        .ok_or_else(|| tr(locale, "reset.timeout"))?;
 
     if reply.content != CONFIRM_MESSAGE {
-        ctx.send_simple_message("Reset cancelled").await?;
+        ctx.send_simple_message(&tr(locale, "reset.cancelled"))
+            .await?;
         return Ok(());
     }
 
     // TODO: Delete all tickets, subjects, logs etc.
 
+    // Overflow categories are removed along with the server
+    let unclaimed_overflow =
+        get_overflow_categories(&mut pool, guild.id, CategoryState::Unclaimed).await?;
+    let claimed_overflow =
+        get_overflow_categories(&mut pool, guild.id, CategoryState::Claimed).await?;
+
     // Delete server from database and get server info for deletions
-    let server_info = sqlx::query!(
-        "DELETE FROM servers WHERE id = $1 RETURNING *",
-        guild.id.get() as i64
-    )
-    .fetch_one(&mut *pool)
-    .await?;
+    let server_info = ServerRepo::delete(&mut pool, guild.id)
+        .await?
+        .ok_or(Error::NotSetUp)?;
+    ctx.data().cache.invalidate(guild.id);
 
     // Roles
-    if get_yes_no_answer(ctx, "Do you want to delete the helper role?").await? {
-        let helper_role_id = RoleId::from(server_info.helper_role_id as u64);
-        guild.id.delete_role(ctx.http(), helper_role_id).await?;
+    if get_yes_no_answer(ctx, locale, "reset.delete_helper_role").await? {
+        guild
+            .id
+            .delete_role(ctx.http(), server_info.helper_role_id)
+            .await?;
     }
 
-    if get_yes_no_answer(ctx, "Do you want to delete the moderator role?").await? {
-        let moderator_role_id = RoleId::from(server_info.moderator_role_id as u64);
-        guild.id.delete_role(ctx.http(), moderator_role_id).await?;
+    if get_yes_no_answer(ctx, locale, "reset.delete_moderator_role").await? {
+        guild
+            .id
+            .delete_role(ctx.http(), server_info.moderator_role_id)
+            .await?;
     }
 
     // Categories
-    if get_yes_no_answer(ctx, "Do you want to delete the unclaimed tickets category?").await? {
-        let unclaimed_category_id = ChannelId::from(server_info.unclaimed_category_id as u64);
-        unclaimed_category_id.delete(ctx.http()).await?;
+    if get_yes_no_answer(ctx, locale, "reset.delete_unclaimed_category").await? {
+        server_info.unclaimed_category_id.delete(ctx.http()).await?;
+        for (category_id, _) in unclaimed_overflow {
+            category_id.delete(ctx.http()).await?;
+        }
     }
 
-    if get_yes_no_answer(ctx, "Do you want to delete the claimed tickets category?").await? {
-        let claimed_category_id = ChannelId::from(server_info.claimed_category_id as u64);
-        claimed_category_id.delete(ctx.http()).await?;
+    if get_yes_no_answer(ctx, locale, "reset.delete_claimed_category").await? {
+        server_info.claimed_category_id.delete(ctx.http()).await?;
+        for (category_id, _) in claimed_overflow {
+            category_id.delete(ctx.http()).await?;
+        }
     }
 
     // Channels aren't deleted because they are the ticket and log channels
     // (which are provided by the server administator therefore not subject to this reset)
 
-    ctx.send_simple_message("Reset successful").await?;
+    ctx.send_simple_message(&tr(locale, "reset.success"))
+        .await?;
 
     Ok(())
 }
 
 /// Helper function for y/n confirmation
-async fn get_yes_no_answer(ctx: Context<'_>, question: &str) -> Result<bool, Error> {
+async fn get_yes_no_answer(
+    ctx: Context<'_>,
+    locale: Locale,
+    question_key: &str,
+) -> Result<bool, Error> {
     let embed = CreateEmbed::default()
-        .title("Confirmation")
-        .description(question)
-        .field("✅", "Yes", true)
-        .field("❌", "No", true);
+        .title(tr(locale, "reset.confirmation"))
+        .description(tr(locale, question_key))
+        .field("✅", tr(locale, "reset.yes"), true)
+        .field("❌", tr(locale, "reset.no"), true);
 
     let reaction_yes = ReactionType::Unicode("✅".to_string());
     let reaction_no = ReactionType::Unicode("❌".to_string());
@@ -105,9 +130,9 @@ async fn get_yes_no_answer(ctx: Context<'_>, question: &str) -> Result<bool, Err
 
     let reaction = sent_message
         .await_reaction(ctx)
-        .timeout(Duration::from_secs(60))
+        .timeout(ctx.data().timeouts.prompt)
         .await
-        .ok_or("Timed out waiting for reaction")?;
+        .ok_or_else(|| tr(locale, "roles.reaction_timeout"))?;
 
     Ok(reaction.emoji == reaction_yes)
 }
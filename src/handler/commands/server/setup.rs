@@ -1,24 +1,29 @@
 //! Setup command used to set up the bot in a server
 
 use crate::{
-    database::is_server_setup,
+    database::{Connection, NewServer, ServerRepo},
     handler::{commands::SimpleMessage, Context, Error},
-    helper::{embed::Custom, parser::parse_discord_channel_id_url},
-    tickets::TICKET_EMOJI,
+    helper::parser::parse_discord_channel_id_url,
+    i18n::{get_locale, get_server_locale, tr, Locale},
+    tickets::{
+        categories::CategoryState,
+        panels::{create_panel, NewPanel},
+        templates::{render_template, TemplateKind},
+    },
 };
 use poise::{
     command,
     serenity_prelude::{
-        model::channel, ChannelId, ChannelType, CreateChannel, CreateEmbed, CreateMessage,
-        GuildChannel, GuildId, MessageId, ReactionType, RoleId,
+        model::channel, ChannelId, ChannelType, CreateChannel, GuildChannel, GuildId, RoleId,
     },
 };
 use roles::get_new_or_existing_role;
-use sqlx::PgConnection;
-use std::time::Duration;
 
 mod roles;
 
+/// Name of the panel created during the setup
+const DEFAULT_PANEL_NAME: &str = "default";
+
 /// Setup the bot in a server
 #[command(
     slash_command,
@@ -34,9 +39,10 @@ pub async fn setup(ctx: Context<'_>) -> Result<(), Error> {
         .ok_or("Failed to get guild ID")?
         .to_partial_guild(ctx.http())
         .await?;
+    let locale = get_locale(ctx).await;
     let mut pool = ctx.data().pool.acquire().await?;
-    if is_server_setup(&mut pool, guild.id).await? {
-        ctx.send_simple_message("The server is already set up")
+    if ServerRepo::exists(&mut pool, guild.id).await? {
+        ctx.send_simple_message(&tr(locale, "setup.already_setup"))
             .await?;
         return Ok(());
     }
@@ -48,28 +54,53 @@ pub async fn setup(ctx: Context<'_>) -> Result<(), Error> {
 
     // Channels
     info!("Setting up ticket channels for {}", guild.name);
-    guild_info.ticket_channel(get_ticket_channel_id(&ctx).await?);
-    guild_info.log_channel(get_log_channel_id(&ctx).await?);
+    guild_info
+        .ticket_channel(get_text_channel_id(&ctx, locale, "setup.ticket_channel_prompt").await?);
+    guild_info.log_channel(get_text_channel_id(&ctx, locale, "setup.log_channel_prompt").await?);
 
     // Categories
     info!("Setting up categories for {}", guild.name);
     guild_info.unclaimed_category(
-        create_server_category(&ctx, guild.id, "Unclaimed Tickets")
-            .await?
-            .id,
+        create_server_category(
+            &ctx,
+            guild.id,
+            &CategoryState::Unclaimed.category_name(locale),
+        )
+        .await?
+        .id,
     );
     guild_info.claimed_category(
-        create_server_category(&ctx, guild.id, "Claimed Tickets")
-            .await?
-            .id,
+        create_server_category(
+            &ctx,
+            guild.id,
+            &CategoryState::Claimed.category_name(locale),
+        )
+        .await?
+        .id,
     );
 
     // Roles
     info!("Setting up roles for {}", guild.name);
-    guild_info.helper_role(get_new_or_existing_role(&ctx, &guild, "Helper Role", "Helper").await?);
+    guild_info.helper_role(
+        get_new_or_existing_role(
+            &ctx,
+            &guild,
+            locale,
+            &tr(locale, "setup.helper_role_title"),
+            &tr(locale, "setup.helper_role_name"),
+        )
+        .await?,
+    );
 
     guild_info.moderator_role(
-        get_new_or_existing_role(&ctx, &guild, "Moderator Role", "Moderator").await?,
+        get_new_or_existing_role(
+            &ctx,
+            &guild,
+            locale,
+            &tr(locale, "setup.moderator_role_title"),
+            &tr(locale, "setup.moderator_role_name"),
+        )
+        .await?,
     );
 
     // Save information about the ticket channel
@@ -92,63 +123,52 @@ async fn create_server_category(
     Ok(category)
 }
 
-async fn get_ticket_channel_id(ctx: &Context<'_>) -> Result<ChannelId, Error> {
-    ctx.send_simple_message("Please provide the ticket channel ID")
-        .await?;
-
-    let channel = parse_channel_id_from_user_input(ctx).await?;
-
-    if !is_guild_text_channel(ctx, channel).await? {
-        ctx.send_simple_message("The channel must be a text channel")
-            .await?;
-        return Err("The channel must be a text channel".into());
-    }
-
-    Ok(channel)
-}
-
-async fn get_log_channel_id(ctx: &Context<'_>) -> Result<ChannelId, Error> {
-    ctx.send_simple_message("Please provide the log channel ID")
-        .await?;
+/// Asks for a text channel of the server
+async fn get_text_channel_id(
+    ctx: &Context<'_>,
+    locale: Locale,
+    prompt: &str,
+) -> Result<ChannelId, Error> {
+    ctx.send_simple_message(&tr(locale, prompt)).await?;
 
-    let channel = parse_channel_id_from_user_input(ctx).await?;
+    let channel = parse_channel_id_from_user_input(ctx, locale).await?;
 
-    if !is_guild_text_channel(ctx, channel).await? {
-        ctx.send_simple_message("The channel must be a text channel")
-            .await?;
-        return Err("The channel must be a text channel".into());
+    if !is_guild_text_channel(ctx, locale, channel).await? {
+        return Err(fail(ctx, locale, "setup.not_text_channel").await);
     }
 
     Ok(channel)
 }
 
-async fn parse_channel_id_from_user_input(ctx: &Context<'_>) -> Result<ChannelId, Error> {
+async fn parse_channel_id_from_user_input(
+    ctx: &Context<'_>,
+    locale: Locale,
+) -> Result<ChannelId, Error> {
     let Some(reply) = ctx
         .author()
         .await_reply(ctx)
-        .timeout(Duration::from_secs(60))
+        .timeout(ctx.data().timeouts.prompt)
         .await
     else {
-        ctx.send_simple_message("Timeout reached").await?;
-        return Err("Timeout reached".into());
+        return Err(fail(ctx, locale, "setup.timeout").await);
     };
 
     let content = reply.content.trim();
 
-    let channel_id: u64 = if let Some(channel_id) = parse_discord_channel_id_url(content) {
-        channel_id
-    } else {
-        ctx.send_simple_message("Timeout reached").await?;
-        return Err("Timeout reached".into());
+    let Some(channel_id) = parse_discord_channel_id_url(content) else {
+        return Err(fail(ctx, locale, "setup.invalid_channel").await);
     };
 
     Ok(channel_id.into())
 }
 
-async fn is_guild_text_channel(ctx: &Context<'_>, channel_id: ChannelId) -> Result<bool, Error> {
+async fn is_guild_text_channel(
+    ctx: &Context<'_>,
+    locale: Locale,
+    channel_id: ChannelId,
+) -> Result<bool, Error> {
     let Ok(channel) = ctx.http().get_channel(channel_id).await else {
-        ctx.send_simple_message("Failed to get channel").await?;
-        return Err("Failed to get channel".into());
+        return Err(fail(ctx, locale, "setup.channel_not_found").await);
     };
 
     if let channel::Channel::Guild(channel) = channel {
@@ -160,6 +180,15 @@ async fn is_guild_text_channel(ctx: &Context<'_>, channel_id: ChannelId) -> Resu
     Ok(true)
 }
 
+/// Tells the user why the setup stopped and returns the matching error
+async fn fail(ctx: &Context<'_>, locale: Locale, key: &str) -> Error {
+    let message = tr(locale, key);
+    if let Err(error) = ctx.send_simple_message(&message).await {
+        return error.into();
+    }
+    message.into()
+}
+
 #[derive(Default)]
 struct ServerSetupBuilder {
     guild: Option<GuildId>,
@@ -234,7 +263,7 @@ impl ServerSetupBuilder {
 struct ServerSetupData {
     /// The guild ID
     guild: GuildId,
-    /// The channel where the bot will send the ticket message and listen for reactions
+    /// The channel where the bot will send the default panel
     ticket_channel: ChannelId,
     /// The category where the unclaimed tickets will be created
     unclaimed_category: ChannelId,
@@ -251,63 +280,52 @@ struct ServerSetupData {
 impl ServerSetupData {
     /// Sets up the server with the provided data
     ///
-    /// It will create the ticket message and save the data to the database
+    /// It will save the data to the database and create the default panel in the ticket channel
     pub async fn setup(&self, ctx: &Context<'_>) -> Result<(), Error> {
-        let message_id = self.setup_reaction_message(ctx).await?;
-
-        let mut pool = ctx.data().pool.acquire().await?;
-
-        self.save(&mut pool, message_id).await
-    }
-
-    async fn setup_reaction_message(&self, ctx: &Context<'_>) -> Result<MessageId, Error> {
         let guild = self.guild.to_partial_guild(ctx.http()).await?;
 
-        let embed = CreateEmbed::default_bot_embed(&guild)
-            .title("Open a ticket")
-            .description("React to this message to open a ticket");
+        let mut pool = ctx.data().pool.acquire().await?;
 
-        let message = CreateMessage::default()
-            .embed(embed)
-            .reactions(vec![ReactionType::Unicode(TICKET_EMOJI.to_string())]);
+        self.save(&mut pool).await?;
+        ctx.data().cache.invalidate(guild.id);
+
+        let locale = get_server_locale(&mut pool, guild.id).await;
+
+        let panel = NewPanel {
+            name: DEFAULT_PANEL_NAME.to_string(),
+            channel_id: self.ticket_channel,
+            title: tr(locale, "panel.default_title"),
+            description: render_template(
+                &mut pool,
+                guild.id,
+                TemplateKind::Panel,
+                locale,
+                &[("server", &guild.name)],
+            )
+            .await?,
+            button_label: tr(locale, "panel.default_button_label"),
+            category_id: None,
+            subject_ids: Vec::new(),
+        };
+
+        create_panel(ctx.http(), &mut pool, &guild, panel).await?;
+        ctx.data().cache.invalidate(guild.id);
 
-        let sent_message = self
-            .ticket_channel
-            .send_message(ctx.http(), message)
-            .await?;
-
-        Ok(sent_message.id)
+        Ok(())
     }
 
     /// Save the server setup data to the database
-    async fn save(
-        &self,
-        conn: &mut PgConnection,
-        ticket_message_id: MessageId,
-    ) -> Result<(), Error> {
-        sqlx::query!(
-            "INSERT INTO servers (
-                id,
-                ticket_channel_id,
-                unclaimed_category_id,
-                claimed_category_id,
-                ticket_message_id,
-                log_channel_id,
-                helper_role_id,
-                moderator_role_id
-            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
-            self.guild.get() as i64,
-            self.ticket_channel.get() as i64,
-            self.unclaimed_category.get() as i64,
-            self.claimed_category.get() as i64,
-            ticket_message_id.get() as i64,
-            self.log_channel.get() as i64,
-            self.helper_role.get() as i64,
-            self.moderator_role.get() as i64
-        )
-        .execute(conn)
-        .await?;
-
-        Ok(())
+    async fn save(&self, conn: &mut Connection) -> Result<(), Error> {
+        let server = NewServer {
+            id: self.guild,
+            ticket_channel_id: self.ticket_channel,
+            unclaimed_category_id: self.unclaimed_category,
+            claimed_category_id: self.claimed_category,
+            helper_role_id: self.helper_role,
+            moderator_role_id: self.moderator_role,
+            log_channel_id: self.log_channel,
+        };
+
+        ServerRepo::insert(conn, &server).await
     }
 }
@@ -1,7 +1,5 @@
 //! This module regroups utilities linked to roles setup.
 
-use std::time::Duration;
-
 use poise::serenity_prelude::{
     CreateEmbed, CreateMessage, EditRole, GuildId, PartialGuild, ReactionType, RoleId,
 };
@@ -9,20 +7,22 @@ use poise::serenity_prelude::{
 use crate::{
     handler::{commands::SimpleMessage, Context, Error},
     helper::{embed::Custom, parser::parse_discord_mention},
+    i18n::{tr, tr_args, Locale},
 };
 
 pub async fn get_new_or_existing_role(
     ctx: &Context<'_>,
     guild: &PartialGuild,
+    locale: Locale,
     title: &str,
     role_name: &str,
 ) -> Result<RoleId, Error> {
     // Ask if the user wants to create a new role or use an existing one
     let embed = CreateEmbed::default()
         .title(title)
-        .description("Do you want to create a new role or use an existing one?")
-        .field("🆕", "Create a new role", true)
-        .field("🔗", "Use an existing role", true);
+        .description(tr(locale, "roles.question"))
+        .field("🆕", tr(locale, "roles.create"), true)
+        .field("🔗", tr(locale, "roles.existing"), true);
 
     let reaction_new = ReactionType::Unicode("🆕".to_string());
     let reaction_existing = ReactionType::Unicode("🔗".to_string());
@@ -35,15 +35,17 @@ pub async fn get_new_or_existing_role(
 
     let reaction = sent_message
         .await_reaction(ctx)
-        .timeout(std::time::Duration::from_secs(60))
+        .timeout(ctx.data().timeouts.prompt)
         .await
-        .ok_or("Timed out waiting for reaction")?;
+        .ok_or_else(|| tr(locale, "roles.reaction_timeout"))?;
 
     let role_id = match reaction.emoji {
         ref emoji if emoji == &reaction_new => create_new_role(ctx, guild.id, role_name).await?,
-        ref emoji if emoji == &reaction_existing => select_existing_role(ctx, guild).await?,
+        ref emoji if emoji == &reaction_existing => {
+            select_existing_role(ctx, guild, locale, title).await?
+        }
         _ => {
-            return Err("Invalid reaction".into());
+            return Err(tr(locale, "roles.invalid_reaction").into());
         }
     };
 
@@ -63,11 +65,20 @@ async fn create_new_role(
 }
 
 /// Select an existing role for the helpers
-async fn select_existing_role(ctx: &Context<'_>, guild: &PartialGuild) -> Result<RoleId, Error> {
+async fn select_existing_role(
+    ctx: &Context<'_>,
+    guild: &PartialGuild,
+    locale: Locale,
+    title: &str,
+) -> Result<RoleId, Error> {
     // Ask the user to mention the role
     let embed = CreateEmbed::default_bot_embed(guild)
-        .title("Select a role")
-        .description("Please mention the role you want to use as the helper role");
+        .title(tr(locale, "roles.select_title"))
+        .description(tr_args(
+            locale,
+            "roles.select_description",
+            &[("role", &title.to_lowercase())],
+        ));
 
     ctx.channel_id()
         .send_message(ctx.http(), CreateMessage::default().embed(embed))
@@ -77,17 +88,17 @@ async fn select_existing_role(ctx: &Context<'_>, guild: &PartialGuild) -> Result
     let Some(reply) = ctx
         .author()
         .await_reply(ctx)
-        .timeout(Duration::from_secs(60))
+        .timeout(ctx.data().timeouts.prompt)
         .await
     else {
-        ctx.send_simple_message("Timed out waiting for reply")
-            .await?;
-        return Err("Timed out waiting for reply".into());
+        let message = tr(locale, "roles.reply_timeout");
+        ctx.send_simple_message(&message).await?;
+        return Err(message.into());
     };
 
     // Parse the role ID
     let role_id = parse_discord_mention(reply.content.trim())
-        .ok_or("Invalid role ID")?
+        .ok_or_else(|| tr(locale, "roles.invalid_role"))?
         .into();
 
     Ok(role_id)
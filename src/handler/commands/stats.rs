@@ -0,0 +1,21 @@
+//! This module regroups commands related to the statistics of the tickets.
+
+pub mod leaderboard;
+pub mod overview;
+
+use crate::i18n::{tr, tr_args, Locale};
+
+/// Formats an average rating along with the number of ratings it is based on
+fn format_rating(locale: Locale, average: Option<f64>, count: i64) -> String {
+    match average {
+        Some(average) if count > 0 => tr_args(
+            locale,
+            "stats.rating",
+            &[
+                ("average", &format!("{average:.2}")),
+                ("count", &count.to_string()),
+            ],
+        ),
+        _ => tr(locale, "stats.no_ratings"),
+    }
+}
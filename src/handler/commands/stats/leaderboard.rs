@@ -0,0 +1,73 @@
+use crate::{
+    database::StatsRepo,
+    handler::{commands::check_server_setup, Context, Error},
+    helper::{
+        embed::Custom,
+        pagination::{chunk_pages, paginate},
+    },
+    i18n::{get_locale, tr, tr_args},
+};
+use poise::{
+    command,
+    serenity_prelude::{CreateEmbed, Mentionable},
+};
+
+use super::format_rating;
+
+/// Number of helpers displayed on a single page
+const HELPERS_PER_PAGE: usize = 10;
+
+/// Shows the helpers ranked by the number of tickets they claimed, along with their ratings
+#[command(
+    slash_command,
+    prefix_command,
+    required_permissions = "MANAGE_CHANNELS",
+    check = "check_server_setup",
+    guild_only
+)]
+pub async fn leaderboard(ctx: Context<'_>) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or("Guild ID not found")?;
+    let guild = guild_id.to_partial_guild(ctx.http()).await?;
+    let locale = get_locale(ctx).await;
+    let mut pool = ctx.data().pool.acquire().await?;
+
+    let helpers = StatsRepo::helpers(&mut pool, guild_id).await?;
+
+    if helpers.is_empty() {
+        ctx.reply(format!("❌ - {}", tr(locale, "stats.leaderboard_empty")))
+            .await?;
+        return Ok(());
+    }
+
+    let lines: Vec<String> = helpers
+        .into_iter()
+        .enumerate()
+        .map(|(rank, helper)| {
+            tr_args(
+                locale,
+                "stats.leaderboard_entry",
+                &[
+                    ("rank", &(rank + 1).to_string()),
+                    ("helper", &helper.helper_id.mention().to_string()),
+                    ("claimed", &helper.claimed.to_string()),
+                    ("closed", &helper.closed.to_string()),
+                    (
+                        "rating",
+                        &format_rating(locale, helper.average_rating, helper.ratings),
+                    ),
+                ],
+            )
+        })
+        .collect();
+
+    let pages = chunk_pages(lines, HELPERS_PER_PAGE)
+        .into_iter()
+        .map(|page| {
+            CreateEmbed::default_bot_embed(&guild)
+                .title(tr(locale, "stats.leaderboard_title"))
+                .description(page.join("\n"))
+        })
+        .collect();
+
+    paginate(ctx, pages, false).await
+}
@@ -0,0 +1,106 @@
+use std::{cmp::Ordering, time::Duration};
+
+use crate::{
+    database::StatsRepo,
+    handler::{commands::check_server_setup, Context, Error},
+    helper::embed::Custom,
+    i18n::{get_locale, tr, tr_args},
+    tickets::format_wait,
+};
+use poise::{command, serenity_prelude::CreateEmbed, CreateReply};
+
+use super::format_rating;
+
+/// Maximum number of subjects displayed
+const MAX_SUBJECTS: usize = 10;
+
+/// Shows the ticket statistics of the server
+#[command(
+    slash_command,
+    prefix_command,
+    required_permissions = "MANAGE_CHANNELS",
+    check = "check_server_setup",
+    guild_only
+)]
+pub async fn stats(ctx: Context<'_>) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or("Guild ID not found")?;
+    let guild = guild_id.to_partial_guild(ctx.http()).await?;
+    let locale = get_locale(ctx).await;
+    let mut pool = ctx.data().pool.acquire().await?;
+
+    let totals = StatsRepo::totals(&mut pool, guild_id).await?;
+
+    // Subjects with the lowest ratings first, as they are the ones needing attention
+    let mut subjects = StatsRepo::subjects(&mut pool, guild_id).await?;
+    subjects.sort_by(|a, b| match (a.average_rating, b.average_rating) {
+        (Some(a), Some(b)) => a.total_cmp(&b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    });
+
+    let subject_lines = subjects
+        .into_iter()
+        .take(MAX_SUBJECTS)
+        .map(|subject| {
+            tr_args(
+                locale,
+                "stats.subject_entry",
+                &[
+                    ("subject", &subject.name),
+                    ("count", &subject.total_tickets.to_string()),
+                    (
+                        "rating",
+                        &format_rating(locale, subject.average_rating, subject.ratings),
+                    ),
+                ],
+            )
+        })
+        .collect::<Vec<String>>()
+        .join("\n");
+
+    let embed = CreateEmbed::default_bot_embed(&guild)
+        .title(tr(locale, "stats.title"))
+        .field(
+            tr(locale, "stats.total_field"),
+            totals.total.to_string(),
+            true,
+        )
+        .field(
+            tr(locale, "stats.open_field"),
+            totals.open.to_string(),
+            true,
+        )
+        .field(
+            tr(locale, "stats.claimed_field"),
+            totals.claimed.to_string(),
+            true,
+        )
+        .field(
+            tr(locale, "stats.rating_field"),
+            format_rating(locale, totals.average_rating, totals.ratings),
+            true,
+        )
+        .field(
+            tr(locale, "stats.first_response_field"),
+            totals.average_first_response.map_or_else(
+                || tr(locale, "stats.no_answers"),
+                |secs| format_wait(Duration::from_secs_f64(secs.max(0.0)), locale),
+            ),
+            true,
+        )
+        .field(
+            tr(locale, "stats.subjects_field"),
+            if subject_lines.is_empty() {
+                tr(locale, "stats.no_subjects")
+            } else {
+                subject_lines
+            },
+            false,
+        );
+
+    ctx.send(CreateReply::default().embed(embed).reply(false))
+        .await?;
+
+    Ok(())
+}
@@ -1,6 +1,42 @@
 //! This module regroups commands related to the subject of a ticket.
 
+use crate::{database::SubjectRepo, handler::Context, helper::fuzzy_match::match_subjects};
+
 // Crate modules
 pub mod add;
+pub mod assign;
+pub mod hours;
 pub mod list;
 pub mod remove;
+pub mod role;
+
+/// Number of subjects suggested by the autocompletion
+const AUTOCOMPLETE_RESULTS: usize = 25;
+
+/// Autocompletes a subject name of the current server
+pub async fn autocomplete_subject(ctx: Context<'_>, partial: &str) -> Vec<String> {
+    let Some(guild_id) = ctx.guild_id() else {
+        return Vec::new();
+    };
+
+    let Ok(mut pool) = ctx.data().pool.acquire().await else {
+        return Vec::new();
+    };
+
+    let Ok(subjects) = SubjectRepo::all(&mut pool, guild_id).await else {
+        return Vec::new();
+    };
+
+    if partial.is_empty() {
+        return subjects
+            .into_iter()
+            .take(AUTOCOMPLETE_RESULTS)
+            .map(|subject| subject.name)
+            .collect();
+    }
+
+    match_subjects(&subjects, partial, AUTOCOMPLETE_RESULTS)
+        .into_iter()
+        .map(|subject| subject.name)
+        .collect()
+}
@@ -1,11 +1,12 @@
 //! Adds a single subject to the list of subjects that can be used to better categorize tickets
 
 use crate::{
+    database::{ServerRepo, SubjectRepo},
     handler::{commands::check_server_setup, Context, Error},
     helper::parser::parse_discord_channel_id_url,
+    i18n::{get_locale, tr, tr_args},
 };
 use poise::command;
-use std::time::Duration;
 
 /// Adds a single subject to the list of subjects that can be used to better categorize tickets
 #[command(
@@ -32,21 +33,27 @@ pub async fn add_slash(
     guild_only
 )]
 pub async fn add_prefix(ctx: Context<'_>, #[rest] name: Option<String>) -> Result<(), Error> {
+    let locale = get_locale(ctx).await;
+
     let Some(name) = name else {
-        ctx.reply("Usage : `$subjectadd <subject>`").await?;
+        let guild_id = ctx.guild_id().ok_or("Failed to get guild ID")?;
+        let mut pool = ctx.data().pool.acquire().await?;
+        let prefix = ServerRepo::prefix(&mut pool, guild_id).await?;
+        ctx.reply(tr_args(locale, "subject.usage", &[("prefix", &prefix)]))
+            .await?;
         return Ok(());
     };
 
-    ctx.reply("Please provide the link to the channel you want to link the subject to")
-        .await?;
+    ctx.reply(tr(locale, "subject.channel_prompt")).await?;
 
     let Some(channel_id) = ctx
         .author()
         .await_reply(ctx)
-        .timeout(Duration::from_secs(60))
+        .timeout(ctx.data().timeouts.prompt)
         .await
     else {
-        ctx.reply("❌ - No channel ID provided").await?;
+        ctx.reply(format!("❌ - {}", tr(locale, "subject.no_channel")))
+            .await?;
         return Ok(());
     };
 
@@ -58,53 +65,37 @@ async fn add_subject(ctx: Context<'_>, name: String, channel_id: String) -> Resu
     const MAX_SUBJECT_LENGTH: usize = 100;
     const MIN_SUBJECT_LENGTH: usize = 1;
 
+    let locale = get_locale(ctx).await;
+
     if name.len() > MAX_SUBJECT_LENGTH {
-        ctx.reply("❌ - The subject is too long").await?;
+        ctx.reply(format!("❌ - {}", tr(locale, "subject.too_long")))
+            .await?;
         return Ok(());
     }
     if name.len() < MIN_SUBJECT_LENGTH {
-        ctx.reply("❌ - The subject is too short").await?;
+        ctx.reply(format!("❌ - {}", tr(locale, "subject.too_short")))
+            .await?;
         return Ok(());
     }
 
     let Some(channel_id) = parse_discord_channel_id_url(&channel_id) else {
-        ctx.reply("❌ - Invalid channel ID").await?;
+        ctx.reply(format!("❌ - {}", tr(locale, "subject.invalid_channel")))
+            .await?;
         return Ok(());
     };
 
     let mut pool = ctx.data().pool.acquire().await?;
-    let guild_id = ctx.guild_id().ok_or("Failed to get guild ID")?.get();
+    let guild_id = ctx.guild_id().ok_or("Failed to get guild ID")?;
 
-    if subject_exists(&mut pool, &name, guild_id).await? {
-        ctx.reply("❌ - Subject already exists").await?;
+    if SubjectRepo::exists(&mut pool, guild_id, &name).await? {
+        ctx.reply(format!("❌ - {}", tr(locale, "subject.already_exists")))
+            .await?;
         return Ok(());
     }
 
-    sqlx::query!(
-        "INSERT INTO subjects (name, server_id, channel_id) VALUES ($1, $2, $3)",
-        name,
-        guild_id as i64,
-        channel_id as i64
-    )
-    .execute(&mut *pool)
-    .await?;
+    SubjectRepo::insert(&mut pool, guild_id, &name, channel_id.into()).await?;
 
     ctx.reply("✅").await?;
 
     Ok(())
 }
-
-async fn subject_exists(
-    pool: &mut sqlx::PgConnection,
-    name: &str,
-    guild_id: u64,
-) -> Result<bool, Error> {
-    let row = sqlx::query!(
-        "SELECT name FROM subjects WHERE name = $1 AND server_id = $2",
-        name,
-        guild_id as i64
-    )
-    .fetch_optional(pool)
-    .await?;
-    Ok(row.is_some())
-}
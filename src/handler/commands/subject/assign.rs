@@ -0,0 +1,43 @@
+use crate::{
+    database::SubjectRepo,
+    handler::{
+        commands::{check_server_setup, subject::autocomplete_subject},
+        Context, Error,
+    },
+    i18n::{get_locale, tr},
+    tickets::assign::AssignMode,
+};
+use poise::command;
+
+/// Changes how the tickets of a subject are given to the helpers
+#[command(
+    slash_command,
+    prefix_command,
+    required_permissions = "MANAGE_CHANNELS",
+    rename = "subjectassign",
+    check = "check_server_setup",
+    guild_only
+)]
+pub async fn assign(
+    ctx: Context<'_>,
+    #[description = "The subject to change"]
+    #[autocomplete = "autocomplete_subject"]
+    name: String,
+    #[description = "How its tickets are assigned, the mode of the server if omitted"] mode: Option<
+        AssignMode,
+    >,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or("Guild ID not found")?;
+    let locale = get_locale(ctx).await;
+    let mut pool = ctx.data().pool.acquire().await?;
+
+    if !SubjectRepo::set_assign_mode(&mut pool, guild_id, &name, mode).await? {
+        ctx.reply(format!("❌ - {}", tr(locale, "subject.not_found")))
+            .await?;
+        return Ok(());
+    }
+
+    ctx.reply("✅").await?;
+
+    Ok(())
+}
@@ -0,0 +1,48 @@
+use crate::{
+    database::SubjectRepo,
+    handler::{
+        commands::{check_server_setup, subject::autocomplete_subject},
+        Context, Error,
+    },
+    i18n::{get_locale, tr},
+    tickets::office_hours::Schedule,
+};
+use poise::command;
+
+/// Narrows the office hours of the server for the tickets of a subject
+#[command(
+    slash_command,
+    prefix_command,
+    required_permissions = "MANAGE_CHANNELS",
+    rename = "subjecthours",
+    check = "check_server_setup",
+    guild_only
+)]
+pub async fn hours(
+    ctx: Context<'_>,
+    #[description = "The subject to change"]
+    #[autocomplete = "autocomplete_subject"]
+    name: String,
+    #[description = "The weekly hours, such as `mon 14:00-18:00`, the ones of the server if omitted"]
+    #[rest]
+    schedule: Option<String>,
+) -> Result<(), Error> {
+    let schedule = schedule
+        .map(|schedule| schedule.parse::<Schedule>())
+        .transpose()
+        .map_err(Error::user)?;
+
+    let guild_id = ctx.guild_id().ok_or("Guild ID not found")?;
+    let locale = get_locale(ctx).await;
+    let mut pool = ctx.data().pool.acquire().await?;
+
+    if !SubjectRepo::set_office_hours(&mut pool, guild_id, &name, schedule.as_ref()).await? {
+        ctx.reply(format!("❌ - {}", tr(locale, "subject.not_found")))
+            .await?;
+        return Ok(());
+    }
+
+    ctx.reply("✅").await?;
+
+    Ok(())
+}
@@ -1,39 +1,119 @@
-use crate::handler::{commands::check_server_setup, Context, Error};
-use poise::command;
+use std::collections::HashSet;
+
+use crate::{
+    database::StatsRepo,
+    handler::{commands::check_server_setup, Context, Error},
+    helper::{
+        embed::Custom,
+        pagination::{chunk_pages, paginate},
+    },
+    i18n::{get_locale, tr, tr_args},
+};
+use poise::{
+    command,
+    serenity_prelude::{ChannelId, CreateEmbed, Mentionable, Permissions},
+};
+
+/// Number of subjects displayed on a single page
+const SUBJECTS_PER_PAGE: usize = 10;
 
 /// List all the subjects that can be used to better categorize tickets
+///
+/// Members only see the subjects linked to channels they can view
 #[command(
     slash_command,
     prefix_command,
-    required_permissions = "MANAGE_CHANNELS",
     rename = "subjectlist",
     check = "check_server_setup",
     guild_only
 )]
 pub async fn list(ctx: Context<'_>) -> Result<(), Error> {
-    let guild_id = ctx.guild_id().ok_or("❌ - Guild ID not found")?;
+    let guild_id = ctx.guild_id().ok_or("Guild ID not found")?;
+    let guild = guild_id.to_partial_guild(ctx.http()).await?;
+    let locale = get_locale(ctx).await;
     let mut pool = ctx.data().pool.acquire().await?;
 
-    let subjects = sqlx::query!(
-        "SELECT name, channel_id FROM subjects WHERE server_id = $1",
-        guild_id.get() as i64
-    )
-    .fetch_all(&mut *pool)
-    .await?;
-
-    let answer = subjects
-        .iter()
-        .map(|subject| format!("- {} - <#{}>", subject.name, subject.channel_id))
-        .collect::<Vec<String>>()
-        .join("\n");
-
-    // TODO: Make into a cleaner embed message
-    if answer.is_empty() {
-        ctx.reply("❌ - No subjects found").await?;
+    let subjects = StatsRepo::subjects(&mut pool, guild_id).await?;
+
+    let is_staff = is_staff(ctx).await;
+    let visible_channels = if is_staff {
+        None
+    } else {
+        Some(visible_channels(ctx).await)
+    };
+
+    let fields: Vec<(String, String)> = subjects
+        .into_iter()
+        .filter(|subject| {
+            visible_channels
+                .as_ref()
+                .is_none_or(|channels| channels.contains(&subject.channel_id))
+        })
+        .map(|subject| {
+            let value = tr_args(
+                locale,
+                "subject.list_entry",
+                &[
+                    ("channel", &subject.channel_id.mention().to_string()),
+                    ("open", &subject.open_tickets.to_string()),
+                    ("total", &subject.total_tickets.to_string()),
+                ],
+            );
+            (subject.name, value)
+        })
+        .collect();
+
+    if fields.is_empty() {
+        ctx.reply(format!("❌ - {}", tr(locale, "subject.none_found")))
+            .await?;
         return Ok(());
     }
 
-    ctx.reply(answer).await?;
+    let total = fields.len().to_string();
+    let pages = chunk_pages(fields, SUBJECTS_PER_PAGE)
+        .into_iter()
+        .map(|page| {
+            CreateEmbed::default_bot_embed(&guild)
+                .title(tr(locale, "subject.list_title"))
+                .description(tr_args(locale, "subject.list_count", &[("count", &total)]))
+                .fields(page.into_iter().map(|(name, value)| (name, value, false)))
+        })
+        .collect();
+
+    paginate(ctx, pages, !is_staff).await
+}
+
+/// Returns whether the author can manage subjects (and therefore see every subject)
+async fn is_staff(ctx: Context<'_>) -> bool {
+    let Some(member) = ctx.author_member().await else {
+        return false;
+    };
+
+    ctx.guild().is_some_and(|guild| {
+        guild
+            .member_permissions(&member)
+            .contains(Permissions::MANAGE_CHANNELS)
+    })
+}
+
+/// Returns the channels of the guild the author is able to view
+async fn visible_channels(ctx: Context<'_>) -> HashSet<ChannelId> {
+    let Some(member) = ctx.author_member().await else {
+        return HashSet::new();
+    };
+
+    let Some(guild) = ctx.guild() else {
+        return HashSet::new();
+    };
 
-    Ok(())
+    guild
+        .channels
+        .values()
+        .filter(|channel| {
+            guild
+                .user_permissions_in(channel, &member)
+                .contains(Permissions::VIEW_CHANNEL)
+        })
+        .map(|channel| channel.id)
+        .collect()
 }
@@ -1,4 +1,8 @@
-use crate::handler::{commands::check_server_setup, Context, Error};
+use crate::{
+    database::SubjectRepo,
+    handler::{commands::check_server_setup, Context, Error},
+    i18n::{get_locale, tr},
+};
 use poise::command;
 
 /// Remove a subject from the list of subjects that can be used to better categorize tickets
@@ -16,30 +20,16 @@ pub async fn remove(
     #[rest]
     name: String,
 ) -> Result<(), Error> {
-    let guild_id = ctx.guild_id().ok_or("❌ - Guild ID not found")?;
+    let guild_id = ctx.guild_id().ok_or("Guild ID not found")?;
+    let locale = get_locale(ctx).await;
     let mut pool = ctx.data().pool.acquire().await?;
 
-    let subject = sqlx::query!(
-        "SELECT name FROM subjects WHERE server_id = $1 AND name = $2",
-        guild_id.get() as i64,
-        name
-    )
-    .fetch_optional(&mut *pool)
-    .await?;
-
-    if subject.is_none() {
-        ctx.reply("❌ - Subject not found").await?;
+    if !SubjectRepo::delete(&mut pool, guild_id, &name).await? {
+        ctx.reply(format!("❌ - {}", tr(locale, "subject.not_found")))
+            .await?;
         return Ok(());
     }
 
-    sqlx::query!(
-        "DELETE FROM subjects WHERE server_id = $1 AND name = $2",
-        guild_id.get() as i64,
-        name
-    )
-    .execute(&mut *pool)
-    .await?;
-
     ctx.reply("✅").await?;
 
     Ok(())
@@ -0,0 +1,43 @@
+use crate::{
+    database::SubjectRepo,
+    handler::{
+        commands::{check_server_setup, subject::autocomplete_subject},
+        Context, Error,
+    },
+    i18n::{get_locale, tr},
+};
+use poise::{command, serenity_prelude::Role};
+
+/// Restricts the tickets of a subject to the helpers holding a role
+#[command(
+    slash_command,
+    prefix_command,
+    required_permissions = "MANAGE_CHANNELS",
+    rename = "subjectrole",
+    check = "check_server_setup",
+    guild_only
+)]
+pub async fn role(
+    ctx: Context<'_>,
+    #[description = "The subject to restrict"]
+    #[autocomplete = "autocomplete_subject"]
+    name: String,
+    #[description = "The role required to take its tickets, every helper if omitted"] role: Option<
+        Role,
+    >,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or("Guild ID not found")?;
+    let locale = get_locale(ctx).await;
+    let mut pool = ctx.data().pool.acquire().await?;
+
+    let role_id = role.map(|role| role.id);
+    if !SubjectRepo::set_helper_role(&mut pool, guild_id, &name, role_id).await? {
+        ctx.reply(format!("❌ - {}", tr(locale, "subject.not_found")))
+            .await?;
+        return Ok(());
+    }
+
+    ctx.reply("✅").await?;
+
+    Ok(())
+}
@@ -0,0 +1,66 @@
+//! This module regroups commands related to the message templates.
+
+use crate::{
+    handler::{Context, Error},
+    helper::template::unknown_placeholders,
+    tickets::templates::{TemplateKind, MAX_TEMPLATE_LENGTH},
+};
+use poise::command;
+
+pub mod list;
+pub mod preview;
+pub mod reset;
+pub mod set;
+
+/// Customizes the messages sent by the bot
+#[command(
+    slash_command,
+    prefix_command,
+    subcommands("set::set", "reset::reset", "preview::preview", "list::list"),
+    subcommand_required,
+    required_permissions = "ADMINISTRATOR",
+    guild_only
+)]
+pub async fn template(_: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Checks that a template fits in a message and only uses the placeholders of its kind
+fn validate_template(kind: TemplateKind, content: &str) -> Result<(), String> {
+    if content.chars().count() > MAX_TEMPLATE_LENGTH {
+        return Err(format!(
+            "The template cannot be longer than {MAX_TEMPLATE_LENGTH} characters"
+        ));
+    }
+
+    let unknown = unknown_placeholders(content, kind.placeholders());
+    if !unknown.is_empty() {
+        return Err(format!(
+            "Unknown placeholder(s): {}. Available: {}",
+            format_placeholders(&unknown),
+            format_placeholders(kind.placeholders())
+        ));
+    }
+
+    Ok(())
+}
+
+/// Formats a list of placeholders as `{a}, {b}`
+fn format_placeholders(placeholders: &[&str]) -> String {
+    placeholders
+        .iter()
+        .map(|placeholder| format!("`{{{placeholder}}}`"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_template() {
+        assert!(validate_template(TemplateKind::Close, "Closed: {reason}").is_ok());
+        assert!(validate_template(TemplateKind::Panel, "Hello {user}").is_err());
+    }
+}
@@ -0,0 +1,49 @@
+use crate::{
+    handler::{commands::check_server_setup, Context, Error},
+    helper::embed::Custom,
+    i18n::{get_locale, tr, tr_args},
+    tickets::templates::{get_custom_template, TemplateKind},
+};
+use poise::{command, serenity_prelude::CreateEmbed, CreateReply};
+
+use super::format_placeholders;
+
+/// Lists the messages that can be customized, with their current text and placeholders
+#[command(
+    slash_command,
+    prefix_command,
+    required_permissions = "ADMINISTRATOR",
+    check = "check_server_setup",
+    guild_only
+)]
+pub async fn list(ctx: Context<'_>) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or("Failed to get guild ID")?;
+    let guild = guild_id.to_partial_guild(ctx.http()).await?;
+    let locale = get_locale(ctx).await;
+    let mut pool = ctx.data().pool.acquire().await?;
+
+    let mut embed = CreateEmbed::default_bot_embed(&guild).title(tr(locale, "template.list_title"));
+
+    for kind in TemplateKind::ALL {
+        let (text, origin) = match get_custom_template(&mut pool, guild_id, kind).await? {
+            Some(template) => (template, tr(locale, "template.custom")),
+            None => (kind.default_text(locale), tr(locale, "template.default")),
+        };
+
+        let value = tr_args(
+            locale,
+            "template.list_entry",
+            &[
+                ("text", &text),
+                ("origin", &origin),
+                ("placeholders", &format_placeholders(kind.placeholders())),
+            ],
+        );
+        embed = embed.field(kind.as_str(), value, false);
+    }
+
+    ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,60 @@
+use crate::{
+    handler::{commands::check_server_setup, Context, Error},
+    helper::{embed::Custom, template::render},
+    i18n::get_locale,
+    tickets::templates::{get_custom_template, TemplateKind},
+};
+use poise::{command, serenity_prelude::CreateEmbed, CreateReply};
+
+use super::validate_template;
+
+/// Shows a message as it would be sent, filled with example values
+#[command(
+    slash_command,
+    prefix_command,
+    required_permissions = "ADMINISTRATOR",
+    check = "check_server_setup",
+    guild_only
+)]
+pub async fn preview(
+    ctx: Context<'_>,
+    #[description = "The message to preview"] kind: TemplateKind,
+    #[description = "A text to try, the current template if empty"]
+    #[rest]
+    content: Option<String>,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or("Failed to get guild ID")?;
+    let guild = guild_id.to_partial_guild(ctx.http()).await?;
+    let locale = get_locale(ctx).await;
+
+    let template = match content {
+        Some(content) => {
+            if let Err(error) = validate_template(kind, &content) {
+                ctx.reply(format!("❌ - {error}")).await?;
+                return Ok(());
+            }
+            content
+        }
+        None => {
+            let mut pool = ctx.data().pool.acquire().await?;
+            get_custom_template(&mut pool, guild_id, kind)
+                .await?
+                .unwrap_or_else(|| kind.default_text(locale))
+        }
+    };
+
+    let samples = kind.sample_values();
+    let values: Vec<(&str, &str)> = samples
+        .iter()
+        .map(|(name, value)| (*name, value.as_str()))
+        .collect();
+
+    let embed = CreateEmbed::default_bot_embed(&guild)
+        .title(format!("Preview of the {} template", kind.as_str()))
+        .description(render(&template, &values));
+
+    ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,27 @@
+use crate::{
+    handler::{commands::check_server_setup, Context, Error},
+    tickets::templates::{reset_template, TemplateKind},
+};
+use poise::command;
+
+/// Restores the default text of a message
+#[command(
+    slash_command,
+    prefix_command,
+    required_permissions = "ADMINISTRATOR",
+    check = "check_server_setup",
+    guild_only
+)]
+pub async fn reset(
+    ctx: Context<'_>,
+    #[description = "The message to restore"] kind: TemplateKind,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or("Failed to get guild ID")?;
+    let mut pool = ctx.data().pool.acquire().await?;
+
+    reset_template(&mut pool, guild_id, kind).await?;
+
+    ctx.reply("✅").await?;
+
+    Ok(())
+}
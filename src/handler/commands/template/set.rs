@@ -0,0 +1,37 @@
+use crate::{
+    handler::{commands::check_server_setup, Context, Error},
+    tickets::templates::{set_template, TemplateKind},
+};
+use poise::command;
+
+use super::validate_template;
+
+/// Sets the template of a message, see `/template list` for the available placeholders
+#[command(
+    slash_command,
+    prefix_command,
+    required_permissions = "ADMINISTRATOR",
+    check = "check_server_setup",
+    guild_only
+)]
+pub async fn set(
+    ctx: Context<'_>,
+    #[description = "The message to customize"] kind: TemplateKind,
+    #[description = "The new text of the message"]
+    #[rest]
+    content: String,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or("Failed to get guild ID")?;
+
+    if let Err(error) = validate_template(kind, &content) {
+        ctx.reply(format!("❌ - {error}")).await?;
+        return Ok(());
+    }
+
+    let mut pool = ctx.data().pool.acquire().await?;
+    set_template(&mut pool, guild_id, kind, &content).await?;
+
+    ctx.reply("✅").await?;
+
+    Ok(())
+}
@@ -1,2 +1,27 @@
+//! This module regroups commands related to tickets.
+
+use crate::handler::{Context, Error};
+use poise::command;
+
+pub mod add;
 pub mod claim;
 pub mod close;
+pub mod close_request;
+pub mod duty;
+pub mod next;
+pub mod open;
+pub mod priority;
+pub mod queue;
+pub mod remove;
+
+/// Manages tickets
+#[command(
+    slash_command,
+    prefix_command,
+    subcommands("open::open", "add::add", "remove::remove", "priority::priority"),
+    subcommand_required,
+    guild_only
+)]
+pub async fn ticket(_: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
@@ -0,0 +1,22 @@
+use crate::{
+    handler::{commands::check_server_setup, Context, Error},
+    tickets::participants,
+};
+use poise::{command, serenity_prelude::User};
+
+/// Adds a user to the current ticket
+#[command(
+    slash_command,
+    prefix_command,
+    check = "check_server_setup",
+    guild_only
+)]
+pub async fn add(
+    ctx: Context<'_>,
+    #[description = "The user to add"] user: User,
+) -> Result<(), Error> {
+    participants::add(&ctx, &user).await?;
+    ctx.reply("✅").await?;
+
+    Ok(())
+}
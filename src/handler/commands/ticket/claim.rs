@@ -12,14 +12,8 @@ use poise::command;
     guild_only
 )]
 pub async fn claim(ctx: Context<'_>) -> Result<(), Error> {
-    match tickets::claim_ticket(&ctx).await {
-        Ok(()) => {
-            ctx.reply("✅").await?;
-        }
-        Err(e) => {
-            error!("Error claiming ticket: {}", e);
-        }
-    }
+    tickets::claim_ticket(&ctx).await?;
+    ctx.reply("✅").await?;
 
     Ok(())
 }
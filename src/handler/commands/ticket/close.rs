@@ -8,14 +8,16 @@ use poise::command;
 #[command(
     slash_command,
     prefix_command,
-    required_permissions = "MANAGE_CHANNELS",
     check = "check_server_setup",
     guild_only
 )]
-pub async fn close(ctx: Context<'_>) -> Result<(), Error> {
-    if let Err(error) = tickets::close_ticket(&ctx).await {
-        error!("Error closing ticket: {}", error);
-    }
+pub async fn close(
+    ctx: Context<'_>,
+    #[description = "The reason of the closing"]
+    #[rest]
+    reason: Option<String>,
+) -> Result<(), Error> {
+    tickets::close_ticket(&ctx, reason).await?;
 
     Ok(())
 }
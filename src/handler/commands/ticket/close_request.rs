@@ -0,0 +1,24 @@
+use crate::{
+    handler::{commands::check_server_setup, Context, Error},
+    tickets,
+};
+use poise::command;
+
+/// Asks the author of a ticket to confirm that their issue is resolved
+#[command(
+    slash_command,
+    prefix_command,
+    rename = "closerequest",
+    check = "check_server_setup",
+    guild_only
+)]
+pub async fn close_request(
+    ctx: Context<'_>,
+    #[description = "The reason of the closing"]
+    #[rest]
+    reason: Option<String>,
+) -> Result<(), Error> {
+    tickets::request_close_ticket(&ctx, reason).await?;
+
+    Ok(())
+}
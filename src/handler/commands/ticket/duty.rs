@@ -0,0 +1,36 @@
+use crate::{
+    handler::{commands::check_server_setup, Context, Error},
+    i18n::{get_locale, tr},
+    tickets::assign,
+};
+use poise::command;
+
+/// Whether a helper takes the assigned tickets
+#[derive(poise::ChoiceParameter)]
+pub enum Duty {
+    #[name = "on"]
+    On,
+    #[name = "off"]
+    Off,
+}
+
+/// Starts or stops taking the tickets assigned automatically
+#[command(
+    slash_command,
+    prefix_command,
+    check = "check_server_setup",
+    guild_only
+)]
+pub async fn duty(
+    ctx: Context<'_>,
+    #[description = "Take the assigned tickets (on) or stop taking them (off)"] state: Duty,
+) -> Result<(), Error> {
+    let on_duty = matches!(state, Duty::On);
+    assign::set_duty(&ctx, on_duty).await?;
+
+    let key = if on_duty { "duty.on" } else { "duty.off" };
+    let locale = get_locale(ctx).await;
+    ctx.reply(format!("✅ - {}", tr(locale, key))).await?;
+
+    Ok(())
+}
@@ -0,0 +1,34 @@
+use crate::{
+    handler::{commands::check_server_setup, Context, Error},
+    i18n::{get_locale, tr, tr_args},
+    tickets,
+};
+use poise::{command, serenity_prelude::Mentionable};
+
+/// Claims the next ticket of the queue
+#[command(
+    slash_command,
+    prefix_command,
+    check = "check_server_setup",
+    guild_only
+)]
+pub async fn next(ctx: Context<'_>) -> Result<(), Error> {
+    let locale = get_locale(ctx).await;
+
+    match tickets::claim_next_ticket(&ctx).await? {
+        Some(ticket) => {
+            let reply = tr_args(
+                locale,
+                "queue.next",
+                &[("ticket", &ticket.location.id().mention().to_string())],
+            );
+            ctx.reply(format!("✅ - {reply}")).await?;
+        }
+        None => {
+            ctx.reply(format!("❌ - {}", tr(locale, "queue.next_empty")))
+                .await?;
+        }
+    }
+
+    Ok(())
+}
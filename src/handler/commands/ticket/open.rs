@@ -0,0 +1,77 @@
+use crate::{
+    database::SubjectRepo,
+    handler::{
+        commands::{check_server_setup, subject::autocomplete_subject, SimpleMessage},
+        Context, Error,
+    },
+    i18n::{get_locale, tr, tr_args},
+    tickets::{self, priority::Priority, CreateOptions},
+};
+use poise::{command, serenity_prelude::Member};
+
+/// Opens a ticket on behalf of a member
+#[command(
+    slash_command,
+    prefix_command,
+    required_permissions = "MANAGE_CHANNELS",
+    check = "check_server_setup",
+    guild_only
+)]
+pub async fn open(
+    ctx: Context<'_>,
+    #[description = "The member to open the ticket for"] member: Member,
+    #[description = "The subject of the ticket"]
+    #[autocomplete = "autocomplete_subject"]
+    subject: Option<String>,
+    #[description = "The priority of the ticket"] priority: Option<Priority>,
+    #[description = "A first message to post in the ticket"]
+    #[rest]
+    message: Option<String>,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or("Failed to get guild ID")?;
+    let locale = get_locale(ctx).await;
+
+    if member.user.bot {
+        ctx.reply(format!("❌ - {}", tr(locale, "ticket.bot_member")))
+            .await?;
+        return Ok(());
+    }
+
+    let mut pool = ctx.data().pool.acquire().await?;
+
+    let subject = match subject {
+        Some(name) => {
+            let subjects = SubjectRepo::all(&mut pool, guild_id).await?;
+            let Some(subject) = subjects.into_iter().find(|subject| subject.name == name) else {
+                ctx.reply(format!("❌ - {}", tr(locale, "subject.not_found")))
+                    .await?;
+                return Ok(());
+            };
+            Some(subject)
+        }
+        None => None,
+    };
+
+    let staff = ctx
+        .author_member()
+        .await
+        .ok_or("Failed to get member")?
+        .into_owned();
+
+    ctx.send_simple_message(&tr_args(
+        locale,
+        "ticket.opening_for",
+        &[("member", member.display_name())],
+    ))
+    .await?;
+
+    let options = CreateOptions {
+        subject,
+        opened_by: Some(staff),
+        message,
+        priority,
+        ..Default::default()
+    };
+
+    tickets::create_ticket(ctx.serenity_context(), ctx.data(), &member, options).await
+}
@@ -0,0 +1,22 @@
+use crate::{
+    handler::{commands::check_server_setup, Context, Error},
+    tickets::priority::{self, Priority},
+};
+use poise::command;
+
+/// Changes the priority of the current ticket
+#[command(
+    slash_command,
+    prefix_command,
+    check = "check_server_setup",
+    guild_only
+)]
+pub async fn priority(
+    ctx: Context<'_>,
+    #[description = "The new priority of the ticket"] level: Priority,
+) -> Result<(), Error> {
+    priority::set_priority(&ctx, level).await?;
+    ctx.reply("✅").await?;
+
+    Ok(())
+}
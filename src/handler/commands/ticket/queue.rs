@@ -0,0 +1,70 @@
+use crate::{
+    handler::{commands::check_server_setup, Context, Error},
+    helper::{
+        embed::Custom,
+        pagination::{chunk_pages, paginate},
+    },
+    i18n::{get_locale, tr, tr_args},
+    tickets,
+};
+use poise::{
+    command,
+    serenity_prelude::{CreateEmbed, Mentionable},
+};
+
+/// Number of tickets displayed on a single page
+const TICKETS_PER_PAGE: usize = 10;
+
+/// Lists the unclaimed tickets, the most urgent first, then the oldest first
+#[command(
+    slash_command,
+    prefix_command,
+    check = "check_server_setup",
+    guild_only
+)]
+pub async fn queue(ctx: Context<'_>) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or("Guild ID not found")?;
+    let guild = guild_id.to_partial_guild(ctx.http()).await?;
+
+    let locale = get_locale(ctx).await;
+
+    let queue = tickets::ticket_queue(&ctx).await?;
+
+    if queue.is_empty() {
+        ctx.reply(format!("✅ - {}", tr(locale, "queue.empty")))
+            .await?;
+        return Ok(());
+    }
+
+    let lines: Vec<String> = queue
+        .into_iter()
+        .enumerate()
+        .map(|(rank, ticket)| {
+            let subject = ticket
+                .subject
+                .unwrap_or_else(|| tr(locale, "queue.no_subject"));
+            tr_args(
+                locale,
+                "queue.entry",
+                &[
+                    ("rank", &(rank + 1).to_string()),
+                    ("ticket", &ticket.location.id().mention().to_string()),
+                    ("subject", &subject),
+                    ("priority", &ticket.priority.label(locale)),
+                    ("wait", &tickets::format_wait(ticket.waiting, locale)),
+                ],
+            )
+        })
+        .collect();
+
+    let pages = chunk_pages(lines, TICKETS_PER_PAGE)
+        .into_iter()
+        .map(|page| {
+            CreateEmbed::default_bot_embed(&guild)
+                .title(tr(locale, "queue.title"))
+                .description(page.join("\n"))
+        })
+        .collect();
+
+    paginate(ctx, pages, true).await
+}
@@ -0,0 +1,22 @@
+use crate::{
+    handler::{commands::check_server_setup, Context, Error},
+    tickets::participants,
+};
+use poise::{command, serenity_prelude::User};
+
+/// Removes a user from the current ticket
+#[command(
+    slash_command,
+    prefix_command,
+    check = "check_server_setup",
+    guild_only
+)]
+pub async fn remove(
+    ctx: Context<'_>,
+    #[description = "The user to remove"] user: User,
+) -> Result<(), Error> {
+    participants::remove(&ctx, &user).await?;
+    ctx.reply("✅").await?;
+
+    Ok(())
+}
@@ -1,6 +1,14 @@
-use poise::serenity_prelude::{self as serenity, CacheHttp, Context, FullEvent, ReactionType};
+use poise::serenity_prelude::{
+    self as serenity, CacheHttp, Context, CreateInteractionResponse,
+    CreateInteractionResponseFollowup, CreateInteractionResponseMessage, CreateMessage, FullEvent,
+    GuildId, Interaction, ReactionType,
+};
 
-use crate::tickets::{self, TICKET_EMOJI};
+use crate::{
+    error,
+    i18n::{get_server_locale, tr, Locale},
+    tickets::{self, activity, feedback, panels, CreateOptions, TICKET_EMOJI},
+};
 
 use super::{Data, Error};
 
@@ -10,55 +18,171 @@ pub async fn event_handler(
     _: poise::FrameworkContext<'_, Data, Error>,
     data: &Data,
 ) -> Result<(), Error> {
-    // TODO: Improve error handling
-    if let FullEvent::ReactionAdd { add_reaction } = event {
-        handle_reaction(ctx, add_reaction, data).await?;
+    match event {
+        FullEvent::ReactionAdd { add_reaction } => {
+            if let Err(error) = handle_reaction(ctx, add_reaction, data).await {
+                report_reaction_error(ctx, data, add_reaction, error).await;
+            }
+        }
+        FullEvent::Message { new_message } => {
+            if let Err(error) = activity::handle_message(&ctx.http, data, new_message).await {
+                report_error(ctx, data, new_message.guild_id, "Ticket message", &error).await;
+            }
+        }
+        FullEvent::InteractionCreate { interaction } => {
+            let result = match feedback::handle_interaction(ctx, data, interaction).await {
+                Ok(()) => panels::handle_interaction(ctx, data, interaction).await,
+                error => error,
+            };
+
+            if let Err(error) = result {
+                report_interaction_error(ctx, data, interaction, error).await;
+            }
+        }
+        _ => {}
     }
     Ok(())
 }
 
+/// Reports the error of an interaction: mistakes are answered to the user, failures are logged
+/// and reported in the log channel of the server
+async fn report_interaction_error(
+    ctx: &Context,
+    data: &Data,
+    interaction: &Interaction,
+    error: Error,
+) {
+    let Interaction::Component(component) = interaction else {
+        report_error(ctx, data, None, "Interaction", &error).await;
+        return;
+    };
+
+    let locale = Locale::from_discord(&component.locale).unwrap_or_default();
+    let content = if error.is_user_facing() {
+        format!("❌ - {}", error.user_message(locale))
+    } else {
+        report_error(ctx, data, component.guild_id, "Button", &error).await;
+        format!("❌ - {}", tr(locale, "errors.internal"))
+    };
+
+    // The interaction may already have been answered before failing
+    let response = CreateInteractionResponse::Message(
+        CreateInteractionResponseMessage::new()
+            .content(&content)
+            .ephemeral(true),
+    );
+    if component
+        .create_response(ctx.http(), response)
+        .await
+        .is_err()
+    {
+        let followup = CreateInteractionResponseFollowup::new()
+            .content(content)
+            .ephemeral(true);
+        if let Err(e) = component.create_followup(ctx.http(), followup).await {
+            error!("Failed to report error of interaction: {e}");
+        }
+    }
+}
+
+/// Reports the error of a ticket reaction: a reaction cannot be answered, so mistakes are sent
+/// to the member in direct message, failures are logged and reported in the log channel
+async fn report_reaction_error(
+    ctx: &Context,
+    data: &Data,
+    reaction: &serenity::Reaction,
+    error: Error,
+) {
+    if !error.is_user_facing() {
+        report_error(ctx, data, reaction.guild_id, "Ticket reaction", &error).await;
+        return;
+    }
+
+    info!("Ticket reaction refused: {error}");
+
+    let (Some(guild_id), Some(user_id)) = (reaction.guild_id, reaction.user_id) else {
+        return;
+    };
+
+    // Reactions carry no locale, the member is answered in the language of the server
+    let locale = match data.pool.acquire().await {
+        Ok(mut pool) => get_server_locale(&mut pool, guild_id).await,
+        Err(_) => Locale::default(),
+    };
+    let message = CreateMessage::new().content(format!("❌ - {}", error.user_message(locale)));
+    if let Err(e) = user_id.direct_message(ctx.http(), message).await {
+        warn!("Failed to report the refusal of a ticket reaction to {user_id}: {e}");
+    }
+}
+
+/// Logs an error that happened outside of a command and reports it in the log channel
+async fn report_error(
+    ctx: &Context,
+    data: &Data,
+    guild_id: Option<GuildId>,
+    context: &str,
+    error: &Error,
+) {
+    if error.is_user_facing() {
+        info!("Event `{context}` refused: {error}");
+        return;
+    }
+
+    error!("Error in event `{context}`: {error}");
+
+    let Some(guild_id) = guild_id else {
+        return;
+    };
+
+    if let Ok(mut pool) = data.pool.acquire().await {
+        error::alert_log_channel(ctx.http(), &mut pool, guild_id, context, error).await;
+    }
+}
+
 async fn handle_reaction(
     ctx: &Context,
     reaction: &serenity::Reaction,
     data: &Data,
 ) -> Result<(), Error> {
-    // TODO: Improve error handling
-    let guild_id = reaction.guild_id.ok_or("Failed to get guild ID")?;
+    // Every reaction of the server comes here, most are filtered out without any request
+    if !matches!(&reaction.emoji, ReactionType::Unicode(emoji) if emoji == TICKET_EMOJI) {
+        return Ok(());
+    }
+
+    let Some(guild_id) = reaction.guild_id else {
+        return Ok(());
+    };
     let user_id = reaction.user_id.ok_or("Failed to get user ID")?;
 
-    let member = guild_id.member(ctx, user_id).await?;
+    // Panels are opened with their button, reactions are still supported for older panels
+    let config = data.cache.get(&data.pool, guild_id).await?;
+    if !config.panel_messages.contains(&reaction.message_id) {
+        return Ok(());
+    }
 
-    let mut pool = data.pool.acquire().await?;
+    let member = match &reaction.member {
+        Some(member) => member.clone(),
+        None => guild_id.member(ctx, user_id).await?,
+    };
 
     if member.user.bot {
         return Ok(());
     }
 
-    if reaction.emoji != ReactionType::Unicode(TICKET_EMOJI.to_string()) {
+    let mut pool = data.pool.acquire().await?;
+    let Some(panel) =
+        panels::get_panel_by_message(&mut pool, reaction.channel_id, reaction.message_id).await?
+    else {
         return Ok(());
-    }
+    };
+    drop(pool);
 
-    let row = sqlx::query!(
-        "SELECT unclaimed_category_id FROM servers WHERE
-        id = $1 AND 
-        ticket_channel_id = $2 AND 
-        ticket_message_id = $3",
-        guild_id.get() as i64,
-        reaction.channel_id.get() as i64,
-        reaction.message_id.get() as i64
-    )
-    .fetch_optional(&mut *pool)
-    .await?;
-
-    match row {
-        Some(row) => {
-            let unclaimed_id = row.unclaimed_category_id;
-            tickets::create_ticket(ctx, data, &member, unclaimed_id as u64).await?;
-        }
-        None => {
-            return Ok(());
-        }
-    }
+    let options = CreateOptions {
+        panel: Some(panel),
+        ..Default::default()
+    };
+
+    tickets::create_ticket(ctx, data, &member, options).await?;
 
     info!("Deleting reaction");
     reaction.delete(ctx.http()).await?;
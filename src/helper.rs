@@ -2,4 +2,6 @@
 
 pub mod embed;
 pub mod fuzzy_match;
+pub mod pagination;
 pub mod parser;
+pub mod template;
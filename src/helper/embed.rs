@@ -3,7 +3,8 @@
 use chrono::Utc;
 use poise::serenity_prelude::{CreateEmbed, CreateEmbedAuthor, PartialGuild};
 
-const DEFAULT_COLOR: u32 = 0x58_65F2;
+/// Colour of the embeds of the bot
+pub const DEFAULT_COLOR: u32 = 0x58_65F2;
 
 /// Custom trait for embeds
 pub trait Custom {
@@ -16,7 +16,7 @@ pub fn match_subjects(subjects: &[Subject], query: &str, number_of_results: usiz
         })
         .collect();
 
-    matched.sort_by(|a, b| b.0.cmp(&a.0));
+    matched.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
 
     matched
         .into_iter()
@@ -0,0 +1,135 @@
+//! This module contains utilities for paginated embed messages
+
+use std::time::Duration;
+
+use poise::{
+    serenity_prelude::{
+        ComponentInteractionCollector, CreateActionRow, CreateButton, CreateEmbed,
+        CreateEmbedFooter, CreateInteractionResponse, CreateInteractionResponseMessage,
+    },
+    CreateReply,
+};
+
+use crate::handler::{Context, Error};
+
+/// Time after which the navigation buttons are removed
+const NAVIGATION_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Sends the given embeds as pages navigable with buttons
+///
+/// The page number is written in the footer of each page.
+/// If there is a single page, no buttons are added.
+pub async fn paginate(
+    ctx: Context<'_>,
+    pages: Vec<CreateEmbed>,
+    ephemeral: bool,
+) -> Result<(), Error> {
+    let total = pages.len();
+    let pages: Vec<CreateEmbed> = pages
+        .into_iter()
+        .enumerate()
+        .map(|(i, page)| page.footer(CreateEmbedFooter::new(format!("Page {}/{total}", i + 1))))
+        .collect();
+
+    let Some(first) = pages.first() else {
+        return Ok(());
+    };
+
+    let ctx_id = ctx.id();
+    let prev_button_id = format!("{ctx_id}prev");
+    let next_button_id = format!("{ctx_id}next");
+
+    let mut reply = CreateReply::default()
+        .embed(first.clone())
+        .ephemeral(ephemeral)
+        .reply(false);
+
+    if total > 1 {
+        reply = reply.components(navigation_buttons(&prev_button_id, &next_button_id));
+    }
+
+    let handle = ctx.send(reply).await?;
+
+    if total <= 1 {
+        return Ok(());
+    }
+
+    let mut current_page = 0;
+    while let Some(press) = ComponentInteractionCollector::new(ctx)
+        .author_id(ctx.author().id)
+        .filter(move |press| press.data.custom_id.starts_with(&ctx_id.to_string()))
+        .timeout(NAVIGATION_TIMEOUT)
+        .await
+    {
+        if press.data.custom_id == next_button_id {
+            current_page = (current_page + 1) % total;
+        } else if press.data.custom_id == prev_button_id {
+            current_page = current_page.checked_sub(1).unwrap_or(total - 1);
+        } else {
+            continue;
+        }
+
+        press
+            .create_response(
+                ctx.serenity_context(),
+                CreateInteractionResponse::UpdateMessage(
+                    CreateInteractionResponseMessage::new().embed(pages[current_page].clone()),
+                ),
+            )
+            .await?;
+    }
+
+    // Remove the buttons once the navigation is over
+    handle
+        .edit(
+            ctx,
+            CreateReply::default()
+                .embed(pages[current_page].clone())
+                .components(vec![]),
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Splits the given items into pages of at most `per_page` items each
+///
+/// Always returns at least one (possibly empty) page.
+pub fn chunk_pages<T>(items: Vec<T>, per_page: usize) -> Vec<Vec<T>> {
+    let mut pages = Vec::new();
+    let mut items = items.into_iter().peekable();
+
+    while items.peek().is_some() {
+        pages.push(items.by_ref().take(per_page).collect());
+    }
+
+    if pages.is_empty() {
+        pages.push(Vec::new());
+    }
+
+    pages
+}
+
+fn navigation_buttons(prev_button_id: &str, next_button_id: &str) -> Vec<CreateActionRow> {
+    vec![CreateActionRow::Buttons(vec![
+        CreateButton::new(prev_button_id).emoji('◀'),
+        CreateButton::new(next_button_id).emoji('▶'),
+    ])]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunk_pages() {
+        let pages = chunk_pages((0..7).collect(), 3);
+        assert_eq!(pages, vec![vec![0, 1, 2], vec![3, 4, 5], vec![6]]);
+    }
+
+    #[test]
+    fn test_chunk_pages_empty() {
+        let pages = chunk_pages(Vec::<u8>::new(), 3);
+        assert_eq!(pages, vec![Vec::<u8>::new()]);
+    }
+}
@@ -7,7 +7,7 @@
 ///
 /// ```
 /// let url = "https://discord.com/channels/123456789/987654321";
-/// let channel_id = helper::parser::parse_discord_channel_id_url(url);
+/// let channel_id = desquestion::helper::parser::parse_discord_channel_id_url(url);
 /// assert_eq!(channel_id, Some(987654321));
 /// ```
 pub fn parse_discord_channel_id_url(url: &str) -> Option<u64> {
@@ -24,11 +24,11 @@ pub fn parse_discord_channel_id_url(url: &str) -> Option<u64> {
 ///
 /// ```
 /// let mention = "<@123456789>";
-/// let id = helper::parser::parse_discord_mention("987654321");
+/// let id = desquestion::helper::parser::parse_discord_mention(mention);
 /// assert_eq!(id, Some(123456789));
 ///
 /// let id = "123456789";
-/// let id = helper::parser::parse_discord_mention(id);
+/// let id = desquestion::helper::parser::parse_discord_mention(id);
 /// assert_eq!(id, Some(123456789));
 /// ```
 pub fn parse_discord_mention(mention: &str) -> Option<u64> {
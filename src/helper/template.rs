@@ -0,0 +1,82 @@
+//! Helper module to render message templates containing `{placeholder}` values
+
+/// Replaces the placeholders of the template with their values
+///
+/// Placeholders without a value are left untouched.
+pub fn render(template: &str, values: &[(&str, &str)]) -> String {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        rendered.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+
+        let value = after.find('}').and_then(|end| {
+            values
+                .iter()
+                .find(|(name, _)| *name == &after[..end])
+                .map(|(_, value)| (end, value))
+        });
+
+        match value {
+            Some((end, value)) => {
+                rendered.push_str(value);
+                rest = &after[end + 1..];
+            }
+            None => {
+                rendered.push('{');
+                rest = after;
+            }
+        }
+    }
+
+    rendered.push_str(rest);
+    rendered
+}
+
+/// Returns the placeholders of the template which are not in the allowed list
+pub fn unknown_placeholders<'a>(template: &'a str, allowed: &[&str]) -> Vec<&'a str> {
+    let mut unknown = Vec::new();
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        let after = &rest[start + 1..];
+        let Some(end) = after.find('}') else {
+            break;
+        };
+
+        let name = &after[..end];
+        if !allowed.contains(&name) && !unknown.contains(&name) {
+            unknown.push(name);
+        }
+        rest = &after[end + 1..];
+    }
+
+    unknown
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_replaces_placeholders() {
+        let rendered = render(
+            "Hello {user}, ticket #{ticket_id} ({user})",
+            &[("user", "Bob"), ("ticket_id", "3")],
+        );
+        assert_eq!(rendered, "Hello Bob, ticket #3 (Bob)");
+    }
+
+    #[test]
+    fn test_render_keeps_unknown_placeholders() {
+        let rendered = render("{user} {other} {", &[("user", "Bob")]);
+        assert_eq!(rendered, "Bob {other} {");
+    }
+
+    #[test]
+    fn test_unknown_placeholders() {
+        let unknown = unknown_placeholders("{user} {reason} {foo} {foo}", &["user", "reason"]);
+        assert_eq!(unknown, vec!["foo"]);
+    }
+}
@@ -0,0 +1,244 @@
+//! This module handles the translation of the messages sent by the bot
+//!
+//! Translations are bundled from the `locales/` directory. Each server has a default locale,
+//! overridden by the Discord locale of the user for interactions. Missing translations fall
+//! back to English.
+
+use std::{collections::HashMap, fmt, str::FromStr, sync::OnceLock};
+
+use poise::{serenity_prelude::GuildId, Command};
+
+use crate::{
+    database::{Connection, ServerRepo},
+    handler::{Context, Data, Error},
+    helper::template::render,
+};
+
+/// The languages the bot is translated to
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, poise::ChoiceParameter)]
+pub enum Locale {
+    #[default]
+    #[name = "en"]
+    En,
+    #[name = "fr"]
+    Fr,
+    #[name = "de"]
+    De,
+}
+
+impl Locale {
+    /// Every supported locale
+    pub const ALL: [Self; 3] = [Self::En, Self::Fr, Self::De];
+
+    /// Returns the code of the locale, as stored in the database and used by Discord
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::En => "en",
+            Self::Fr => "fr",
+            Self::De => "de",
+        }
+    }
+
+    /// Returns the locale matching a Discord locale (`en-US`, `fr`, ...), if supported
+    pub fn from_discord(code: &str) -> Option<Self> {
+        let language = code.split('-').next().unwrap_or(code);
+        language.to_lowercase().parse().ok()
+    }
+
+    fn source(self) -> &'static str {
+        match self {
+            Self::En => include_str!("../locales/en.toml"),
+            Self::Fr => include_str!("../locales/fr.toml"),
+            Self::De => include_str!("../locales/de.toml"),
+        }
+    }
+}
+
+impl FromStr for Locale {
+    type Err = String;
+
+    fn from_str(code: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|locale| locale.as_str() == code)
+            .ok_or_else(|| format!("Unknown locale: {code}"))
+    }
+}
+
+impl fmt::Display for Locale {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+type Translations = HashMap<Locale, HashMap<String, String>>;
+
+fn translations() -> &'static Translations {
+    static TRANSLATIONS: OnceLock<Translations> = OnceLock::new();
+
+    TRANSLATIONS.get_or_init(|| {
+        Locale::ALL
+            .into_iter()
+            .map(|locale| {
+                let table: toml::Table = locale
+                    .source()
+                    .parse()
+                    .unwrap_or_else(|error| panic!("Invalid {locale} translation file: {error}"));
+                let mut entries = HashMap::new();
+                flatten(&table, "", &mut entries);
+                (locale, entries)
+            })
+            .collect()
+    })
+}
+
+/// Flattens the nested tables of a translation file into dotted keys
+fn flatten(table: &toml::Table, prefix: &str, entries: &mut HashMap<String, String>) {
+    for (key, value) in table {
+        let key = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{prefix}.{key}")
+        };
+
+        match value {
+            toml::Value::Table(table) => flatten(table, &key, entries),
+            toml::Value::String(text) => {
+                entries.insert(key, text.clone());
+            }
+            value => {
+                entries.insert(key, value.to_string());
+            }
+        }
+    }
+}
+
+fn lookup(locale: Locale, key: &str) -> Option<&'static str> {
+    translations()
+        .get(&locale)
+        .and_then(|entries| entries.get(key))
+        .map(String::as_str)
+}
+
+/// Returns the translation of the key, in English if it is not translated
+pub fn tr(locale: Locale, key: &str) -> String {
+    lookup(locale, key)
+        .or_else(|| lookup(Locale::En, key))
+        .map_or_else(|| key.to_string(), str::to_string)
+}
+
+/// Returns the translation of the key with its `{placeholders}` replaced
+pub fn tr_args(locale: Locale, key: &str, values: &[(&str, &str)]) -> String {
+    render(&tr(locale, key), values)
+}
+
+/// Returns the default locale of the server
+pub async fn get_server_locale(pool: &mut Connection, guild_id: GuildId) -> Locale {
+    match ServerRepo::find(pool, guild_id).await {
+        Ok(Some(server)) => server.locale,
+        Ok(None) => Locale::default(),
+        Err(error) => {
+            warn!("Failed to get the locale of guild {guild_id}: {error}");
+            Locale::default()
+        }
+    }
+}
+
+/// Returns the locale to answer the author of the command in
+///
+/// The Discord locale of the user is used for interactions, the locale of the server otherwise.
+pub async fn get_locale(ctx: Context<'_>) -> Locale {
+    if let Some(locale) = ctx.locale().and_then(Locale::from_discord) {
+        return locale;
+    }
+
+    let Some(guild_id) = ctx.guild_id() else {
+        return Locale::default();
+    };
+
+    match ctx.data().pool.acquire().await {
+        Ok(mut pool) => get_server_locale(&mut pool, guild_id).await,
+        Err(_) => Locale::default(),
+    }
+}
+
+/// Adds the translated descriptions of the commands and of their parameters
+///
+/// Descriptions are read from the `commands."<qualified name>"` tables of the translation files,
+/// the descriptions of the parameters from their `params` subtable.
+pub fn localize_commands(commands: &mut [Command<Data, Error>]) {
+    localize_subcommands(commands, "");
+}
+
+fn localize_subcommands(commands: &mut [Command<Data, Error>], parent: &str) {
+    for command in commands {
+        let qualified_name = if parent.is_empty() {
+            command.name.clone()
+        } else {
+            format!("{parent} {}", command.name)
+        };
+        let prefix = format!("commands.{qualified_name}");
+
+        for locale in Locale::ALL
+            .into_iter()
+            .filter(|&locale| locale != Locale::En)
+        {
+            if let Some(description) = lookup(locale, &format!("{prefix}.description")) {
+                command
+                    .description_localizations
+                    .insert(locale.as_str().to_string(), description.to_string());
+            }
+
+            for parameter in &mut command.parameters {
+                if let Some(description) =
+                    lookup(locale, &format!("{prefix}.params.{}", parameter.name))
+                {
+                    parameter
+                        .description_localizations
+                        .insert(locale.as_str().to_string(), description.to_string());
+                }
+            }
+        }
+
+        localize_subcommands(&mut command.subcommands, &qualified_name);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    /// Returns the message keys of a locale (command descriptions are optional)
+    fn message_keys(locale: Locale) -> HashSet<&'static String> {
+        translations()[&locale]
+            .keys()
+            .filter(|key| !key.starts_with("commands."))
+            .collect()
+    }
+
+    #[test]
+    fn test_translations_have_the_same_keys() {
+        let english = message_keys(Locale::En);
+        for locale in [Locale::Fr, Locale::De] {
+            let translated = message_keys(locale);
+            let missing: Vec<_> = english.difference(&translated).collect();
+            let unknown: Vec<_> = translated.difference(&english).collect();
+            assert!(missing.is_empty(), "Missing {locale} keys: {missing:?}");
+            assert!(unknown.is_empty(), "Unknown {locale} keys: {unknown:?}");
+        }
+    }
+
+    #[test]
+    fn test_from_discord() {
+        assert_eq!(Locale::from_discord("en-US"), Some(Locale::En));
+        assert_eq!(Locale::from_discord("fr"), Some(Locale::Fr));
+        assert_eq!(Locale::from_discord("es-ES"), None);
+    }
+
+    #[test]
+    fn test_tr_falls_back_to_key() {
+        assert_eq!(tr(Locale::Fr, "missing.key"), "missing.key");
+    }
+}
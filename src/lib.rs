@@ -0,0 +1,24 @@
+//! A Discord ticketing bot, designed to facilitate anonymous questions in educational settings
+//!
+//! The binary only loads the configuration and starts the client, the bot itself lives here so
+//! that it can be driven by the integration tests.
+
+#![allow(clippy::cast_possible_wrap)]
+#![allow(clippy::cast_sign_loss)]
+
+#[macro_use]
+extern crate tracing;
+
+// Crate modules
+pub mod cache;
+pub mod config;
+pub mod database;
+pub mod error;
+pub mod handler;
+pub mod helper;
+pub mod i18n;
+pub mod logging;
+pub mod metrics;
+pub mod shutdown;
+pub mod status;
+pub mod tickets;
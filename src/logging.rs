@@ -1,10 +1,11 @@
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter, Registry};
 
-pub fn setup() -> Result<(), String> {
+/// Sets up the logs, `RUST_LOG` takes precedence over the configured level
+pub fn setup(level: &str) -> Result<(), String> {
     let fmt_layer = tracing_subscriber::fmt::layer().compact();
 
     let filter_layer = EnvFilter::try_from_default_env()
-        .or_else(|_| EnvFilter::try_new("info"))
+        .or_else(|_| EnvFilter::try_new(level))
         .map_err(|error| error.to_string())?;
 
     Registry::default()
@@ -1,49 +1,103 @@
-#![allow(clippy::cast_possible_wrap)]
-#![allow(clippy::cast_sign_loss)]
-
+use desquestion::{
+    cache::ConfigCache,
+    config::Config,
+    database,
+    handler::{get_framework, Data},
+    logging,
+    metrics::Metrics,
+    shutdown::{self, Shutdown},
+    status,
+    tickets::{activity, office_hours::Scheduler, sla},
+};
 use dotenv::dotenv;
-use handler::get_framework;
-use poise::serenity_prelude::{Client, GatewayIntents};
-use std::env;
+use poise::serenity_prelude::Client;
+use std::{process, sync::Arc};
 
 #[macro_use]
 extern crate tracing;
 
-// Crate modules
-mod database;
-mod handler;
-mod helper;
-mod logging;
-mod tickets;
-
 #[tokio::main]
 async fn main() {
-    logging::setup().unwrap_or_else(|error| panic!("Failed to set up logging: {error}"));
+    // The environment can also be set without a .env file
+    dotenv().ok();
+
+    let config = Config::load().unwrap_or_else(|error| {
+        eprintln!("{error}");
+        process::exit(1);
+    });
 
-    dotenv().unwrap_or_else(|error| panic!("Failed to load .env file : {error}"));
+    logging::setup(&config.log_level)
+        .unwrap_or_else(|error| panic!("Failed to set up logging: {error}"));
 
-    let db_pool = database::get_database_pool()
+    let db_pool = database::get_database_pool(&config.database)
         .await
         .unwrap_or_else(|error| panic!("Failed to create database pool: {error}"));
 
-    let discord_token = env::var("DISCORD_TOKEN")
-        .unwrap_or_else(|error| panic!("Failed to get DISCORD_TOKEN from .env file : {error}"));
+    // Bring the schema up to date, a new SQLite file starts empty
+    database::MIGRATOR
+        .run(&db_pool)
+        .await
+        .unwrap_or_else(|error| panic!("Failed to apply the database migrations: {error}"));
+
+    let shutdown = Arc::new(Shutdown::default());
+    let metrics = Arc::new(Metrics::default());
 
-    let intents = GatewayIntents::GUILDS
-        | GatewayIntents::GUILD_MESSAGES
-        | GatewayIntents::DIRECT_MESSAGES
-        | GatewayIntents::GUILD_MESSAGE_REACTIONS
-        | GatewayIntents::MESSAGE_CONTENT;
+    let data = Data {
+        pool: db_pool.clone(),
+        shutdown: Arc::clone(&shutdown),
+        timeouts: config.timeouts,
+        metrics: Arc::clone(&metrics),
+        cache: Arc::new(ConfigCache::default()),
+    };
 
-    let mut client = Client::builder(discord_token, intents)
-        .framework(get_framework(db_pool))
-        .intents(intents)
+    let mut client = Client::builder(config.discord_token, config.intents)
+        .framework(get_framework(data.clone()))
         .await
         .unwrap_or_else(|error| panic!("Failed to create client: {error}"));
 
+    // The background tasks stop with the shutdown token, before the database pool is closed
+    let tasks = [
+        tokio::spawn(Scheduler::new(Arc::clone(&metrics)).run(
+            Arc::clone(&client.http),
+            db_pool.clone(),
+            shutdown.token(),
+        )),
+        tokio::spawn(sla::run(
+            Arc::clone(&client.http),
+            db_pool.clone(),
+            shutdown.token(),
+        )),
+        tokio::spawn(activity::run(Arc::clone(&client.http), data)),
+    ];
+
+    if let Some(address) = config.http_address {
+        tokio::spawn(status::serve(
+            address,
+            db_pool.clone(),
+            Arc::clone(&client.shard_manager),
+            metrics,
+        ));
+    }
+
+    tokio::spawn(shutdown::handle_signals(
+        Arc::clone(&shutdown),
+        config.timeouts.shutdown,
+        Arc::clone(&client.http),
+        Arc::clone(&client.shard_manager),
+    ));
+
     if let Err(error) = client.start().await {
         error!("Client error: {error}");
     }
 
+    shutdown.stop();
+    for task in tasks {
+        if let Err(error) = task.await {
+            error!("Background task failed: {error}");
+        }
+    }
+
+    db_pool.close().await;
+
     info!("Client shutting down");
 }
@@ -0,0 +1,179 @@
+//! This module collects the metrics of the bot, exposed in the Prometheus text format
+//!
+//! The counters are kept in memory and reset when the bot restarts, as Prometheus expects.
+
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    sync::{Mutex, MutexGuard},
+    time::Duration,
+};
+
+use poise::serenity_prelude::GuildId;
+
+/// Upper bounds of the buckets of the command latency, in seconds
+///
+/// Commands waiting for the user (setup, confirmations...) can take up to a few minutes.
+const LATENCY_BUCKETS: [f64; 10] = [0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 60.0, 300.0];
+
+/// The metrics of the bot
+#[derive(Default)]
+pub struct Metrics {
+    tickets_created: Mutex<BTreeMap<GuildId, u64>>,
+    tickets_claimed: Mutex<BTreeMap<GuildId, u64>>,
+    tickets_closed: Mutex<BTreeMap<GuildId, u64>>,
+    creation_timeouts: Mutex<BTreeMap<GuildId, u64>>,
+    command_latency: Mutex<BTreeMap<String, Histogram>>,
+}
+
+/// The distribution of the durations of a command
+#[derive(Default)]
+struct Histogram {
+    buckets: [u64; LATENCY_BUCKETS.len()],
+    sum: f64,
+    count: u64,
+}
+
+impl Histogram {
+    fn observe(&mut self, seconds: f64) {
+        for (bucket, bound) in self.buckets.iter_mut().zip(LATENCY_BUCKETS) {
+            if seconds <= bound {
+                *bucket += 1;
+            }
+        }
+        self.sum += seconds;
+        self.count += 1;
+    }
+}
+
+impl Metrics {
+    pub fn ticket_created(&self, guild_id: GuildId) {
+        increment(&self.tickets_created, guild_id);
+    }
+
+    pub fn ticket_claimed(&self, guild_id: GuildId) {
+        increment(&self.tickets_claimed, guild_id);
+    }
+
+    pub fn ticket_closed(&self, guild_id: GuildId) {
+        increment(&self.tickets_closed, guild_id);
+    }
+
+    pub fn creation_timed_out(&self, guild_id: GuildId) {
+        increment(&self.creation_timeouts, guild_id);
+    }
+
+    /// Records how long a command took, by its qualified name
+    pub fn command_finished(&self, command: &str, duration: Duration) {
+        lock(&self.command_latency)
+            .entry(command.to_string())
+            .or_default()
+            .observe(duration.as_secs_f64());
+    }
+
+    /// Returns the metrics in the Prometheus text format
+    pub fn render(&self) -> String {
+        let mut output = String::new();
+
+        let counters = [
+            (
+                "tickets_created_total",
+                "Tickets created",
+                &self.tickets_created,
+            ),
+            (
+                "tickets_claimed_total",
+                "Tickets claimed",
+                &self.tickets_claimed,
+            ),
+            (
+                "tickets_closed_total",
+                "Tickets closed",
+                &self.tickets_closed,
+            ),
+            (
+                "ticket_creation_timeouts_total",
+                "Ticket creations abandoned because the member did not answer",
+                &self.creation_timeouts,
+            ),
+        ];
+
+        for (name, help, counter) in counters {
+            let _ = writeln!(output, "# HELP {name} {help}");
+            let _ = writeln!(output, "# TYPE {name} counter");
+            for (guild_id, value) in lock(counter).iter() {
+                let _ = writeln!(output, "{name}{{guild=\"{guild_id}\"}} {value}");
+            }
+        }
+
+        let name = "command_duration_seconds";
+        let _ = writeln!(output, "# HELP {name} Time taken by the commands");
+        let _ = writeln!(output, "# TYPE {name} histogram");
+        for (command, histogram) in lock(&self.command_latency).iter() {
+            let command = command.replace('\\', "\\\\").replace('"', "\\\"");
+            for (bound, count) in LATENCY_BUCKETS.iter().zip(histogram.buckets) {
+                let _ = writeln!(
+                    output,
+                    "{name}_bucket{{command=\"{command}\",le=\"{bound}\"}} {count}"
+                );
+            }
+            let _ = writeln!(
+                output,
+                "{name}_bucket{{command=\"{command}\",le=\"+Inf\"}} {}",
+                histogram.count
+            );
+            let _ = writeln!(
+                output,
+                "{name}_sum{{command=\"{command}\"}} {}",
+                histogram.sum
+            );
+            let _ = writeln!(
+                output,
+                "{name}_count{{command=\"{command}\"}} {}",
+                histogram.count
+            );
+        }
+
+        output
+    }
+}
+
+fn increment(counter: &Mutex<BTreeMap<GuildId, u64>>, guild_id: GuildId) {
+    *lock(counter).entry(guild_id).or_default() += 1;
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    // A panic while holding the lock cannot leave a counter in an invalid state
+    mutex
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_counters() {
+        let metrics = Metrics::default();
+        metrics.ticket_created(GuildId::new(1));
+        metrics.ticket_created(GuildId::new(1));
+
+        assert!(metrics
+            .render()
+            .contains("tickets_created_total{guild=\"1\"} 2\n"));
+    }
+
+    #[test]
+    fn test_render_histogram() {
+        let metrics = Metrics::default();
+        metrics.command_finished("ticket open", Duration::from_millis(200));
+
+        let output = metrics.render();
+        assert!(output
+            .contains("command_duration_seconds_bucket{command=\"ticket open\",le=\"0.1\"} 0\n"));
+        assert!(output
+            .contains("command_duration_seconds_bucket{command=\"ticket open\",le=\"0.25\"} 1\n"));
+        assert!(output.contains("command_duration_seconds_count{command=\"ticket open\"} 1\n"));
+    }
+}
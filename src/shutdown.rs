@@ -0,0 +1,194 @@
+//! This module handles the graceful shutdown of the bot
+//!
+//! When a shutdown signal is received, no new ticket is accepted, the members in the middle of
+//! a ticket creation are told that the bot is restarting and the closings in progress are given
+//! some time to finish before the shards are closed. The background tasks are cancelled through
+//! the shutdown token and awaited before the database pool is closed.
+
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+use poise::serenity_prelude::{ChannelId, Http, ShardManager};
+use tokio::sync::Notify;
+use tokio_util::sync::CancellationToken;
+
+use crate::i18n::{tr, Locale};
+
+/// The shutdown state of the bot, shared by the commands and the events
+#[derive(Default)]
+pub struct Shutdown {
+    stopping: AtomicBool,
+    /// The channels of the tickets being created, with the language of their member
+    pending_flows: Mutex<HashMap<ChannelId, Locale>>,
+    in_flight_closes: AtomicUsize,
+    close_finished: Notify,
+    /// Cancelled when the bot stops, to end the background tasks
+    token: CancellationToken,
+}
+
+impl Shutdown {
+    /// Returns whether new tickets can be created
+    pub fn is_accepting(&self) -> bool {
+        !self.stopping.load(Ordering::SeqCst)
+    }
+
+    /// Returns the token cancelled when the bot stops
+    pub fn token(&self) -> CancellationToken {
+        self.token.clone()
+    }
+
+    /// Stops accepting tickets and cancels the background tasks
+    pub fn stop(&self) {
+        self.stopping.store(true, Ordering::SeqCst);
+        self.token.cancel();
+    }
+
+    /// Registers a ticket creation waiting for the member, until the returned guard is dropped
+    pub fn track_flow(self: &Arc<Self>, channel_id: ChannelId, locale: Locale) -> FlowGuard {
+        self.pending_flows
+            .lock()
+            .expect("Pending flows lock poisoned")
+            .insert(channel_id, locale);
+
+        FlowGuard {
+            shutdown: Arc::clone(self),
+            channel_id,
+        }
+    }
+
+    /// Registers a ticket closing, until the returned guard is dropped
+    pub fn track_close(self: &Arc<Self>) -> CloseGuard {
+        self.in_flight_closes.fetch_add(1, Ordering::SeqCst);
+
+        CloseGuard {
+            shutdown: Arc::clone(self),
+        }
+    }
+
+    /// Tells the members in the middle of a ticket creation that the bot is restarting
+    async fn notify_pending_flows(&self, http: &Http) {
+        let flows: Vec<(ChannelId, Locale)> = self
+            .pending_flows
+            .lock()
+            .expect("Pending flows lock poisoned")
+            .iter()
+            .map(|(channel_id, locale)| (*channel_id, *locale))
+            .collect();
+
+        for (channel_id, locale) in flows {
+            if let Err(error) = channel_id
+                .say(http, tr(locale, "shutdown.restarting"))
+                .await
+            {
+                warn!("Failed to notify pending ticket {channel_id}: {error}");
+            }
+        }
+    }
+
+    /// Waits for the closings in progress to finish, returns `false` if they took too long
+    async fn wait_for_closes(&self, timeout: Duration) -> bool {
+        let all_finished = async {
+            loop {
+                let finished = self.close_finished.notified();
+                if self.in_flight_closes.load(Ordering::SeqCst) == 0 {
+                    return;
+                }
+                finished.await;
+            }
+        };
+
+        tokio::time::timeout(timeout, all_finished).await.is_ok()
+    }
+}
+
+/// A ticket creation in progress, unregistered when dropped
+pub struct FlowGuard {
+    shutdown: Arc<Shutdown>,
+    channel_id: ChannelId,
+}
+
+impl Drop for FlowGuard {
+    fn drop(&mut self) {
+        self.shutdown
+            .pending_flows
+            .lock()
+            .expect("Pending flows lock poisoned")
+            .remove(&self.channel_id);
+    }
+}
+
+/// A ticket closing in progress, unregistered when dropped
+pub struct CloseGuard {
+    shutdown: Arc<Shutdown>,
+}
+
+impl Drop for CloseGuard {
+    fn drop(&mut self) {
+        self.shutdown
+            .in_flight_closes
+            .fetch_sub(1, Ordering::SeqCst);
+        self.shutdown.close_finished.notify_waiters();
+    }
+}
+
+/// Waits for a shutdown signal, then stops the bot gracefully
+pub async fn handle_signals(
+    shutdown: Arc<Shutdown>,
+    close_timeout: Duration,
+    http: Arc<Http>,
+    shards: Arc<ShardManager>,
+) {
+    wait_for_signal().await;
+    info!("Shutdown signal received, stopping the bot");
+
+    shutdown.stop();
+    shutdown.notify_pending_flows(&http).await;
+
+    if !shutdown.wait_for_closes(close_timeout).await {
+        warn!("Some tickets were still closing after {close_timeout:?}");
+    }
+
+    shards.shutdown_all().await;
+}
+
+/// Waits for Ctrl+C, or SIGTERM on Unix
+async fn wait_for_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        let mut terminate =
+            signal(SignalKind::terminate()).expect("Failed to listen for SIGTERM signals");
+
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => {}
+            _ = terminate.recv() => {}
+        }
+    }
+
+    #[cfg(not(unix))]
+    tokio::signal::ctrl_c()
+        .await
+        .expect("Failed to listen for Ctrl+C signals");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_wait_for_closes() {
+        let shutdown = Arc::new(Shutdown::default());
+        let guard = shutdown.track_close();
+
+        assert!(!shutdown.wait_for_closes(Duration::from_millis(10)).await);
+        drop(guard);
+        assert!(shutdown.wait_for_closes(Duration::from_millis(10)).await);
+    }
+}
@@ -0,0 +1,108 @@
+//! This module serves the HTTP endpoints used to monitor the bot
+//!
+//! - `/healthz` answers `200` when every shard is connected to the gateway and the database
+//!   answers, `503` otherwise
+//! - `/metrics` returns the metrics of the bot in the Prometheus text format
+
+use std::{net::SocketAddr, sync::Arc};
+
+use axum::{
+    extract::State,
+    http::{header, StatusCode},
+    routing::get,
+    Router,
+};
+use poise::serenity_prelude::{ConnectionStage, ShardManager};
+
+use crate::{database::Pool, metrics::Metrics};
+
+/// What the endpoints need to report the status of the bot
+#[derive(Clone)]
+struct Status {
+    pool: Pool,
+    shards: Arc<ShardManager>,
+    metrics: Arc<Metrics>,
+}
+
+/// Serves the monitoring endpoints until the bot stops
+pub async fn serve(
+    address: SocketAddr,
+    pool: Pool,
+    shards: Arc<ShardManager>,
+    metrics: Arc<Metrics>,
+) {
+    let status = Status {
+        pool,
+        shards,
+        metrics,
+    };
+
+    let router = Router::new()
+        .route("/healthz", get(health))
+        .route("/metrics", get(metrics_text))
+        .with_state(status);
+
+    let listener = match tokio::net::TcpListener::bind(address).await {
+        Ok(listener) => listener,
+        Err(error) => {
+            error!("Failed to listen on {address}: {error}");
+            return;
+        }
+    };
+
+    info!("Serving health and metrics on {address}");
+    if let Err(error) = axum::serve(listener, router).await {
+        error!("Health and metrics server error: {error}");
+    }
+}
+
+async fn health(State(status): State<Status>) -> (StatusCode, String) {
+    let gateway = {
+        let runners = status.shards.runners.lock().await;
+        if runners.is_empty() {
+            Err("no shard running".to_string())
+        } else {
+            let disconnected: Vec<String> = runners
+                .iter()
+                .filter(|(_, runner)| runner.stage != ConnectionStage::Connected)
+                .map(|(id, runner)| format!("shard {id} {}", runner.stage))
+                .collect();
+
+            if disconnected.is_empty() {
+                Ok(())
+            } else {
+                Err(disconnected.join(", "))
+            }
+        }
+    };
+
+    let database = sqlx::query("SELECT 1")
+        .execute(&status.pool)
+        .await
+        .map(|_| ())
+        .map_err(|error| error.to_string());
+
+    let code = if gateway.is_ok() && database.is_ok() {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+
+    let report = |result: Result<(), String>| result.err().unwrap_or_else(|| "ok".to_string());
+    let body = format!(
+        "gateway: {}\ndatabase: {}\n",
+        report(gateway),
+        report(database)
+    );
+
+    (code, body)
+}
+
+async fn metrics_text(
+    State(status): State<Status>,
+) -> ([(header::HeaderName, &'static str); 1], String) {
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        status.metrics.render(),
+    )
+}
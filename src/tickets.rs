@@ -1,14 +1,32 @@
 //! This module handles the ticket logic
 
 // Ticket actions
+pub mod activity;
+pub mod assign;
 mod claim;
 mod close;
 mod create;
+pub mod feedback;
+pub mod location;
+pub mod office_hours;
+pub mod panels;
+pub mod participants;
+pub mod priority;
+mod queue;
+pub mod sla;
+mod staff;
+
+// Ticket records
+mod audit;
+pub mod categories;
+pub mod templates;
+mod transcript;
 
 // Re-exports of the ticket actions
-pub use claim::claim as claim_ticket;
-pub use close::close as close_ticket;
-pub use create::create as create_ticket;
+pub use claim::{claim as claim_ticket, claim_next as claim_next_ticket};
+pub use close::{close as close_ticket, request_close as request_close_ticket};
+pub use create::{create as create_ticket, CreateOptions};
+pub use queue::{format_wait, queue as ticket_queue};
 
 /// The emoji used for tickets
 pub const TICKET_EMOJI: &str = "🎫";
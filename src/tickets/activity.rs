@@ -0,0 +1,133 @@
+//! This module tracks the activity of the tickets, from the messages written in them
+//!
+//! The last messages of the author and of the staff drive the reminders of the helpers, and the
+//! first answer of the staff the statistics and the queue. A server can also close the tickets in
+//! which nobody wrote for a while.
+
+use std::{sync::Arc, time::Duration};
+
+use poise::serenity_prelude::{Http, Message, UserId};
+
+use crate::{
+    database::{Ticket, TicketRepo},
+    handler::{Data, Error},
+    i18n::{get_server_locale, tr},
+};
+
+use super::{
+    close::{finalize, get_closing_ticket},
+    staff::is_ticket_staff,
+};
+
+/// How often the inactive tickets are looked for
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Records a message written in a ticket by its author or by a staff member
+pub async fn handle_message(http: &Http, data: &Data, message: &Message) -> Result<(), Error> {
+    if message.author.bot {
+        return Ok(());
+    }
+    let Some(guild_id) = message.guild_id else {
+        return Ok(());
+    };
+
+    // Every message of the server comes here, most are filtered out without any query
+    let config = data.cache.get(&data.pool, guild_id).await?;
+    let Some(server) = &config.server else {
+        return Ok(());
+    };
+    if !config.ticket_channels.contains(&message.channel_id) {
+        return Ok(());
+    }
+
+    let mut pool = data.pool.acquire().await?;
+    let Some(ticket) = TicketRepo::by_channel(&mut pool, message.channel_id)
+        .await?
+        .filter(|ticket| ticket.is_open)
+    else {
+        return Ok(());
+    };
+
+    let from_staff = message.author.id != ticket.author_id;
+    if from_staff {
+        let roles = match &message.member {
+            Some(member) => member.roles.clone(),
+            None => guild_id.member(http, message.author.id).await?.roles,
+        };
+
+        // The participants added to the ticket are neither its author nor the staff
+        if !is_ticket_staff(&mut pool, server, ticket.subject_id, &roles).await? {
+            return Ok(());
+        }
+    }
+
+    TicketRepo::record_message(&mut pool, ticket.id, from_staff).await
+}
+
+/// Closes the inactive tickets every minute, until the bot stops
+pub async fn run(http: Arc<Http>, data: Data) {
+    let token = data.shutdown.token();
+    let mut interval = tokio::time::interval(CHECK_INTERVAL);
+    loop {
+        tokio::select! {
+            _ = token.cancelled() => return,
+            _ = interval.tick() => {}
+        }
+        if let Err(error) = close_inactive(&http, &data).await {
+            error!("Failed to close the inactive tickets: {error}");
+        }
+    }
+}
+
+/// Closes the tickets of every server in which nobody wrote for too long
+pub async fn close_inactive(http: &Http, data: &Data) -> Result<(), Error> {
+    let mut pool = data.pool.acquire().await?;
+    let tickets = TicketRepo::inactive(&mut pool).await?;
+    drop(pool);
+
+    if tickets.is_empty() {
+        return Ok(());
+    }
+
+    // The tickets are closed by the bot itself
+    let bot_id = http.get_current_user().await?.id;
+
+    for ticket in tickets {
+        if !data.shutdown.is_accepting() {
+            break;
+        }
+
+        let ticket_id = ticket.id;
+        if let Err(error) = close_ticket(http, data, ticket, bot_id).await {
+            warn!("Failed to close inactive ticket {ticket_id}: {error}");
+        }
+    }
+
+    Ok(())
+}
+
+/// Closes an inactive ticket, like a moderator would
+async fn close_ticket(
+    http: &Http,
+    data: &Data,
+    ticket: Ticket,
+    bot_id: UserId,
+) -> Result<(), Error> {
+    // Registered before checking the shutdown, so that a shutdown starting meanwhile waits for it
+    let _closing = data.shutdown.track_close();
+    if !data.shutdown.is_accepting() {
+        return Ok(());
+    }
+
+    let guild = ticket.guild_id.to_partial_guild(http).await?;
+
+    let mut pool = data.pool.acquire().await?;
+    let locale = get_server_locale(&mut pool, guild.id).await;
+    let Some(closing) = get_closing_ticket(&mut pool, ticket.location.id()).await? else {
+        return Ok(());
+    };
+    drop(pool);
+
+    let reason = tr(locale, "close.inactive_reason");
+    finalize(http, data, &guild, closing, bot_id, &reason).await
+}
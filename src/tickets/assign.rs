@@ -0,0 +1,154 @@
+//! This module handles the automatic assignment of the tickets to the helpers on duty
+//!
+//! A server (or one of its subjects) can assign its tickets instead of letting the helpers claim
+//! them, either in turn or to the helper with the fewest open tickets.
+
+use std::{fmt, str::FromStr};
+
+use poise::serenity_prelude::{CreateMessage, Http, Mentionable, UserId};
+
+use crate::{
+    database::{Connection, Server, ServerRepo, Storage, TicketRepo},
+    handler::{Context, Error},
+    i18n::{get_locale, tr, tr_args, Locale},
+};
+
+use super::{claim::join_ticket, location::TicketLocation};
+
+/// How the new tickets are given to the helpers
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, poise::ChoiceParameter)]
+pub enum AssignMode {
+    /// The helpers claim the tickets themselves
+    #[default]
+    #[name = "manual"]
+    Manual,
+    /// The tickets are given in turn to the helpers on duty
+    #[name = "round_robin"]
+    RoundRobin,
+    /// The tickets are given to the helper on duty with the fewest open tickets
+    #[name = "least_busy"]
+    LeastBusy,
+}
+
+impl AssignMode {
+    /// Returns the name of the mode, as stored in the database
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Manual => "manual",
+            Self::RoundRobin => "round_robin",
+            Self::LeastBusy => "least_busy",
+        }
+    }
+}
+
+impl FromStr for AssignMode {
+    type Err = String;
+
+    fn from_str(mode: &str) -> Result<Self, Self::Err> {
+        match mode {
+            "manual" => Ok(Self::Manual),
+            "round_robin" => Ok(Self::RoundRobin),
+            "least_busy" => Ok(Self::LeastBusy),
+            _ => Err(format!("Unknown assign mode: {mode}")),
+        }
+    }
+}
+
+impl fmt::Display for AssignMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Marks the author of the command as available for the assigned tickets, or away
+pub async fn set_duty(ctx: &Context<'_>, on_duty: bool) -> Result<(), Error> {
+    let locale = get_locale(*ctx).await;
+    let guild_id = ctx
+        .guild_id()
+        .ok_or_else(|| Error::user(tr(locale, "duty.guild_only")))?;
+    let mut pool = ctx.data().pool.acquire().await?;
+
+    let server = ServerRepo::get(&mut pool, guild_id).await?;
+    if !ctx
+        .author()
+        .has_role(ctx.http(), guild_id, server.helper_role_id)
+        .await?
+    {
+        return Err(Error::permission(tr(locale, "duty.not_helper")));
+    }
+
+    pool.set_duty(guild_id, ctx.author().id, on_duty).await
+}
+
+/// Assigns a new ticket to a helper on duty, when its subject (or its server) assigns the tickets
+///
+/// Returns the helper the ticket was given to, `None` if it is left to be claimed.
+pub(super) async fn auto_assign(
+    http: &Http,
+    pool: &mut Connection,
+    server: &Server,
+    location: TicketLocation,
+    subject_id: Option<u64>,
+) -> Result<Option<UserId>, Error> {
+    let (mode, subject_role) = match subject_id {
+        Some(subject_id) => (
+            pool.subject_assign_mode(server.id, subject_id)
+                .await?
+                .unwrap_or(server.assign_mode),
+            pool.subject_helper_role(server.id, subject_id).await?,
+        ),
+        None => (server.assign_mode, None),
+    };
+
+    if mode == AssignMode::Manual {
+        return Ok(None);
+    }
+
+    for helper_id in pool.duty_helpers(server.id, mode).await? {
+        // The helpers on duty may have left or lost their roles since
+        let Ok(member) = server.id.member(http, helper_id).await else {
+            continue;
+        };
+        let can_take = member.roles.contains(&server.helper_role_id)
+            && subject_role.is_none_or(|role_id| member.roles.contains(&role_id));
+        if !can_take {
+            continue;
+        }
+
+        // The ticket may have been claimed meanwhile
+        if !TicketRepo::claim(pool, location.id(), helper_id).await? {
+            return Ok(None);
+        }
+        pool.record_assignment(server.id, helper_id).await?;
+        join_ticket(http, pool, server.id, location, helper_id).await?;
+
+        return Ok(Some(helper_id));
+    }
+
+    Ok(None)
+}
+
+/// Returns the message telling the author of a ticket which helper it was assigned to
+pub(super) fn assigned_message(locale: Locale, helper_id: UserId) -> CreateMessage {
+    CreateMessage::new().content(tr_args(
+        locale,
+        "ticket.assigned",
+        &[("helper", &helper_id.mention().to_string())],
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_assign_mode_round_trip() {
+        for mode in [
+            AssignMode::Manual,
+            AssignMode::RoundRobin,
+            AssignMode::LeastBusy,
+        ] {
+            assert_eq!(mode.as_str().parse(), Ok(mode));
+        }
+    }
+}
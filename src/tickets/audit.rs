@@ -0,0 +1,35 @@
+//! This module handles the audit log of the tickets, sent to the log channel of the server
+
+use poise::serenity_prelude::{CreateEmbed, CreateMessage, GuildId, Http, PartialGuild};
+
+use crate::{
+    database::{Connection, ServerRepo},
+    handler::Error,
+    helper::embed::Custom,
+    i18n::{tr, Locale},
+};
+
+/// Sends a message to the log channel of the server
+pub async fn send_log(
+    http: &Http,
+    pool: &mut Connection,
+    guild_id: GuildId,
+    message: CreateMessage,
+) -> Result<(), Error> {
+    ServerRepo::get(pool, guild_id)
+        .await?
+        .log_channel_id
+        .send_message(http, message)
+        .await?;
+
+    Ok(())
+}
+
+/// Returns the base embed used for audit log entries
+pub fn log_embed(guild: &PartialGuild, locale: Locale, title: &str, ticket_id: i32) -> CreateEmbed {
+    CreateEmbed::default_bot_embed(guild).title(title).field(
+        tr(locale, "ticket.ticket_field"),
+        format!("#{ticket_id}"),
+        true,
+    )
+}
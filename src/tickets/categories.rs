@@ -0,0 +1,180 @@
+//! This module handles the ticket categories of a server
+//!
+//! Discord limits a category to 50 channels, so when the base category of a state
+//! (unclaimed or claimed) is full, overflow categories are created next to it
+//! ("Unclaimed Tickets 2", "Unclaimed Tickets 3", ...) and removed once empty. The categories
+//! chosen by the panels overflow the same way.
+
+use std::collections::HashMap;
+
+use poise::serenity_prelude::{ChannelId, ChannelType, CreateChannel, GuildChannel, GuildId, Http};
+
+use crate::{
+    database::{Connection, ServerRepo, Storage},
+    handler::Error,
+    i18n::{get_server_locale, tr, Locale},
+};
+
+/// Maximum number of channels in a category (Discord limit)
+const CATEGORY_CHANNEL_LIMIT: usize = 50;
+
+/// Position of the first overflow category (the base category being the first one)
+const FIRST_OVERFLOW_POSITION: i16 = 2;
+
+/// The state of the tickets placed in a category
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CategoryState {
+    Unclaimed,
+    Claimed,
+}
+
+impl CategoryState {
+    /// Returns the name of the state, as stored in the database
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Unclaimed => "unclaimed",
+            Self::Claimed => "claimed",
+        }
+    }
+
+    /// Returns the name of the base category of the state
+    pub fn category_name(self, locale: Locale) -> String {
+        match self {
+            Self::Unclaimed => tr(locale, "categories.unclaimed"),
+            Self::Claimed => tr(locale, "categories.claimed"),
+        }
+    }
+}
+
+/// Returns a category of the given state with room for a new ticket channel,
+/// creating (or reusing) an overflow category when every category is full
+pub async fn get_available_category(
+    http: &Http,
+    pool: &mut Connection,
+    guild_id: GuildId,
+    state: CategoryState,
+) -> Result<ChannelId, Error> {
+    let base = get_base_category(pool, guild_id, state).await?;
+    get_available_category_in(http, pool, guild_id, state, base).await
+}
+
+/// Returns the given base category (such as the category of a panel), or one of its overflow
+/// categories, with room for a new ticket channel of the given state
+pub async fn get_available_category_in(
+    http: &Http,
+    pool: &mut Connection,
+    guild_id: GuildId,
+    state: CategoryState,
+    base: ChannelId,
+) -> Result<ChannelId, Error> {
+    let channels = guild_id.channels(http).await?;
+    let overflow = pool.categories(guild_id, state, Some(base)).await?;
+
+    let categories = std::iter::once(base).chain(overflow.iter().map(|(id, _)| *id));
+    for category in categories {
+        if channels.contains_key(&category)
+            && count_children(&channels, category) < CATEGORY_CHANNEL_LIMIT
+        {
+            return Ok(category);
+        }
+    }
+
+    let position = overflow
+        .iter()
+        .map(|(_, position)| position + 1)
+        .max()
+        .unwrap_or(FIRST_OVERFLOW_POSITION);
+    // The categories of the server keep the name of their state, the others the name of their base
+    let server_base = get_base_category(pool, guild_id, state).await?;
+    let base_name = match channels.get(&base) {
+        Some(channel) if base != server_base => channel.name.clone(),
+        _ => state.category_name(get_server_locale(pool, guild_id).await),
+    };
+    let name = format!("{base_name} {position}");
+
+    // Reuse a category with the same name if an administrator already created one
+    let existing = channels
+        .values()
+        .find(|channel| channel.kind == ChannelType::Category && channel.name == name)
+        .map(|channel| channel.id);
+
+    let category = match existing {
+        Some(category) => category,
+        None => {
+            info!("Creating overflow category {name} in guild {guild_id}");
+            // The overflow category inherits the permissions of the base category
+            let permissions = channels
+                .get(&base)
+                .map(|base| base.permission_overwrites.clone())
+                .unwrap_or_default();
+
+            let builder = CreateChannel::new(name)
+                .kind(ChannelType::Category)
+                .permissions(permissions);
+
+            guild_id.create_channel(http, builder).await?.id
+        }
+    };
+
+    pool.insert_category(category, guild_id, state, base, position)
+        .await?;
+
+    Ok(category)
+}
+
+/// Deletes the overflow categories which no longer contain any channel
+pub async fn remove_empty_overflow_categories(
+    http: &Http,
+    pool: &mut Connection,
+    guild_id: GuildId,
+) -> Result<(), Error> {
+    let channels = guild_id.channels(http).await?;
+
+    for state in [CategoryState::Unclaimed, CategoryState::Claimed] {
+        for (category, _) in get_overflow_categories(pool, guild_id, state).await? {
+            if count_children(&channels, category) > 0 {
+                continue;
+            }
+
+            if channels.contains_key(&category) {
+                info!("Deleting empty overflow category {category} in guild {guild_id}");
+                category.delete(http).await?;
+            }
+
+            pool.delete_category(category).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the overflow categories of the given state, whatever category they extend, along with
+/// their position
+pub async fn get_overflow_categories(
+    pool: &mut Connection,
+    guild_id: GuildId,
+    state: CategoryState,
+) -> Result<Vec<(ChannelId, i16)>, Error> {
+    pool.categories(guild_id, state, None).await
+}
+
+/// Returns the base category of the given state, chosen during the setup
+async fn get_base_category(
+    pool: &mut Connection,
+    guild_id: GuildId,
+    state: CategoryState,
+) -> Result<ChannelId, Error> {
+    let server = ServerRepo::get(pool, guild_id).await?;
+
+    Ok(match state {
+        CategoryState::Unclaimed => server.unclaimed_category_id,
+        CategoryState::Claimed => server.claimed_category_id,
+    })
+}
+
+fn count_children(channels: &HashMap<ChannelId, GuildChannel>, category: ChannelId) -> usize {
+    channels
+        .values()
+        .filter(|channel| channel.parent_id == Some(category))
+        .count()
+}
@@ -1,56 +1,135 @@
-use crate::handler::{Context, Error};
-use poise::serenity_prelude::{ChannelId, ChannelType, EditChannel};
+use crate::{
+    database::{Connection, Ticket, TicketRepo},
+    handler::{Context, Error},
+    i18n::{get_locale, tr},
+};
+use poise::serenity_prelude::{ChannelId, ChannelType, EditChannel, GuildId, Http, UserId};
+
+use super::{
+    categories::{get_available_category, remove_empty_overflow_categories, CategoryState},
+    location::TicketLocation,
+    staff::get_staff_roles,
+};
 
 pub async fn claim(ctx: &Context<'_>) -> Result<(), Error> {
     let mut pool = ctx.data().pool.acquire().await?;
     let guild_id = ctx.guild_id().ok_or("Not in a guild")?;
 
-    let row = sqlx::query!(
-        "SELECT helper_role_id FROM servers WHERE id = $1",
-        guild_id.get() as i64
+    check_helper(ctx, &mut pool, guild_id).await?;
+
+    let channel = ctx.channel_id();
+
+    let Some(ticket) = TicketRepo::by_channel(&mut pool, channel).await? else {
+        let locale = get_locale(*ctx).await;
+        return Err(Error::user(tr(locale, "close.not_a_ticket")));
+    };
+
+    // Claimed first, so that two helpers never both join the ticket
+    if !TicketRepo::claim(&mut pool, channel, ctx.author().id).await? {
+        let locale = get_locale(*ctx).await;
+        return Err(Error::user(tr(locale, "claim.unavailable")));
+    }
+
+    join_ticket(
+        ctx.http(),
+        &mut pool,
+        guild_id,
+        ticket.location,
+        ctx.author().id,
     )
-    .fetch_optional(&mut *pool)
     .await?;
 
-    let role_id = row
-        .map(|row| row.helper_role_id)
-        .ok_or("Failed to find the server")? as u64;
+    ctx.data().metrics.ticket_claimed(guild_id);
 
-    // Cannot claim
-    if !ctx.author().has_role(ctx.http(), guild_id, role_id).await? {
-        return Ok(());
-    }
+    Ok(())
+}
 
-    let channel = ctx.channel_id();
+/// Claims the first ticket of the queue the author can take, and returns it
+///
+/// Returns `None` when no ticket is waiting for the author.
+pub async fn claim_next(ctx: &Context<'_>) -> Result<Option<Ticket>, Error> {
+    let mut pool = ctx.data().pool.acquire().await?;
+    let guild_id = ctx.guild_id().ok_or("Not in a guild")?;
+
+    check_helper(ctx, &mut pool, guild_id).await?;
+
+    let member = ctx.author_member().await.ok_or("Failed to get member")?;
 
-    let ticket_id = sqlx::query!(
-        "SELECT ticket_id FROM tickets WHERE channel_id = $1",
-        channel.get() as i64
+    let Some(ticket) =
+        TicketRepo::claim_next(&mut pool, guild_id, ctx.author().id, &member.roles).await?
+    else {
+        return Ok(None);
+    };
+
+    join_ticket(
+        ctx.http(),
+        &mut pool,
+        guild_id,
+        ticket.location,
+        ctx.author().id,
     )
-    .fetch_optional(&mut *pool)
     .await?;
 
-    // Not a ticket channel
-    if ticket_id.is_none() {
-        return Ok(());
+    ctx.data().metrics.ticket_claimed(guild_id);
+
+    Ok(Some(ticket))
+}
+
+/// Refuses the authors who are not helpers
+async fn check_helper(
+    ctx: &Context<'_>,
+    pool: &mut Connection,
+    guild_id: GuildId,
+) -> Result<(), Error> {
+    if !get_staff_roles(ctx.http(), pool, guild_id, ctx.author().id)
+        .await?
+        .helper
+    {
+        let locale = get_locale(*ctx).await;
+        return Err(Error::permission(tr(locale, "claim.not_helper")));
+    }
+
+    Ok(())
+}
+
+/// Gives the ticket to the helper: the channel is moved to the claimed category, threads having
+/// no category, the helper is added to the thread instead
+pub(super) async fn join_ticket(
+    http: &Http,
+    pool: &mut Connection,
+    guild_id: GuildId,
+    location: TicketLocation,
+    helper_id: UserId,
+) -> Result<(), Error> {
+    match location {
+        TicketLocation::Channel(channel_id) => {
+            move_to_claimed_category(http, pool, guild_id, channel_id).await
+        }
+        TicketLocation::Thread(_) => location.add_member(http, helper_id).await,
     }
+}
 
+/// Moves the ticket channel to the claimed category
+async fn move_to_claimed_category(
+    http: &Http,
+    pool: &mut Connection,
+    guild_id: GuildId,
+    channel_id: ChannelId,
+) -> Result<(), Error> {
     // Change category
-    let category_channel_id: ChannelId = ChannelId::from(
-        sqlx::query!(
-            "SELECT claimed_category_id FROM servers WHERE id = $1",
-            guild_id.get() as i64
-        )
-        .fetch_one(&mut *pool)
-        .await?
-        .claimed_category_id as u64,
-    );
+    let category_channel_id =
+        get_available_category(http, pool, guild_id, CategoryState::Claimed).await?;
 
     let edit_channel = EditChannel::new()
         .kind(ChannelType::Text)
         .category(category_channel_id);
 
-    ctx.channel_id().edit(ctx.http(), edit_channel).await?;
+    channel_id.edit(http, edit_channel).await?;
+
+    // The unclaimed overflow category the channel came from may now be empty
+    if let Err(error) = remove_empty_overflow_categories(http, pool, guild_id).await {
+        warn!("Failed to remove empty overflow categories: {error}");
+    }
 
     Ok(())
 }
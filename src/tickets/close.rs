@@ -1,52 +1,319 @@
-use poise::serenity_prelude::{CreateEmbed, CreateMessage, Http, PartialGuild, UserId};
+use std::time::Duration;
+
+use poise::{
+    serenity_prelude::{
+        ButtonStyle, ChannelId, ComponentInteractionCollector, Context as SerenityContext,
+        CreateActionRow, CreateButton, CreateEmbed, CreateInteractionResponse,
+        CreateInteractionResponseMessage, CreateMessage, Http, Mentionable, PartialGuild, UserId,
+    },
+    CreateReply,
+};
 
 use crate::{
-    handler::{Context, Error},
+    database::{Connection, SubjectRepo, TicketRepo},
+    handler::{Context, Data, Error},
     helper::embed::Custom,
+    i18n::{get_locale, get_server_locale, tr, tr_args, Locale},
+};
+
+use super::{
+    audit::{log_embed, send_log},
+    categories::remove_empty_overflow_categories,
+    feedback::rating_buttons,
+    location::TicketLocation,
+    participants::get_participants,
+    staff::get_staff_roles,
+    templates::{render_template, TemplateKind},
+    transcript::{self, TranscriptHeader},
 };
 
-pub async fn close(ctx: &Context<'_>) -> Result<(), Error> {
+/// A ticket about to be closed
+pub struct ClosingTicket {
+    pub ticket_id: i32,
+    pub location: TicketLocation,
+    pub author: UserId,
+    pub subject: Option<String>,
+}
+
+/// Closes the ticket of the current channel
+///
+/// Only the author of the ticket and moderators can close it (helpers send a close request).
+/// The closing has to be confirmed, then can be cancelled during a short delay.
+pub async fn close(ctx: &Context<'_>, reason: Option<String>) -> Result<(), Error> {
+    let locale = get_locale(*ctx).await;
     let guild = ctx
         .guild_id()
-        .ok_or("Not in a guild")?
+        .ok_or_else(|| Error::user(tr(locale, "close.guild_only")))?
         .to_partial_guild(&ctx.http())
         .await?;
 
     let mut pool = ctx.data().pool.acquire().await?;
+    let server_locale = get_server_locale(&mut pool, guild.id).await;
+
+    let ticket = get_closing_ticket(&mut pool, ctx.channel_id())
+        .await?
+        .ok_or_else(|| Error::user(tr(locale, "close.not_a_ticket")))?;
+
+    if ctx.author().id != ticket.author
+        && !get_staff_roles(ctx.http(), &mut pool, guild.id, ctx.author().id)
+            .await?
+            .moderator
+    {
+        return Err(Error::permission(tr(locale, "close.not_allowed")));
+    }
+
+    drop(pool);
+
+    if !confirm(ctx, locale).await? {
+        return Ok(());
+    }
 
-    let channel = ctx.channel_id();
+    let reason = reason.unwrap_or_else(|| tr(server_locale, "close.default_reason"));
 
-    let Some(ticket) = sqlx::query!(
-        "SELECT ticket_id, author_id FROM tickets WHERE channel_id = $1",
-        channel.get() as i64
+    let _closing = ctx.data().shutdown.track_close();
+    if countdown(
+        ctx.serenity_context(),
+        &guild,
+        server_locale,
+        ticket.location.id(),
+        &reason,
+        ctx.data().timeouts.close_delay,
     )
-    .fetch_optional(&mut *pool)
     .await?
+    {
+        finalize(
+            ctx.http(),
+            ctx.data(),
+            &guild,
+            ticket,
+            ctx.author().id,
+            &reason,
+        )
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Asks the author of the ticket of the current channel to confirm that their issue is resolved
+///
+/// The ticket is closed if the author accepts the request.
+pub async fn request_close(ctx: &Context<'_>, reason: Option<String>) -> Result<(), Error> {
+    let locale = get_locale(*ctx).await;
+    let guild = ctx
+        .guild_id()
+        .ok_or_else(|| Error::user(tr(locale, "close.guild_only")))?
+        .to_partial_guild(&ctx.http())
+        .await?;
+
+    let mut pool = ctx.data().pool.acquire().await?;
+    // The request is answered by the author, in the language of the server
+    let server_locale = get_server_locale(&mut pool, guild.id).await;
+
+    let ticket = get_closing_ticket(&mut pool, ctx.channel_id())
+        .await?
+        .ok_or_else(|| Error::user(tr(locale, "close.not_a_ticket")))?;
+
+    if !get_staff_roles(ctx.http(), &mut pool, guild.id, ctx.author().id)
+        .await?
+        .any()
+    {
+        return Err(Error::permission(tr(locale, "close.request_not_allowed")));
+    }
+
+    drop(pool);
+
+    let reason = reason.unwrap_or_else(|| tr(server_locale, "close.default_reason"));
+
+    let ctx_id = ctx.id();
+    let accept_id = format!("{ctx_id}accept");
+    let decline_id = format!("{ctx_id}decline");
+
+    let embed = CreateEmbed::default_bot_embed(&guild)
+        .title(tr(server_locale, "close.request_title"))
+        .description(tr_args(
+            server_locale,
+            "close.request_description",
+            &[("staff", &ctx.author().mention().to_string())],
+        ))
+        .field(tr(server_locale, "close.reason_field"), &reason, false);
+
+    let handle = ctx
+        .send(
+            CreateReply::default()
+                .content(ticket.author.mention().to_string())
+                .embed(embed)
+                .components(confirmation_buttons(
+                    &accept_id,
+                    &decline_id,
+                    &tr(server_locale, "close.request_close_button"),
+                    &tr(server_locale, "close.request_keep_button"),
+                )),
+        )
+        .await?;
+
+    let author = ticket.author;
+    let Some(press) = ComponentInteractionCollector::new(ctx)
+        .filter(move |press| {
+            press.data.custom_id.starts_with(&ctx_id.to_string()) && press.user.id == author
+        })
+        .timeout(ctx.data().timeouts.close_request)
+        .await
     else {
-        warn!("Tried to close a non-ticket channel");
+        handle
+            .edit(
+                *ctx,
+                CreateReply::default()
+                    .content(tr(server_locale, "close.request_expired"))
+                    .components(vec![]),
+            )
+            .await?;
         return Ok(());
     };
 
-    // Check if the user is the author of the ticket
-    if ctx.author().id != UserId::from(ticket.author_id as u64) {
-        warn!("Tried to close a ticket that doesn't belong to them");
+    let accepted = press.data.custom_id == accept_id;
+    let answer = if accepted {
+        tr(server_locale, "close.request_accepted")
+    } else {
+        tr(server_locale, "close.request_declined")
+    };
+
+    press
+        .create_response(
+            ctx.serenity_context(),
+            CreateInteractionResponse::UpdateMessage(
+                CreateInteractionResponseMessage::new()
+                    .content(answer)
+                    .components(vec![]),
+            ),
+        )
+        .await?;
+
+    let _closing = ctx.data().shutdown.track_close();
+    if accepted
+        && countdown(
+            ctx.serenity_context(),
+            &guild,
+            server_locale,
+            ticket.location.id(),
+            &reason,
+            ctx.data().timeouts.close_delay,
+        )
+        .await?
+    {
+        finalize(ctx.http(), ctx.data(), &guild, ticket, author, &reason).await?;
+    }
+
+    Ok(())
+}
+
+/// Closes a ticket for good: stores the closing, logs it with its transcript,
+/// notifies the author and the participants, then deletes the channel
+pub async fn finalize(
+    http: &Http,
+    data: &Data,
+    guild: &PartialGuild,
+    ticket: ClosingTicket,
+    closed_by: UserId,
+    reason: &str,
+) -> Result<(), Error> {
+    let mut pool = data.pool.acquire().await?;
+    let locale = get_server_locale(&mut pool, guild.id).await;
+
+    let participants = get_participants(&mut pool, ticket.ticket_id).await?;
+
+    let author_mention = ticket.author.mention().to_string();
+    let ticket_id = ticket.ticket_id.to_string();
+    let subject = ticket
+        .subject
+        .clone()
+        .unwrap_or_else(|| tr(locale, "ticket.other_subject"));
+    let values = [
+        ("user", author_mention.as_str()),
+        ("ticket_id", ticket_id.as_str()),
+        ("subject", subject.as_str()),
+        ("reason", reason),
+        ("server", guild.name.as_str()),
+    ];
+    let closing_text =
+        render_template(&mut pool, guild.id, TemplateKind::Close, locale, &values).await?;
+
+    // Only one closing of the ticket goes through
+    if !TicketRepo::close(&mut pool, ticket.ticket_id, closed_by, reason).await? {
         return Ok(());
     }
+    data.cache
+        .remove_ticket_channel(guild.id, ticket.location.id());
 
-    // TODO: Check if the user is a moderator (helpers can't close tickets)
+    data.metrics.ticket_closed(guild.id);
 
-    // TODO: Log the closing of the ticket
+    // Log the closing of the ticket along with its transcript. The ticket is closed now, so its
+    // channel is deleted even if the log fails
+    let header = TranscriptHeader {
+        ticket_id: ticket.ticket_id,
+        author: ticket.author,
+        subject: ticket.subject,
+        participants: participants.clone(),
+    };
+    let logged: Result<(), Error> = async {
+        let transcript = transcript::build(http, ticket.location.id(), &header).await?;
+        let embed = log_embed(
+            guild,
+            locale,
+            &tr(locale, "close.closed_title"),
+            ticket.ticket_id,
+        )
+        .field(
+            tr(locale, "close.author_field"),
+            ticket.author.mention().to_string(),
+            true,
+        )
+        .field(
+            tr(locale, "close.closed_by_field"),
+            closed_by.mention().to_string(),
+            true,
+        )
+        .field(tr(locale, "close.reason_field"), reason, false);
+        send_log(
+            http,
+            &mut pool,
+            guild.id,
+            CreateMessage::new().embed(embed).add_file(transcript),
+        )
+        .await
+    }
+    .await;
+    if let Err(error) = logged {
+        warn!(
+            "Failed to log the closing of ticket {}: {error}",
+            ticket.ticket_id
+        );
+    }
 
-    send_closed_ticket_dm(
-        UserId::from(ticket.author_id as u64),
-        &guild,
-        ctx.http(),
-        "Ticket closed",
-    )
-    .await?;
+    // The author is asked to rate the ticket
+    let author_dm =
+        closed_ticket_dm(guild, locale, &closing_text).components(rating_buttons(ticket.ticket_id));
+    if let Err(error) = ticket.author.dm(http, author_dm).await {
+        warn!(
+            "Failed to send closing DM to author {}: {error}",
+            ticket.author
+        );
+    }
+
+    for participant in participants {
+        if let Err(error) =
+            send_closed_ticket_dm(participant, guild, locale, http, &closing_text).await
+        {
+            warn!("Failed to send closing DM to participant {participant}: {error}");
+        }
+    }
 
     // Delete the channel
-    ctx.channel_id().delete(&ctx.http()).await?;
+    ticket.location.delete(http).await?;
+
+    if let Err(error) = remove_empty_overflow_categories(http, &mut pool, guild.id).await {
+        warn!("Failed to remove empty overflow categories: {error}");
+    }
 
     Ok(())
 }
@@ -54,15 +321,187 @@ pub async fn close(ctx: &Context<'_>) -> Result<(), Error> {
 pub async fn send_closed_ticket_dm(
     user: UserId,
     guild: &PartialGuild,
+    locale: Locale,
     http_cache: &Http,
     reason: &str,
 ) -> Result<(), Error> {
+    user.dm(http_cache, closed_ticket_dm(guild, locale, reason))
+        .await?;
+
+    Ok(())
+}
+
+/// Returns the message sent in DM when a ticket is closed
+fn closed_ticket_dm(guild: &PartialGuild, locale: Locale, reason: &str) -> CreateMessage {
+    let embed = CreateEmbed::default_bot_embed(guild)
+        .title(tr(locale, "close.closed_title"))
+        .field(tr(locale, "close.reason_field"), reason, false);
+
+    CreateMessage::default().embed(embed)
+}
+
+/// Returns the open ticket linked to the channel, if any
+pub(super) async fn get_closing_ticket(
+    pool: &mut Connection,
+    channel_id: ChannelId,
+) -> Result<Option<ClosingTicket>, Error> {
+    let Some(ticket) = TicketRepo::by_channel(pool, channel_id)
+        .await?
+        .filter(|ticket| ticket.is_open)
+    else {
+        return Ok(None);
+    };
+
+    let subject = match ticket.subject_id {
+        Some(subject_id) => SubjectRepo::get(pool, ticket.guild_id, subject_id)
+            .await?
+            .map(|subject| subject.name),
+        None => None,
+    };
+
+    Ok(Some(ClosingTicket {
+        ticket_id: ticket.id,
+        location: ticket.location,
+        author: ticket.author_id,
+        subject,
+    }))
+}
+
+/// Asks the author of the command to confirm the closing
+async fn confirm(ctx: &Context<'_>, locale: Locale) -> Result<bool, Error> {
+    let ctx_id = ctx.id();
+    let confirm_id = format!("{ctx_id}confirm");
+    let cancel_id = format!("{ctx_id}cancel");
+
+    let handle = ctx
+        .send(
+            CreateReply::default()
+                .content(tr(locale, "close.confirm_question"))
+                .components(confirmation_buttons(
+                    &confirm_id,
+                    &cancel_id,
+                    &tr(locale, "close.confirm_button"),
+                    &tr(locale, "close.cancel_button"),
+                ))
+                .ephemeral(true),
+        )
+        .await?;
+
+    let author = ctx.author().id;
+    let Some(press) = ComponentInteractionCollector::new(ctx)
+        .filter(move |press| {
+            press.data.custom_id.starts_with(&ctx_id.to_string()) && press.user.id == author
+        })
+        .timeout(ctx.data().timeouts.prompt)
+        .await
+    else {
+        handle
+            .edit(
+                *ctx,
+                CreateReply::default()
+                    .content(tr(locale, "close.cancelled"))
+                    .components(vec![]),
+            )
+            .await?;
+        return Ok(false);
+    };
+
+    let confirmed = press.data.custom_id == confirm_id;
+    let answer = if confirmed {
+        tr(locale, "close.closing")
+    } else {
+        tr(locale, "close.cancelled")
+    };
+
+    press
+        .create_response(
+            ctx.serenity_context(),
+            CreateInteractionResponse::UpdateMessage(
+                CreateInteractionResponseMessage::new()
+                    .content(answer)
+                    .components(vec![]),
+            ),
+        )
+        .await?;
+
+    Ok(confirmed)
+}
+
+/// Announces the closing of the ticket and waits for a possible cancellation
+///
+/// Returns `true` if the ticket should be closed
+async fn countdown(
+    ctx: &SerenityContext,
+    guild: &PartialGuild,
+    locale: Locale,
+    channel_id: ChannelId,
+    reason: &str,
+    delay: Duration,
+) -> Result<bool, Error> {
     let embed = CreateEmbed::default_bot_embed(guild)
-        .title("Ticket Closed")
-        .field("Reason", reason, false);
+        .title(tr(locale, "close.countdown_title"))
+        .description(tr_args(
+            locale,
+            "close.countdown_description",
+            &[("seconds", &delay.as_secs().to_string())],
+        ))
+        .field(tr(locale, "close.reason_field"), reason, false);
 
-    user.dm(http_cache, CreateMessage::default().embed(embed))
+    let cancel_button = CreateButton::new("close_cancel")
+        .label(tr(locale, "close.cancel_button"))
+        .style(ButtonStyle::Danger);
+
+    let message = channel_id
+        .send_message(
+            ctx,
+            CreateMessage::new()
+                .embed(embed)
+                .components(vec![CreateActionRow::Buttons(vec![cancel_button])]),
+        )
         .await?;
 
-    Ok(())
+    let Some(press) = message
+        .await_component_interaction(ctx)
+        .timeout(delay)
+        .await
+    else {
+        return Ok(true);
+    };
+
+    press
+        .create_response(
+            ctx,
+            CreateInteractionResponse::UpdateMessage(
+                CreateInteractionResponseMessage::new()
+                    .embed(
+                        CreateEmbed::default_bot_embed(guild)
+                            .title(tr(locale, "close.cancelled_title"))
+                            .description(tr_args(
+                                locale,
+                                "close.cancelled_description",
+                                &[("user", &press.user.mention().to_string())],
+                            )),
+                    )
+                    .components(vec![]),
+            ),
+        )
+        .await?;
+
+    Ok(false)
+}
+
+fn confirmation_buttons(
+    confirm_id: &str,
+    cancel_id: &str,
+    confirm_label: &str,
+    cancel_label: &str,
+) -> Vec<CreateActionRow> {
+    vec![CreateActionRow::Buttons(vec![
+        CreateButton::new(confirm_id)
+            .label(confirm_label)
+            .style(ButtonStyle::Danger),
+        CreateButton::new(cancel_id)
+            .label(cancel_label)
+            .style(ButtonStyle::Secondary),
+    ])]
 }
@@ -1,79 +1,347 @@
 use std::time::Duration;
 
 use crate::{
-    database::get_subjects,
+    database::{NewTicket, ServerRepo, Storage, Subject, SubjectRepo, TicketRepo},
     handler::{Data, Error},
     helper::{embed::Custom, fuzzy_match::match_subjects},
+    i18n::{tr, tr_args, Locale},
 };
+use chrono::Utc;
 use poise::serenity_prelude::{
     CacheHttp, ChannelId, ChannelType, ComponentInteractionDataKind, Context, CreateActionRow,
-    CreateChannel, CreateEmbed, CreateEmbedFooter, CreateMessage, CreateSelectMenu,
-    CreateSelectMenuKind, CreateSelectMenuOption, EditChannel, Http, Member, Mentionable,
-    PartialGuild,
+    CreateChannel, CreateEmbed, CreateEmbedAuthor, CreateEmbedFooter, CreateInteractionResponse,
+    CreateMessage, CreateSelectMenu, CreateSelectMenuKind, CreateSelectMenuOption, CreateThread,
+    GuildChannel, Http, Member, Mentionable, PartialGuild, UserId,
 };
 
-use super::{close::send_closed_ticket_dm, TICKET_EMOJI};
+use super::{
+    assign::{assigned_message, auto_assign},
+    categories::{get_available_category, get_available_category_in, CategoryState},
+    close::send_closed_ticket_dm,
+    location::{TicketLocation, TicketMode},
+    office_hours::{ClosedMode, OfficeHours},
+    panels::Panel,
+    priority::{
+        announce_priority, priority_select_menu, recolour_greeting, Priority, PRIORITY_SELECT,
+    },
+    templates::{render_template, TemplateKind},
+    TICKET_EMOJI,
+};
+
+/// Options given when creating a ticket
+#[derive(Default)]
+pub struct CreateOptions {
+    /// The subject of the ticket, the subject prompts are skipped when given
+    pub subject: Option<Subject>,
+    /// The staff member who opened the ticket on behalf of the member
+    pub opened_by: Option<Member>,
+    /// A first message to post in the ticket channel
+    pub message: Option<String>,
+    /// The panel the ticket was opened from
+    pub panel: Option<Panel>,
+    /// The language of the member, the default language of the server if `None`
+    pub locale: Option<Locale>,
+    /// The priority of the ticket, the member chooses it along with the subject if `None`
+    pub priority: Option<Priority>,
+}
 
 /// Handles the creation of a ticket
-/// It creates a new channel in the unclaimed category
-/// and sends a DM to the user with the channel id
+/// It creates a new channel in the unclaimed category (or a private thread of the ticket channel,
+/// depending on the ticket mode of the server) and sends a DM to the user with the channel id
 pub async fn create(
     ctx: &Context,
     data: &Data,
     member: &Member,
-    unclaimed_category_id: u64,
+    mut options: CreateOptions,
 ) -> Result<(), Error> {
-    // TODO: Improve error handling
+    if !data.shutdown.is_accepting() {
+        let locale = options.locale.unwrap_or_default();
+        return Err(Error::user(tr(locale, "shutdown.not_accepting")));
+    }
+
     let guild = member.guild_id.to_partial_guild(ctx.http()).await?;
     let mut pool = data.pool.acquire().await?;
 
-    // Create channel
-    let channel_builder = CreateChannel::new(temp_ticket_channel_name(member))
-        .category(unclaimed_category_id)
-        .position(0)
-        .topic("Ticket channel")
-        .kind(ChannelType::Text);
+    let server = ServerRepo::get(&mut pool, guild.id).await?;
+
+    let mode = server.ticket_mode;
+    let locale = options.locale.unwrap_or(server.locale);
+
+    // The staff can open tickets at any time, members only during office hours (or as pending)
+    let by_member = options.opened_by.is_none();
+    let server_hours = OfficeHours::of_server(&server);
+    if by_member && server.closed_mode == ClosedMode::Refuse && !server_hours.is_open(Utc::now()) {
+        let notice = server_hours.closed_notice(locale, Utc::now());
+        return Err(Error::user(tr_args(
+            locale,
+            "office_hours.refused",
+            &[("notice", &notice)],
+        )));
+    }
+
+    // Create channel or thread
+    let channel = match mode {
+        TicketMode::Channel => {
+            // The category of the panel overflows like the ones of the server
+            let category = match options.panel.as_ref().and_then(|panel| panel.category_id) {
+                Some(base) => {
+                    get_available_category_in(
+                        ctx.http(),
+                        &mut pool,
+                        guild.id,
+                        CategoryState::Unclaimed,
+                        base,
+                    )
+                    .await?
+                }
+                None => {
+                    get_available_category(
+                        ctx.http(),
+                        &mut pool,
+                        guild.id,
+                        CategoryState::Unclaimed,
+                    )
+                    .await?
+                }
+            };
+
+            let channel_builder = CreateChannel::new(temp_ticket_channel_name(member))
+                .category(category)
+                .position(0)
+                .topic(tr(locale, "ticket.channel_topic"))
+                .kind(ChannelType::Text);
+
+            guild.create_channel(ctx.http(), channel_builder).await?
+        }
+        TicketMode::Thread => {
+            let thread_builder = CreateThread::new(temp_ticket_channel_name(member))
+                .kind(ChannelType::PrivateThread)
+                .invitable(false);
+
+            // Threads are created in the channel of the panel, or the default ticket channel
+            let parent = options
+                .panel
+                .as_ref()
+                .map_or(server.ticket_channel_id, |panel| panel.channel_id);
+
+            parent.create_thread(ctx.http(), thread_builder).await?
+        }
+    };
+
+    let location = mode.location(channel.id);
+    let _flow = data.shutdown.track_flow(channel.id, locale);
+    location.add_member(ctx.http(), member.user.id).await?;
+
+    // Subjects offered by the panel, every subject of the server otherwise
+    let mut subjects = SubjectRepo::all(&mut pool, guild.id).await?;
+    if let Some(panel) = options
+        .panel
+        .as_ref()
+        .filter(|panel| !panel.subject_ids.is_empty())
+    {
+        subjects.retain(|subject| subject.id.is_some_and(|id| panel.subject_ids.contains(&id)));
+
+        // A panel with a single subject does not need to ask for it
+        if options.subject.is_none() && subjects.len() == 1 {
+            options.subject = subjects.pop();
+        }
+    }
 
-    let mut channel = guild.create_channel(ctx.http(), channel_builder).await?;
+    let user_mention = member.mention().to_string();
+    let channel_mention = channel.mention().to_string();
+    let values = [
+        ("user", user_mention.as_str()),
+        ("channel", channel_mention.as_str()),
+        ("server", guild.name.as_str()),
+    ];
+    let instructions =
+        render_template(&mut pool, guild.id, TemplateKind::OpenDm, locale, &values).await?;
+    let greeting =
+        render_template(&mut pool, guild.id, TemplateKind::Greeting, locale, &values).await?;
 
     // Send DM to the user in a separate task to avoid blocking
     let cache_copy = ctx.http.clone();
     let user = member.user.clone();
     let channel_id = channel.id;
     let guild_copy = guild.clone();
+    let opened_by = options.opened_by.as_ref().map(|staff| staff.user.id);
+    let prefix = server.prefix.clone();
     tokio::spawn(async move {
-        let message = get_open_ticket_dm(&guild_copy, channel_id);
-        user.dm(cache_copy, message).await.unwrap_or_else(|e| {
-            panic!("Failed to send DM to user: {e}");
-        });
+        let message = get_open_ticket_dm(
+            &guild_copy,
+            locale,
+            &prefix,
+            channel_id,
+            opened_by,
+            &instructions,
+        );
+        if let Err(error) = user.dm(&cache_copy, message).await {
+            let error = Error::from(error);
+            warn!("Failed to send DM to user {}: {error}", user.id);
+
+            // The user would not know where their ticket is, they are told in the ticket instead
+            if error.is_dm_closed() {
+                let notice = tr_args(
+                    locale,
+                    "ticket.dm_failed",
+                    &[("user", &user.mention().to_string())],
+                );
+                if let Err(error) = channel_id.say(&cache_copy, notice).await {
+                    warn!("Failed to warn user {} about the DM: {error}", user.id);
+                }
+            }
+        }
     });
 
     // Send message in channel
-    channel
-        .send_message(ctx.http(), get_open_ticket_message(member, &guild))
+    let shown_priority = options.priority.unwrap_or_default();
+    let mut greeting_message = channel
+        .send_message(
+            ctx.http(),
+            get_open_ticket_message(&guild, locale, &server.prefix, &options, &greeting),
+        )
         .await?;
 
-    // Wait for user input
-    // TODO: Make timeout configurable
-    let subject = match channel
-        .await_reply(ctx)
-        .timeout(Duration::from_secs(60))
+    if let Some(message) = &options.message {
+        channel
+            .send_message(
+                ctx.http(),
+                get_staff_message(&guild, locale, &options, message),
+            )
+            .await?;
+    }
+
+    let (subject, priority) = match options.subject {
+        Some(subject) => (subject, shown_priority),
+        None => match prompt_subject(
+            ctx,
+            subjects,
+            &guild,
+            locale,
+            &channel,
+            options.priority,
+            data.timeouts.prompt,
+        )
+        .await?
+        {
+            Some(choice) => choice,
+            None => {
+                data.metrics.creation_timed_out(guild.id);
+                let reason = tr(locale, "ticket.creation_timeout");
+                return discard_ticket(location, member, &guild, locale, ctx.http(), &reason).await;
+            }
+        },
+    };
+
+    // Update channel name
+    let name = format!("{}-{}", TICKET_EMOJI, subject.name);
+    location
+        .rename(ctx.http(), priority.channel_name(&name))
+        .await?;
+
+    if priority != shown_priority {
+        recolour_greeting(ctx.http(), &mut greeting_message, priority).await?;
+    }
+
+    // The subject may narrow the office hours, and they may have ended during the prompt
+    let subject_hours = match subject.id {
+        Some(subject_id) => pool.subject_office_hours(guild.id, subject_id).await?,
+        None => None,
+    };
+    let hours = server_hours.with_subject(subject_hours);
+    let now = Utc::now();
+    let pending = by_member && !hours.is_open(now);
+    if pending && server.closed_mode == ClosedMode::Refuse {
+        let reason = tr_args(
+            locale,
+            "office_hours.refused",
+            &[("notice", &hours.closed_notice(locale, now))],
+        );
+        return discard_ticket(location, member, &guild, locale, ctx.http(), &reason).await;
+    }
+
+    // Add ticket to database
+    let ticket = NewTicket {
+        location,
+        guild_id: guild.id,
+        author_id: member.user.id,
+        subject_id: subject.id,
+        opened_by,
+        priority,
+        greeting_message_id: Some(greeting_message.id),
+        pending,
+    };
+    TicketRepo::insert(&mut pool, &ticket).await?;
+    data.cache.add_ticket_channel(guild.id, location.id());
+
+    let message = CreateMessage::new().content(tr_args(
+        locale,
+        "ticket.created_with_subject",
+        &[("subject", &subject.name)],
+    ));
+
+    channel.send_message(ctx.http(), message).await?;
+
+    data.metrics.ticket_created(guild.id);
+
+    // Pending tickets are handed to the helpers when the office hours start
+    if pending {
+        let message = tr_args(
+            locale,
+            "office_hours.pending",
+            &[("notice", &hours.closed_notice(locale, now))],
+        );
+        channel.say(ctx.http(), message).await?;
+        return Ok(());
+    }
+
+    // The ticket is already usable, an assignment failure leaves it to be claimed
+    let assigned = auto_assign(ctx.http(), &mut pool, &server, location, subject.id)
         .await
-    {
-        Some(reply) => reply.content,
-        None => {
-            return handle_timeout(&channel.id, member, &guild, ctx.http()).await;
+        .unwrap_or_else(|error| {
+            warn!("Failed to assign ticket {}: {error}", location.id());
+            None
+        });
+
+    match assigned {
+        Some(helper_id) => {
+            channel
+                .send_message(ctx.http(), assigned_message(locale, helper_id))
+                .await?;
+
+            data.metrics.ticket_claimed(guild.id);
         }
+        None => announce_priority(ctx.http(), &mut pool, guild.id, location, priority).await,
+    }
+
+    Ok(())
+}
+
+/// Asks the member for the subject of the ticket, and for its priority if it is not given
+///
+/// Returns `None` if the member did not answer in time
+async fn prompt_subject(
+    ctx: &Context,
+    subjects: Vec<Subject>,
+    guild: &PartialGuild,
+    locale: Locale,
+    channel: &GuildChannel,
+    priority: Option<Priority>,
+    timeout: Duration,
+) -> Result<Option<(Subject, Priority)>, Error> {
+    // Wait for user input
+    let subject = match channel.await_reply(ctx).timeout(timeout).await {
+        Some(reply) => reply.content,
+        None => return Ok(None),
     };
 
     // Fuzzy match subjects
-    let subjects = get_subjects(&mut pool, guild.id).await?;
     let mut fuzzy_result = match_subjects(&subjects, &subject, 5);
 
     // Add default subject
-    fuzzy_result.push(crate::database::Subject {
+    fuzzy_result.push(Subject {
         id: None,
-        name: "Other".to_string(),
+        name: tr(locale, "ticket.other_subject"),
     });
 
     // Send select menu
@@ -90,106 +358,165 @@ pub async fn create(
         },
     );
 
+    let mut components = vec![CreateActionRow::SelectMenu(select_menu)];
+    if priority.is_none() {
+        components.push(CreateActionRow::SelectMenu(priority_select_menu(locale)));
+    }
+
     let message = CreateMessage::default()
         .embed(
-            CreateEmbed::default_bot_embed(&guild)
-                .title("Select an option")
-                .description("Please select the subject of your ticket"),
+            CreateEmbed::default_bot_embed(guild)
+                .title(tr(locale, "ticket.select_title"))
+                .description(tr(locale, "ticket.select_description")),
         )
-        .components(vec![CreateActionRow::SelectMenu(select_menu)]);
+        .components(components);
 
     let sent = channel.send_message(ctx.http(), message).await?;
 
-    // Wait for user input
-    let subject = match sent
-        .await_component_interaction(ctx)
-        .timeout(Duration::from_secs(60))
-        .await
-    {
-        Some(component) => match component.data.kind {
-            ComponentInteractionDataKind::StringSelect { values } => {
-                assert!(values.len() == 1);
-                let Ok(index) = values[0].parse::<usize>() else {
-                    return Ok(());
-                };
-                fuzzy_result.remove(index)
-            }
-            _ => return Ok(()),
-        },
-        None => return Ok(()),
-    };
-
-    // Update channel name
-    let edit_channel = EditChannel::default().name(format!("{}-{}", TICKET_EMOJI, subject.name));
+    // Wait for user input, the priority can be chosen any number of times before the subject
+    let mut priority = priority.unwrap_or_default();
+    let component = loop {
+        let Some(component) = sent.await_component_interaction(ctx).timeout(timeout).await else {
+            return Ok(None);
+        };
 
-    channel.edit(ctx.http(), edit_channel).await?;
+        if component.data.custom_id != PRIORITY_SELECT {
+            break component;
+        }
 
-    // Add ticket to database
-    sqlx::query!(
-        "INSERT INTO tickets (channel_id, server_id, subject_id, author_id) VALUES ($1, $2, $3, $4)",
-        channel.id.get() as i64,
-        guild.id.get() as i64,
-        subject.id.and_then(|id| Some(id as i64)),
-        member.user.id.get() as i64
-    ).execute(&mut *pool).await?;
+        if let ComponentInteractionDataKind::StringSelect { values } = &component.data.kind {
+            if let Some(chosen) = values.first().and_then(|value| value.parse().ok()) {
+                priority = chosen;
+            }
+        }
+        component
+            .create_response(ctx.http(), CreateInteractionResponse::Acknowledge)
+            .await?;
+    };
 
-    let message =
-        CreateMessage::new().content(format!("Ticket created with subject: {}", subject.name));
+    let ComponentInteractionDataKind::StringSelect { values } = component.data.kind else {
+        return Ok(None);
+    };
 
-    channel.send_message(ctx.http(), message).await?;
+    let [value] = values.as_slice() else {
+        return Err("Expected a single subject in the selection".into());
+    };
+    let Ok(index) = value.parse::<usize>() else {
+        return Ok(None);
+    };
 
-    Ok(())
+    Ok(Some((fuzzy_result.remove(index), priority)))
 }
 
-/// Handles the timeout for the ticket creation
-async fn handle_timeout(
-    channel: &ChannelId,
+/// Deletes a ticket that could not be opened (timed out or outside office hours) and tells its
+/// author why
+async fn discard_ticket(
+    location: TicketLocation,
     member: &Member,
     guild: &PartialGuild,
+    locale: Locale,
     http: &Http,
+    reason: &str,
 ) -> Result<(), Error> {
     // Delete ticket channel
-    channel.delete(http).await?;
+    location.delete(http).await?;
 
     // Send DM to user
-    send_closed_ticket_dm(member.user.id, guild, http, "Ticket creation timed out").await?;
+    if let Err(error) = send_closed_ticket_dm(member.user.id, guild, locale, http, reason).await {
+        warn!(
+            "Failed to send closing DM to user {}: {error}",
+            member.user.id
+        );
+    }
 
     Ok(())
 }
 
 /// Returns an embed message to be sent to the user in DM when the person opens a ticket
-fn get_open_ticket_dm(guild: &PartialGuild, channel_id: ChannelId) -> CreateMessage {
-    let embed = CreateEmbed::default_bot_embed(guild)
-        .title("Ticket Created")
-        .field("Ticket Channel", format!("<#{channel_id}>"), false)
+fn get_open_ticket_dm(
+    guild: &PartialGuild,
+    locale: Locale,
+    prefix: &str,
+    channel_id: ChannelId,
+    opened_by: Option<UserId>,
+    instructions: &str,
+) -> CreateMessage {
+    let mut embed = CreateEmbed::default_bot_embed(guild).title(tr(locale, "ticket.created_title"));
+
+    if let Some(staff) = opened_by {
+        embed = embed.description(tr_args(
+            locale,
+            "ticket.opened_by_staff",
+            &[("staff", &staff.mention().to_string())],
+        ));
+    }
+
+    let embed = embed
         .field(
-            "Next Steps",
-            "Please visit the ticket channel and provide details about your question or issue.",
+            tr(locale, "ticket.channel_field"),
+            format!("<#{channel_id}>"),
             false,
         )
-        .footer(CreateEmbedFooter::new(
-            "To close the ticket, type `$close` in the ticket channel",
-        ));
+        .field(tr(locale, "ticket.next_steps_field"), instructions, false)
+        .footer(CreateEmbedFooter::new(tr_args(
+            locale,
+            "ticket.close_hint",
+            &[("prefix", prefix)],
+        )));
 
     CreateMessage::new().embed(embed)
 }
 
 /// Returns an embed message to be sent to the user in the ticket channel when the ticket is opened
-fn get_open_ticket_message(member: &Member, guild: &PartialGuild) -> CreateMessage {
-    let embed = CreateEmbed::default_bot_embed(guild)
-        .title("Ticket Created")
-        .description(format!(
-            "Hello {} welcome to your ticket channel.",
-            member.mention()
-        ))
-        .field(
-            "Subject",
-            "Please provide the subject of your ticket",
+fn get_open_ticket_message(
+    guild: &PartialGuild,
+    locale: Locale,
+    prefix: &str,
+    options: &CreateOptions,
+    greeting: &str,
+) -> CreateMessage {
+    let subject = options.subject.as_ref().map_or_else(
+        || tr(locale, "ticket.subject_prompt"),
+        |subject| subject.name.clone(),
+    );
+
+    let mut embed = CreateEmbed::default_bot_embed(guild)
+        .colour(options.priority.unwrap_or_default().colour())
+        .title(tr(locale, "ticket.created_title"))
+        .description(greeting)
+        .field(tr(locale, "ticket.subject_field"), subject, false);
+
+    if let Some(staff) = &options.opened_by {
+        embed = embed.field(
+            tr(locale, "ticket.opened_by_field"),
+            staff.mention().to_string(),
             false,
-        )
-        .footer(CreateEmbedFooter::new(
-            "To close the ticket, type `$close` in the ticket channel",
-        ));
+        );
+    }
+
+    let embed = embed.footer(CreateEmbedFooter::new(tr_args(
+        locale,
+        "ticket.close_hint",
+        &[("prefix", prefix)],
+    )));
+
+    CreateMessage::new().embed(embed)
+}
+
+/// Returns an embed message containing the first message of the staff member who opened the ticket
+fn get_staff_message(
+    guild: &PartialGuild,
+    locale: Locale,
+    options: &CreateOptions,
+    message: &str,
+) -> CreateMessage {
+    let mut embed = CreateEmbed::default_bot_embed(guild)
+        .title(tr(locale, "ticket.staff_message_title"))
+        .description(message);
+
+    if let Some(staff) = &options.opened_by {
+        embed = embed.author(CreateEmbedAuthor::new(staff.display_name()).icon_url(staff.face()));
+    }
 
     CreateMessage::new().embed(embed)
 }
@@ -197,7 +524,7 @@ fn get_open_ticket_message(member: &Member, guild: &PartialGuild) -> CreateMessa
 /// Returns the name of the temporary name for the newly created ticket channel
 /// It is temporary as the name will be changed based on the user's input
 pub fn temp_ticket_channel_name(member: &Member) -> String {
-    let current_time = chrono::Utc::now();
+    let current_time = Utc::now();
     format!(
         "{}-{}-{}",
         TICKET_EMOJI,
@@ -0,0 +1,246 @@
+//! This module handles the satisfaction rating sent to the author once their ticket is closed
+
+use poise::serenity_prelude::{
+    ActionRowComponent, ButtonStyle, ComponentInteraction, Context, CreateActionRow, CreateButton,
+    CreateInputText, CreateInteractionResponse, CreateInteractionResponseMessage, CreateModal,
+    InputTextStyle, Interaction, ModalInteraction,
+};
+
+use crate::{
+    database::Storage,
+    handler::{Data, Error},
+    i18n::{tr, tr_args, Locale},
+};
+
+/// Prefix of the custom IDs of the feedback components
+const FEEDBACK_PREFIX: &str = "feedback";
+
+/// Highest rating that can be given
+const MAX_RATING: i16 = 5;
+
+/// Maximum length of a feedback comment
+const MAX_COMMENT_LENGTH: u16 = 1024;
+
+/// Returns the rating buttons (1 to 5 stars) of a ticket
+pub fn rating_buttons(ticket_id: i32) -> Vec<CreateActionRow> {
+    let buttons = (1..=MAX_RATING)
+        .map(|rating| {
+            CreateButton::new(format!("{FEEDBACK_PREFIX}:rate:{ticket_id}:{rating}"))
+                .label("⭐".repeat(rating as usize))
+                .style(ButtonStyle::Secondary)
+        })
+        .collect();
+
+    vec![CreateActionRow::Buttons(buttons)]
+}
+
+/// Handles the interactions with the feedback components, ignoring any other interaction
+pub async fn handle_interaction(
+    ctx: &Context,
+    data: &Data,
+    interaction: &Interaction,
+) -> Result<(), Error> {
+    match interaction {
+        Interaction::Component(component) => {
+            let Some(action) = parse_custom_id(&component.data.custom_id) else {
+                return Ok(());
+            };
+            match action {
+                FeedbackAction::Rate { ticket_id, rating } => {
+                    rate(ctx, data, component, ticket_id, rating).await
+                }
+                FeedbackAction::Comment { ticket_id } => {
+                    open_comment_modal(ctx, component, ticket_id).await
+                }
+            }
+        }
+        Interaction::Modal(modal) => {
+            let Some(FeedbackAction::Comment { ticket_id }) =
+                parse_custom_id(&modal.data.custom_id)
+            else {
+                return Ok(());
+            };
+            comment(ctx, data, modal, ticket_id).await
+        }
+        _ => Ok(()),
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum FeedbackAction {
+    Rate { ticket_id: i32, rating: i16 },
+    Comment { ticket_id: i32 },
+}
+
+/// Parses the custom ID of a feedback component
+///
+/// Custom IDs are either `feedback:rate:<ticket>:<rating>` or `feedback:comment:<ticket>`
+fn parse_custom_id(custom_id: &str) -> Option<FeedbackAction> {
+    let mut parts = custom_id.split(':');
+
+    if parts.next()? != FEEDBACK_PREFIX {
+        return None;
+    }
+
+    let action = parts.next()?;
+    let ticket_id = parts.next()?.parse().ok()?;
+
+    match action {
+        "rate" => {
+            let rating = parts.next()?.parse().ok()?;
+            (1..=MAX_RATING)
+                .contains(&rating)
+                .then_some(FeedbackAction::Rate { ticket_id, rating })
+        }
+        "comment" => Some(FeedbackAction::Comment { ticket_id }),
+        _ => None,
+    }
+}
+
+/// Stores the rating of a ticket, then offers to leave a comment
+async fn rate(
+    ctx: &Context,
+    data: &Data,
+    component: &ComponentInteraction,
+    ticket_id: i32,
+    rating: i16,
+) -> Result<(), Error> {
+    let locale = Locale::from_discord(&component.locale).unwrap_or_default();
+    let mut pool = data.pool.acquire().await?;
+
+    // Only the author of a closed ticket can rate it
+    let stored = pool
+        .rate_ticket(ticket_id, component.user.id, rating)
+        .await?;
+
+    if !stored {
+        return Err(Error::user(tr_args(
+            locale,
+            "feedback.cannot_rate",
+            &[("ticket_id", &ticket_id.to_string())],
+        )));
+    }
+
+    let comment_button = CreateButton::new(format!("{FEEDBACK_PREFIX}:comment:{ticket_id}"))
+        .label(tr(locale, "feedback.comment_button"))
+        .style(ButtonStyle::Primary);
+
+    component
+        .create_response(
+            ctx,
+            CreateInteractionResponse::UpdateMessage(
+                CreateInteractionResponseMessage::new()
+                    .content(tr_args(
+                        locale,
+                        "feedback.rated",
+                        &[("stars", &"⭐".repeat(rating as usize))],
+                    ))
+                    .components(vec![CreateActionRow::Buttons(vec![comment_button])]),
+            ),
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Opens the modal used to write a feedback comment
+async fn open_comment_modal(
+    ctx: &Context,
+    component: &ComponentInteraction,
+    ticket_id: i32,
+) -> Result<(), Error> {
+    let locale = Locale::from_discord(&component.locale).unwrap_or_default();
+
+    let input = CreateInputText::new(
+        InputTextStyle::Paragraph,
+        tr(locale, "feedback.comment_label"),
+        "comment",
+    )
+    .placeholder(tr(locale, "feedback.comment_placeholder"))
+    .max_length(MAX_COMMENT_LENGTH)
+    .required(true);
+
+    let modal = CreateModal::new(
+        format!("{FEEDBACK_PREFIX}:comment:{ticket_id}"),
+        tr(locale, "feedback.modal_title"),
+    )
+    .components(vec![CreateActionRow::InputText(input)]);
+
+    component
+        .create_response(ctx, CreateInteractionResponse::Modal(modal))
+        .await?;
+
+    Ok(())
+}
+
+/// Stores the feedback comment of a ticket
+async fn comment(
+    ctx: &Context,
+    data: &Data,
+    modal: &ModalInteraction,
+    ticket_id: i32,
+) -> Result<(), Error> {
+    let comment = modal
+        .data
+        .components
+        .iter()
+        .flat_map(|row| &row.components)
+        .find_map(|component| match component {
+            ActionRowComponent::InputText(input) => input.value.clone(),
+            _ => None,
+        })
+        .unwrap_or_default();
+
+    let locale = Locale::from_discord(&modal.locale).unwrap_or_default();
+    let mut pool = data.pool.acquire().await?;
+
+    pool.comment_ticket(ticket_id, modal.user.id, &comment)
+        .await?;
+
+    modal
+        .create_response(
+            ctx,
+            CreateInteractionResponse::UpdateMessage(
+                CreateInteractionResponseMessage::new()
+                    .content(tr(locale, "feedback.thanks"))
+                    .components(vec![]),
+            ),
+        )
+        .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_rate_custom_id() {
+        let action = parse_custom_id("feedback:rate:12:4");
+        assert_eq!(
+            action,
+            Some(FeedbackAction::Rate {
+                ticket_id: 12,
+                rating: 4
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_rate_custom_id_out_of_range() {
+        assert_eq!(parse_custom_id("feedback:rate:12:6"), None);
+        assert_eq!(parse_custom_id("feedback:rate:12:0"), None);
+    }
+
+    #[test]
+    fn test_parse_comment_custom_id() {
+        let action = parse_custom_id("feedback:comment:7");
+        assert_eq!(action, Some(FeedbackAction::Comment { ticket_id: 7 }));
+    }
+
+    #[test]
+    fn test_parse_unrelated_custom_id() {
+        assert_eq!(parse_custom_id("123456prev"), None);
+    }
+}
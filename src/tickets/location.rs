@@ -0,0 +1,184 @@
+//! This module maps a ticket to the place it lives in on Discord (a channel or a thread)
+
+use std::{fmt, str::FromStr};
+
+use poise::serenity_prelude::{
+    ChannelId, EditChannel, EditThread, Http, PermissionOverwrite, PermissionOverwriteType,
+    Permissions, RoleId, UserId,
+};
+
+use crate::{database::Snowflake, handler::Error};
+
+/// Permissions given to a member of a ticket channel
+const MEMBER_PERMISSIONS: Permissions = Permissions::VIEW_CHANNEL
+    .union(Permissions::SEND_MESSAGES)
+    .union(Permissions::READ_MESSAGE_HISTORY)
+    .union(Permissions::ATTACH_FILES);
+
+/// How the tickets of a server are created
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, poise::ChoiceParameter)]
+pub enum TicketMode {
+    /// Each ticket is a text channel in the ticket categories
+    #[default]
+    #[name = "channel"]
+    Channel,
+    /// Each ticket is a private thread of the ticket channel
+    #[name = "thread"]
+    Thread,
+}
+
+impl TicketMode {
+    /// Returns the name of the mode, as stored in the database
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Channel => "channel",
+            Self::Thread => "thread",
+        }
+    }
+
+    /// Returns the location of a ticket created with this mode
+    pub fn location(self, channel_id: ChannelId) -> TicketLocation {
+        match self {
+            Self::Channel => TicketLocation::Channel(channel_id),
+            Self::Thread => TicketLocation::Thread(channel_id),
+        }
+    }
+}
+
+impl FromStr for TicketMode {
+    type Err = String;
+
+    fn from_str(mode: &str) -> Result<Self, Self::Err> {
+        match mode {
+            "channel" => Ok(Self::Channel),
+            "thread" => Ok(Self::Thread),
+            _ => Err(format!("Unknown ticket mode: {mode}")),
+        }
+    }
+}
+
+impl fmt::Display for TicketMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The place a ticket lives in on Discord
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TicketLocation {
+    /// A text channel in one of the ticket categories
+    Channel(ChannelId),
+    /// A private thread of the ticket channel
+    Thread(ChannelId),
+}
+
+impl TicketLocation {
+    /// Builds the location from its database representation
+    pub fn from_db(kind: &str, channel_id: i64) -> Result<Self, Error> {
+        Ok(kind
+            .parse::<TicketMode>()?
+            .location(ChannelId::from_db(channel_id)))
+    }
+
+    /// Returns the ID of the channel or thread
+    pub fn id(self) -> ChannelId {
+        match self {
+            Self::Channel(id) | Self::Thread(id) => id,
+        }
+    }
+
+    /// Returns the mode the ticket was created with
+    pub fn mode(self) -> TicketMode {
+        match self {
+            Self::Channel(_) => TicketMode::Channel,
+            Self::Thread(_) => TicketMode::Thread,
+        }
+    }
+
+    /// Gives a user access to the ticket
+    pub async fn add_member(self, http: &Http, user_id: UserId) -> Result<(), Error> {
+        match self {
+            Self::Channel(id) => {
+                let overwrite = PermissionOverwrite {
+                    allow: MEMBER_PERMISSIONS,
+                    deny: Permissions::empty(),
+                    kind: PermissionOverwriteType::Member(user_id),
+                };
+                id.create_permission(http, overwrite).await?;
+            }
+            Self::Thread(id) => id.add_thread_member(http, user_id).await?,
+        }
+
+        Ok(())
+    }
+
+    /// Removes the access of a user to the ticket
+    pub async fn remove_member(self, http: &Http, user_id: UserId) -> Result<(), Error> {
+        match self {
+            Self::Channel(id) => {
+                id.delete_permission(http, PermissionOverwriteType::Member(user_id))
+                    .await?;
+            }
+            Self::Thread(id) => id.remove_thread_member(http, user_id).await?,
+        }
+
+        Ok(())
+    }
+
+    /// Renames the channel or thread of the ticket
+    pub async fn rename(self, http: &Http, name: String) -> Result<(), Error> {
+        match self {
+            Self::Channel(id) => {
+                id.edit(http, EditChannel::new().name(name)).await?;
+            }
+            Self::Thread(id) => {
+                id.edit_thread(http, EditThread::new().name(name)).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Deletes the channel or thread of the ticket
+    pub async fn delete(self, http: &Http) -> Result<(), Error> {
+        self.id().delete(http).await?;
+        Ok(())
+    }
+}
+
+/// Allows the roles to see the private threads of a channel, keeping their other permission
+/// overwrites
+///
+/// Private threads are only visible to their members and to those who can manage threads, so the
+/// staff needs it on every channel the tickets are created in as threads.
+pub async fn allow_thread_management(
+    http: &Http,
+    channel_id: ChannelId,
+    role_ids: &[RoleId],
+) -> Result<(), Error> {
+    let channel = channel_id
+        .to_channel(http)
+        .await?
+        .guild()
+        .ok_or("The ticket channel is not a guild channel")?;
+
+    for &role_id in role_ids {
+        let (allow, deny) = channel
+            .permission_overwrites
+            .iter()
+            .find(|overwrite| overwrite.kind == PermissionOverwriteType::Role(role_id))
+            .map_or((Permissions::empty(), Permissions::empty()), |overwrite| {
+                (overwrite.allow, overwrite.deny)
+            });
+
+        let overwrite = PermissionOverwrite {
+            allow: allow | Permissions::VIEW_CHANNEL | Permissions::MANAGE_THREADS,
+            deny: deny - Permissions::VIEW_CHANNEL - Permissions::MANAGE_THREADS,
+            kind: PermissionOverwriteType::Role(role_id),
+        };
+
+        channel_id.create_permission(http, overwrite).await?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,601 @@
+//! This module handles the office hours, when the helpers staff the queue
+//!
+//! Outside the office hours of its server (narrowed by the ones of its subject), a ticket is
+//! either refused or accepted as pending: it is then handed to the helpers once the office hours
+//! start. The panels show when a closed server opens next. The office hours are read in the time
+//! zone of the server, a region following its daylight saving time or a fixed offset from UTC.
+
+use std::{collections::HashMap, fmt, str::FromStr, sync::Arc, time::Duration};
+
+use chrono::{
+    DateTime, Datelike, Days, FixedOffset, NaiveDateTime, NaiveTime, TimeZone, Utc, Weekday,
+};
+use chrono_tz::Tz;
+use poise::serenity_prelude::{
+    CreateAllowedMentions, CreateMessage, GuildId, Http, Mentionable, RoleId,
+};
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    database::{Connection, Pool, Server, ServerRepo, Storage, Ticket},
+    handler::Error,
+    i18n::{tr, tr_args, Locale},
+    metrics::Metrics,
+};
+
+use super::{
+    assign::{assigned_message, auto_assign},
+    location::TicketLocation,
+    panels::get_panels,
+    priority::sort_category,
+};
+
+/// How often the scheduler checks the office hours
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// The days of the week, as written in the schedules (short and full names)
+const WEEKDAYS: [(&str, &str, Weekday); 7] = [
+    ("mon", "monday", Weekday::Mon),
+    ("tue", "tuesday", Weekday::Tue),
+    ("wed", "wednesday", Weekday::Wed),
+    ("thu", "thursday", Weekday::Thu),
+    ("fri", "friday", Weekday::Fri),
+    ("sat", "saturday", Weekday::Sat),
+    ("sun", "sunday", Weekday::Sun),
+];
+
+/// The hours a schedule is open on one day of the week
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Window {
+    day: Weekday,
+    opens: NaiveTime,
+    closes: NaiveTime,
+}
+
+/// The weekly office hours of a server or a subject, such as `mon-fri 09:00-17:00, sat 10:00-12:00`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Schedule {
+    text: String,
+    windows: Vec<Window>,
+}
+
+impl Schedule {
+    /// Returns the schedule as written, as stored in the database
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    /// Returns whether the schedule is open at the given local time
+    fn is_open(&self, at: NaiveDateTime) -> bool {
+        let time = at.time();
+        self.windows.iter().any(|window| {
+            window.day == at.weekday() && window.opens <= time && time < window.closes
+        })
+    }
+
+    /// Returns the openings of the schedule in the week following the given local time
+    fn openings(&self, from: NaiveDateTime) -> Vec<NaiveDateTime> {
+        let mut openings = Vec::new();
+        for offset in 0..=7 {
+            let Some(date) = from.date().checked_add_days(Days::new(offset)) else {
+                continue;
+            };
+            for window in self
+                .windows
+                .iter()
+                .filter(|window| window.day == date.weekday())
+            {
+                let opening = date.and_time(window.opens);
+                if opening > from {
+                    openings.push(opening);
+                }
+            }
+        }
+        openings
+    }
+}
+
+impl FromStr for Schedule {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let text = text.trim();
+        let mut windows = Vec::new();
+
+        for part in text.split(',') {
+            let (days, hours) = part
+                .trim()
+                .split_once(' ')
+                .ok_or_else(|| format!("Expected days and hours in `{}`", part.trim()))?;
+            let (opens, closes) = parse_hours(hours.trim())?;
+
+            for day in parse_days(days)? {
+                windows.push(Window { day, opens, closes });
+            }
+        }
+
+        Ok(Self {
+            text: text.to_string(),
+            windows,
+        })
+    }
+}
+
+impl fmt::Display for Schedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+/// Parses a day (`mon`) or a range of days (`mon-fri`, `sat-sun`)
+fn parse_days(days: &str) -> Result<Vec<Weekday>, String> {
+    let (first, last) = days.split_once('-').unwrap_or((days, days));
+    let (first, last) = (parse_day(first)?, parse_day(last)?);
+
+    let mut days = vec![first];
+    let mut day = first;
+    while day != last {
+        day = day.succ();
+        days.push(day);
+    }
+
+    Ok(days)
+}
+
+/// Parses the name of a day, short or in full
+fn parse_day(day: &str) -> Result<Weekday, String> {
+    let day = day.trim().to_lowercase();
+    WEEKDAYS
+        .into_iter()
+        .find(|(short, full, _)| day == *short || day == *full)
+        .map(|(_, _, weekday)| weekday)
+        .ok_or_else(|| format!("Unknown day: `{day}`"))
+}
+
+/// Parses the hours of a day (`09:00-17:00`)
+fn parse_hours(hours: &str) -> Result<(NaiveTime, NaiveTime), String> {
+    let parse = |time: &str| {
+        NaiveTime::parse_from_str(time.trim(), "%H:%M")
+            .map_err(|_| format!("Invalid time: `{}`, expected `HH:MM`", time.trim()))
+    };
+
+    let (opens, closes) = hours
+        .split_once('-')
+        .ok_or_else(|| format!("Expected hours such as `09:00-17:00`, got `{hours}`"))?;
+    let (opens, closes) = (parse(opens)?, parse(closes)?);
+
+    if opens >= closes {
+        return Err(format!("The office hours `{hours}` close before they open"));
+    }
+
+    Ok((opens, closes))
+}
+
+/// The time zone the office hours of a server are read in
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ServerTimeZone {
+    /// A region of the time zone database (`Europe/Paris`), following its daylight saving time
+    Region(Tz),
+    /// A fixed offset from UTC (`+02:00`), never changing with the seasons
+    Offset(FixedOffset),
+}
+
+impl ServerTimeZone {
+    /// Returns the local time at the given instant
+    fn local(self, at: DateTime<Utc>) -> NaiveDateTime {
+        match self {
+            Self::Region(tz) => at.with_timezone(&tz).naive_local(),
+            Self::Offset(offset) => at.with_timezone(&offset).naive_local(),
+        }
+    }
+
+    /// Returns the instant of a local time, `None` if it is skipped by a daylight saving change
+    fn to_utc(self, local: NaiveDateTime) -> Option<DateTime<Utc>> {
+        // A local time repeated when the clocks go back is taken the first time
+        match self {
+            Self::Region(tz) => tz
+                .from_local_datetime(&local)
+                .earliest()
+                .map(|at| at.to_utc()),
+            Self::Offset(offset) => offset
+                .from_local_datetime(&local)
+                .earliest()
+                .map(|at| at.to_utc()),
+        }
+    }
+}
+
+impl FromStr for ServerTimeZone {
+    type Err = String;
+
+    /// Parses a region (`Europe/Paris`) or an offset from UTC (`+02:00`, `UTC-5`, `UTC`)
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let text = text.trim();
+        if let Ok(tz) = text.parse::<Tz>() {
+            return Ok(Self::Region(tz));
+        }
+
+        let invalid = || {
+            format!(
+                "Invalid time zone: `{text}`, expected a region such as `Europe/Paris` or an \
+                offset such as `+02:00`"
+            )
+        };
+
+        let offset = ["UTC", "utc", "GMT", "gmt"]
+            .into_iter()
+            .find_map(|prefix| text.strip_prefix(prefix))
+            .unwrap_or(text)
+            .trim();
+        if offset.is_empty() {
+            return FixedOffset::east_opt(0)
+                .map(Self::Offset)
+                .ok_or_else(invalid);
+        }
+
+        let (sign, offset) = if let Some(offset) = offset.strip_prefix('+') {
+            (1, offset)
+        } else if let Some(offset) = offset.strip_prefix('-') {
+            (-1, offset)
+        } else {
+            return Err(invalid());
+        };
+        let (hours, minutes) = offset.split_once(':').unwrap_or((offset, "0"));
+        let hours: i32 = hours.parse().map_err(|_| invalid())?;
+        let minutes: i32 = minutes.parse().map_err(|_| invalid())?;
+        if minutes >= 60 {
+            return Err(invalid());
+        }
+
+        FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+            .map(Self::Offset)
+            .ok_or_else(invalid)
+    }
+}
+
+impl fmt::Display for ServerTimeZone {
+    /// Formats the time zone as stored in the database
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Region(tz) => f.write_str(tz.name()),
+            Self::Offset(offset) => write!(f, "UTC{offset}"),
+        }
+    }
+}
+
+/// What happens to the tickets opened outside office hours
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, poise::ChoiceParameter)]
+pub enum ClosedMode {
+    /// The tickets cannot be opened
+    #[default]
+    #[name = "refuse"]
+    Refuse,
+    /// The tickets are opened, and handed to the helpers when the office hours start
+    #[name = "pending"]
+    Pending,
+}
+
+impl ClosedMode {
+    /// Returns the name of the mode, as stored in the database
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Refuse => "refuse",
+            Self::Pending => "pending",
+        }
+    }
+}
+
+impl FromStr for ClosedMode {
+    type Err = String;
+
+    fn from_str(mode: &str) -> Result<Self, Self::Err> {
+        match mode {
+            "refuse" => Ok(Self::Refuse),
+            "pending" => Ok(Self::Pending),
+            _ => Err(format!("Unknown closed mode: {mode}")),
+        }
+    }
+}
+
+impl fmt::Display for ClosedMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The office hours a ticket has to respect: the ones of its server, narrowed by its subject
+#[derive(Clone, Debug)]
+pub struct OfficeHours {
+    time_zone: ServerTimeZone,
+    schedules: Vec<Schedule>,
+}
+
+impl OfficeHours {
+    /// Returns the office hours of the server
+    pub fn of_server(server: &Server) -> Self {
+        Self {
+            time_zone: server.time_zone,
+            schedules: server.office_hours.iter().cloned().collect(),
+        }
+    }
+
+    /// Narrows the office hours to the ones of a subject
+    pub fn with_subject(mut self, schedule: Option<Schedule>) -> Self {
+        self.schedules.extend(schedule);
+        self
+    }
+
+    /// Returns whether the tickets are staffed at the given time
+    pub fn is_open(&self, now: DateTime<Utc>) -> bool {
+        let now = self.time_zone.local(now);
+        self.schedules.iter().all(|schedule| schedule.is_open(now))
+    }
+
+    /// Returns when the office hours start next, `None` if they never do
+    pub fn next_opening(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let local = self.time_zone.local(now);
+
+        let mut openings: Vec<_> = self
+            .schedules
+            .iter()
+            .flat_map(|schedule| schedule.openings(local))
+            .filter_map(|opening| self.time_zone.to_utc(opening))
+            .collect();
+        openings.sort();
+
+        openings.into_iter().find(|&opening| self.is_open(opening))
+    }
+
+    /// Returns the notice telling when the office hours start next
+    pub fn closed_notice(&self, locale: Locale, now: DateTime<Utc>) -> String {
+        match self.next_opening(now) {
+            Some(opening) => tr_args(
+                locale,
+                "office_hours.closed",
+                &[("opening", &format!("<t:{}:F>", opening.timestamp()))],
+            ),
+            None => tr(locale, "office_hours.closed_indefinitely"),
+        }
+    }
+}
+
+/// Returns the notice shown on the panels of a closed server, `None` when it is open
+pub fn panel_notice(server: &Server, now: DateTime<Utc>) -> Option<String> {
+    let hours = OfficeHours::of_server(server);
+    (!hours.is_open(now)).then(|| hours.closed_notice(server.locale, now))
+}
+
+/// Updates the panels of the server with its current office hours
+pub async fn refresh_panels(
+    http: &Http,
+    pool: &mut Connection,
+    server: &Server,
+    now: DateTime<Utc>,
+) -> Result<(), Error> {
+    let panels = get_panels(pool, server.id).await?;
+    if panels.is_empty() {
+        return Ok(());
+    }
+
+    let guild = server.id.to_partial_guild(http).await?;
+    let notice = panel_notice(server, now);
+    for panel in panels {
+        panel
+            .update_message(http, &guild, notice.as_deref())
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// Watches the office hours: the panels are updated when a server opens or closes, and the pending
+/// tickets are handed to the helpers once their office hours start
+pub struct Scheduler {
+    metrics: Arc<Metrics>,
+    /// Whether each server with office hours was open at the last check
+    open: HashMap<GuildId, bool>,
+}
+
+impl Scheduler {
+    pub fn new(metrics: Arc<Metrics>) -> Self {
+        Self {
+            metrics,
+            open: HashMap::new(),
+        }
+    }
+
+    /// Checks the office hours every minute, until the bot stops
+    pub async fn run(mut self, http: Arc<Http>, pool: Pool, token: CancellationToken) {
+        let mut interval = tokio::time::interval(CHECK_INTERVAL);
+        loop {
+            tokio::select! {
+                _ = token.cancelled() => return,
+                _ = interval.tick() => {}
+            }
+            if let Err(error) = self.check(&http, &pool, Utc::now()).await {
+                error!("Failed to check the office hours: {error}");
+            }
+        }
+    }
+
+    /// Checks the office hours at the given time
+    pub async fn check(
+        &mut self,
+        http: &Http,
+        pool: &Pool,
+        now: DateTime<Utc>,
+    ) -> Result<(), Error> {
+        let mut conn = pool.acquire().await?;
+
+        for server in conn.scheduled_servers().await? {
+            let open = OfficeHours::of_server(&server).is_open(now);
+            if self.open.insert(server.id, open) == Some(open) {
+                continue;
+            }
+
+            if let Err(error) = refresh_panels(http, &mut conn, &server, now).await {
+                warn!(
+                    "Failed to update the panels of server {}: {error}",
+                    server.id
+                );
+            }
+        }
+
+        for ticket in conn.pending_tickets().await? {
+            if let Err(error) = self.release(http, &mut conn, &ticket, now).await {
+                warn!("Failed to release pending ticket {}: {error}", ticket.id);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Hands a pending ticket to the helpers if its office hours started
+    async fn release(
+        &self,
+        http: &Http,
+        pool: &mut Connection,
+        ticket: &Ticket,
+        now: DateTime<Utc>,
+    ) -> Result<(), Error> {
+        let server = ServerRepo::get(pool, ticket.guild_id).await?;
+        let subject_hours = match ticket.subject_id {
+            Some(subject_id) => pool.subject_office_hours(server.id, subject_id).await?,
+            None => None,
+        };
+        if !OfficeHours::of_server(&server)
+            .with_subject(subject_hours)
+            .is_open(now)
+        {
+            return Ok(());
+        }
+
+        pool.release_pending_ticket(ticket.id).await?;
+
+        // A helper may have taken the ticket in the meantime
+        if ticket.claimed_by.is_some() {
+            return Ok(());
+        }
+
+        let location = ticket.location;
+        match auto_assign(http, pool, &server, location, ticket.subject_id).await? {
+            Some(helper_id) => {
+                let message = assigned_message(server.locale, helper_id);
+                location.id().send_message(http, message).await?;
+
+                self.metrics.ticket_claimed(server.id);
+            }
+            None => {
+                if let TicketLocation::Channel(channel_id) = location {
+                    sort_category(http, pool, server.id, channel_id).await?;
+                }
+
+                let message = released_message(server.locale, server.helper_role_id);
+                location.id().send_message(http, message).await?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the message pinging the helpers for a ticket opened outside office hours
+fn released_message(locale: Locale, role_id: RoleId) -> CreateMessage {
+    CreateMessage::new()
+        .content(tr_args(
+            locale,
+            "office_hours.released",
+            &[("role", &role_id.mention().to_string())],
+        ))
+        .allowed_mentions(CreateAllowedMentions::new().roles([role_id]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(text: &str) -> DateTime<Utc> {
+        // 2024-08-05 is a Monday
+        DateTime::parse_from_rfc3339(text).unwrap().to_utc()
+    }
+
+    fn hours(schedule: &str, time_zone: &str) -> OfficeHours {
+        OfficeHours {
+            time_zone: time_zone.parse().unwrap(),
+            schedules: vec![schedule.parse().unwrap()],
+        }
+    }
+
+    #[test]
+    fn test_parse_schedule() {
+        assert!("mon-fri 09:00-17:00, saturday 10:00-12:00"
+            .parse::<Schedule>()
+            .is_ok());
+        assert!("fri-mon 09:00-17:00".parse::<Schedule>().is_ok());
+        assert!("mon 17:00-09:00".parse::<Schedule>().is_err());
+        assert!("someday 09:00-17:00".parse::<Schedule>().is_err());
+        assert!("mon 9h-17h".parse::<Schedule>().is_err());
+    }
+
+    #[test]
+    fn test_parse_time_zone() {
+        let offset = |seconds| ServerTimeZone::Offset(FixedOffset::east_opt(seconds).unwrap());
+
+        assert_eq!("UTC".parse(), Ok(ServerTimeZone::Region(Tz::UTC)));
+        assert_eq!("+02:00".parse(), Ok(offset(7200)));
+        assert_eq!("UTC-5".parse(), Ok(offset(-18000)));
+        assert_eq!(
+            "Europe/Paris".parse(),
+            Ok(ServerTimeZone::Region(Tz::Europe__Paris))
+        );
+        assert!("Mars/Olympus".parse::<ServerTimeZone>().is_err());
+
+        // The time zones are stored as displayed
+        for time_zone in ["UTC+02:00", "UTC-05:00", "Europe/Paris"] {
+            let parsed: ServerTimeZone = time_zone.parse().unwrap();
+            assert_eq!(parsed.to_string(), time_zone);
+        }
+    }
+
+    #[test]
+    fn test_office_hours() {
+        let hours = hours("mon-fri 09:00-17:00", "+02:00");
+
+        assert!(hours.is_open(at("2024-08-05T07:30:00Z")));
+        assert!(!hours.is_open(at("2024-08-05T15:00:00Z")));
+
+        // Friday evening, the next opening is on Monday morning
+        assert_eq!(
+            hours.next_opening(at("2024-08-09T18:00:00Z")),
+            Some(at("2024-08-12T07:00:00Z"))
+        );
+    }
+
+    #[test]
+    fn test_office_hours_follow_daylight_saving_time() {
+        let hours = hours("mon-fri 09:00-17:00", "Europe/Paris");
+
+        // 09:30 in summer (UTC+2), but 08:30 in winter (UTC+1)
+        assert!(hours.is_open(at("2024-08-05T07:30:00Z")));
+        assert!(!hours.is_open(at("2024-01-08T07:30:00Z")));
+
+        // The clocks go forward on the night of Sunday 2024-03-31
+        assert_eq!(
+            hours.next_opening(at("2024-03-29T18:00:00Z")),
+            Some(at("2024-04-01T07:00:00Z"))
+        );
+    }
+
+    #[test]
+    fn test_subject_narrows_office_hours() {
+        let hours = hours("mon-fri 09:00-17:00", "UTC")
+            .with_subject(Some("mon 14:00-20:00".parse().unwrap()));
+
+        assert!(!hours.is_open(at("2024-08-05T10:00:00Z")));
+        assert!(hours.is_open(at("2024-08-05T15:00:00Z")));
+        assert_eq!(
+            hours.next_opening(at("2024-08-05T18:00:00Z")),
+            Some(at("2024-08-12T14:00:00Z"))
+        );
+    }
+}
@@ -0,0 +1,306 @@
+//! This module handles the ticket panels, the messages members use to open a ticket
+//!
+//! A server can have several panels, each with its own text, target category
+//! and set of subjects.
+
+use std::collections::HashSet;
+
+use chrono::Utc;
+use poise::serenity_prelude::{
+    ButtonStyle, CacheHttp, ChannelId, Context, CreateActionRow, CreateButton, CreateEmbed,
+    CreateInteractionResponse, CreateInteractionResponseMessage, CreateMessage, EditMessage,
+    GuildId, Http, Interaction, MessageId, PartialGuild, ReactionType,
+};
+
+use crate::{
+    database::{Connection, ServerRepo, Storage},
+    handler::{Data, Error},
+    helper::embed::Custom,
+    i18n::{tr, Locale},
+};
+
+use super::{
+    create::CreateOptions,
+    location::{allow_thread_management, TicketMode},
+    office_hours::panel_notice,
+    TICKET_EMOJI,
+};
+
+/// Prefix of the custom IDs of the panel buttons
+const PANEL_PREFIX: &str = "panel";
+
+/// A message members use to open a ticket
+#[derive(Clone, Debug)]
+pub struct Panel {
+    pub id: i32,
+    pub guild_id: GuildId,
+    pub name: String,
+    pub channel_id: ChannelId,
+    pub message_id: MessageId,
+    pub title: String,
+    pub description: String,
+    pub button_label: String,
+    /// The category the tickets are created in, the unclaimed categories of the server if `None`
+    pub category_id: Option<ChannelId>,
+    /// The subjects that can be chosen, every subject of the server if empty
+    pub subject_ids: Vec<u64>,
+}
+
+impl Panel {
+    /// Updates the message displaying the panel with its current settings, and the notice of the
+    /// office hours when the server is closed
+    pub async fn update_message(
+        &self,
+        http: &Http,
+        guild: &PartialGuild,
+        closed_notice: Option<&str>,
+    ) -> Result<(), Error> {
+        let message = EditMessage::new()
+            .embed(panel_embed(
+                guild,
+                &self.title,
+                &self.description,
+                closed_notice,
+            ))
+            .components(panel_components(self.id, &self.button_label));
+
+        self.channel_id
+            .edit_message(http, self.message_id, message)
+            .await?;
+
+        Ok(())
+    }
+}
+
+/// Returns the embed displaying a panel
+fn panel_embed(
+    guild: &PartialGuild,
+    title: &str,
+    description: &str,
+    closed_notice: Option<&str>,
+) -> CreateEmbed {
+    let embed = CreateEmbed::default_bot_embed(guild)
+        .title(title)
+        .description(description);
+
+    match closed_notice {
+        Some(notice) => embed.field("🔒", notice, false),
+        None => embed,
+    }
+}
+
+/// Returns the button used to open a ticket from a panel
+fn panel_components(panel_id: i32, button_label: &str) -> Vec<CreateActionRow> {
+    let button = CreateButton::new(format!("{PANEL_PREFIX}:{panel_id}"))
+        .label(button_label)
+        .emoji(ReactionType::Unicode(TICKET_EMOJI.to_string()))
+        .style(ButtonStyle::Primary);
+
+    vec![CreateActionRow::Buttons(vec![button])]
+}
+
+/// The settings of a panel about to be created
+pub struct NewPanel {
+    pub name: String,
+    pub channel_id: ChannelId,
+    pub title: String,
+    pub description: String,
+    pub button_label: String,
+    pub category_id: Option<ChannelId>,
+    pub subject_ids: Vec<u64>,
+}
+
+/// Creates a panel: saves it and sends its message in its channel
+pub async fn create_panel(
+    http: &Http,
+    pool: &mut Connection,
+    guild: &PartialGuild,
+    panel: NewPanel,
+) -> Result<Panel, Error> {
+    let server = ServerRepo::get(pool, guild.id).await?;
+    let closed_notice = panel_notice(&server, Utc::now());
+
+    // The ID of the panel is needed for its button, the message is stored once sent
+    let panel_id = pool.insert_panel(guild.id, &panel).await?;
+
+    let message = CreateMessage::new()
+        .embed(panel_embed(
+            guild,
+            &panel.title,
+            &panel.description,
+            closed_notice.as_deref(),
+        ))
+        .components(panel_components(panel_id, &panel.button_label));
+
+    let sent = match panel.channel_id.send_message(http, message).await {
+        Ok(sent) => sent,
+        Err(error) => {
+            delete_panel(pool, panel_id).await?;
+            return Err(error.into());
+        }
+    };
+
+    pool.set_panel_message(panel_id, sent.id).await?;
+
+    if server.ticket_mode == TicketMode::Thread {
+        let role_ids = [server.helper_role_id, server.moderator_role_id];
+        allow_thread_management(http, panel.channel_id, &role_ids).await?;
+    }
+
+    let panel = Panel {
+        id: panel_id,
+        guild_id: guild.id,
+        name: panel.name,
+        channel_id: panel.channel_id,
+        message_id: sent.id,
+        title: panel.title,
+        description: panel.description,
+        button_label: panel.button_label,
+        category_id: panel.category_id,
+        subject_ids: panel.subject_ids,
+    };
+
+    set_panel_subjects(pool, &panel, &panel.subject_ids).await?;
+
+    Ok(panel)
+}
+
+/// Returns the panel with the given ID
+pub async fn get_panel(pool: &mut Connection, panel_id: i32) -> Result<Option<Panel>, Error> {
+    pool.panel(panel_id).await
+}
+
+/// Returns the panel displayed by the given message
+pub async fn get_panel_by_message(
+    pool: &mut Connection,
+    channel_id: ChannelId,
+    message_id: MessageId,
+) -> Result<Option<Panel>, Error> {
+    match pool.panel_id_by_message(channel_id, message_id).await? {
+        Some(panel_id) => get_panel(pool, panel_id).await,
+        None => Ok(None),
+    }
+}
+
+/// Returns the messages displaying the panels of the server
+pub async fn get_panel_messages(
+    pool: &mut Connection,
+    guild_id: GuildId,
+) -> Result<HashSet<MessageId>, Error> {
+    Ok(pool.panel_messages(guild_id).await?.into_iter().collect())
+}
+
+/// Returns the panel of the server with the given name
+pub async fn get_panel_by_name(
+    pool: &mut Connection,
+    guild_id: GuildId,
+    name: &str,
+) -> Result<Option<Panel>, Error> {
+    match pool.panel_id_by_name(guild_id, name).await? {
+        Some(panel_id) => get_panel(pool, panel_id).await,
+        None => Ok(None),
+    }
+}
+
+/// Returns every panel of the server
+pub async fn get_panels(pool: &mut Connection, guild_id: GuildId) -> Result<Vec<Panel>, Error> {
+    let ids = pool.panel_ids(guild_id).await?;
+
+    let mut panels = Vec::with_capacity(ids.len());
+    for panel_id in ids {
+        if let Some(panel) = get_panel(pool, panel_id).await? {
+            panels.push(panel);
+        }
+    }
+
+    Ok(panels)
+}
+
+/// Saves the settings of a panel (its subjects are saved with [`set_panel_subjects`])
+pub async fn update_panel(pool: &mut Connection, panel: &Panel) -> Result<(), Error> {
+    pool.update_panel(panel).await
+}
+
+/// Deletes a panel, its message is left to the caller
+pub async fn delete_panel(pool: &mut Connection, panel_id: i32) -> Result<(), Error> {
+    pool.delete_panel(panel_id).await
+}
+
+/// Replaces the subjects of a panel
+pub async fn set_panel_subjects(
+    pool: &mut Connection,
+    panel: &Panel,
+    subject_ids: &[u64],
+) -> Result<(), Error> {
+    pool.set_panel_subjects(panel, subject_ids).await
+}
+
+/// Handles the clicks on the panel buttons, ignoring any other interaction
+pub async fn handle_interaction(
+    ctx: &Context,
+    data: &Data,
+    interaction: &Interaction,
+) -> Result<(), Error> {
+    let Interaction::Component(component) = interaction else {
+        return Ok(());
+    };
+
+    let Some(panel_id) = parse_custom_id(&component.data.custom_id) else {
+        return Ok(());
+    };
+
+    let Some(member) = &component.member else {
+        return Ok(());
+    };
+    let locale = Locale::from_discord(&component.locale);
+
+    let mut pool = data.pool.acquire().await?;
+    let Some(panel) = get_panel(&mut pool, panel_id).await? else {
+        return Ok(());
+    };
+    drop(pool);
+
+    component
+        .create_response(
+            ctx.http(),
+            CreateInteractionResponse::Message(
+                CreateInteractionResponseMessage::new()
+                    .content(tr(locale.unwrap_or_default(), "ticket.being_created"))
+                    .ephemeral(true),
+            ),
+        )
+        .await?;
+
+    let options = CreateOptions {
+        panel: Some(panel),
+        locale,
+        ..Default::default()
+    };
+
+    super::create_ticket(ctx, data, member, options).await
+}
+
+/// Parses the custom ID of a panel button (`panel:<id>`)
+fn parse_custom_id(custom_id: &str) -> Option<i32> {
+    custom_id
+        .strip_prefix(PANEL_PREFIX)?
+        .strip_prefix(':')?
+        .parse()
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_panel_custom_id() {
+        assert_eq!(parse_custom_id("panel:3"), Some(3));
+    }
+
+    #[test]
+    fn test_parse_unrelated_custom_id() {
+        assert_eq!(parse_custom_id("feedback:comment:3"), None);
+        assert_eq!(parse_custom_id("panel:"), None);
+    }
+}
@@ -0,0 +1,157 @@
+//! This module handles the participants of a ticket (users added on top of its author)
+
+use poise::serenity_prelude::{CreateMessage, Mentionable, User, UserId};
+
+use crate::{
+    database::{Connection, Storage, Ticket, TicketRepo},
+    handler::{Context, Error},
+    i18n::{get_locale, get_server_locale, tr},
+};
+
+use super::{
+    audit::{log_embed, send_log},
+    staff::get_staff_roles,
+};
+
+/// Adds a user to the ticket of the current channel
+pub async fn add(ctx: &Context<'_>, user: &User) -> Result<(), Error> {
+    let locale = get_locale(*ctx).await;
+    let guild = ctx
+        .guild_id()
+        .ok_or_else(|| Error::user(tr(locale, "participants.guild_only")))?
+        .to_partial_guild(ctx.http())
+        .await?;
+    let mut pool = ctx.data().pool.acquire().await?;
+
+    let ticket = get_managed_ticket(ctx, &mut pool).await?;
+
+    if user.bot {
+        return Err(Error::user(tr(locale, "participants.bot")));
+    }
+
+    if user.id == ticket.author_id {
+        return Err(Error::user(tr(locale, "participants.author")));
+    }
+
+    let inserted = pool
+        .add_participant(ticket.id, user.id, ctx.author().id)
+        .await?;
+
+    if !inserted {
+        return Err(Error::user(tr(locale, "participants.already_added")));
+    }
+
+    // Without the permissions of the channel, the participant could not see the ticket
+    if let Err(error) = ticket.location.add_member(ctx.http(), user.id).await {
+        pool.remove_participant(ticket.id, user.id).await?;
+        return Err(error);
+    }
+
+    let server_locale = get_server_locale(&mut pool, guild.id).await;
+    let embed = log_embed(
+        &guild,
+        server_locale,
+        &tr(server_locale, "participants.added_title"),
+        ticket.id,
+    )
+    .field(
+        tr(server_locale, "participants.participant_field"),
+        user.mention().to_string(),
+        true,
+    )
+    .field(
+        tr(server_locale, "participants.added_by_field"),
+        ctx.author().mention().to_string(),
+        true,
+    );
+    send_log(
+        ctx.http(),
+        &mut pool,
+        guild.id,
+        CreateMessage::new().embed(embed),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Removes a user from the ticket of the current channel
+pub async fn remove(ctx: &Context<'_>, user: &User) -> Result<(), Error> {
+    let locale = get_locale(*ctx).await;
+    let guild = ctx
+        .guild_id()
+        .ok_or_else(|| Error::user(tr(locale, "participants.guild_only")))?
+        .to_partial_guild(ctx.http())
+        .await?;
+    let mut pool = ctx.data().pool.acquire().await?;
+
+    let ticket = get_managed_ticket(ctx, &mut pool).await?;
+
+    let removed = pool.remove_participant(ticket.id, user.id).await?;
+
+    if !removed {
+        return Err(Error::user(tr(locale, "participants.not_participant")));
+    }
+
+    ticket.location.remove_member(ctx.http(), user.id).await?;
+
+    let server_locale = get_server_locale(&mut pool, guild.id).await;
+    let embed = log_embed(
+        &guild,
+        server_locale,
+        &tr(server_locale, "participants.removed_title"),
+        ticket.id,
+    )
+    .field(
+        tr(server_locale, "participants.participant_field"),
+        user.mention().to_string(),
+        true,
+    )
+    .field(
+        tr(server_locale, "participants.removed_by_field"),
+        ctx.author().mention().to_string(),
+        true,
+    );
+    send_log(
+        ctx.http(),
+        &mut pool,
+        guild.id,
+        CreateMessage::new().embed(embed),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Returns the participants of a ticket
+pub async fn get_participants(pool: &mut Connection, ticket_id: i32) -> Result<Vec<UserId>, Error> {
+    pool.participants(ticket_id).await
+}
+
+/// Returns the open ticket of the current channel if the author is allowed to manage its
+/// participants (the author of the ticket, helpers and moderators)
+async fn get_managed_ticket(ctx: &Context<'_>, pool: &mut Connection) -> Result<Ticket, Error> {
+    let locale = get_locale(*ctx).await;
+    let guild_id = ctx
+        .guild_id()
+        .ok_or_else(|| Error::user(tr(locale, "participants.guild_only")))?;
+
+    // A closed ticket is about to be deleted
+    let ticket = TicketRepo::by_channel(pool, ctx.channel_id())
+        .await?
+        .filter(|ticket| ticket.is_open)
+        .ok_or_else(|| Error::user(tr(locale, "close.not_a_ticket")))?;
+
+    if ctx.author().id == ticket.author_id {
+        return Ok(ticket);
+    }
+
+    if get_staff_roles(ctx.http(), pool, guild_id, ctx.author().id)
+        .await?
+        .any()
+    {
+        return Ok(ticket);
+    }
+
+    Err(Error::permission(tr(locale, "participants.not_allowed")))
+}
@@ -0,0 +1,316 @@
+//! This module handles the priority of the tickets
+//!
+//! The priority is shown by the prefix of the ticket channel and the colour of its greeting. It
+//! orders the channels of the unclaimed category, and only the tickets with a high priority ping
+//! the helpers.
+
+use std::{cmp::Reverse, collections::HashMap, fmt, str::FromStr};
+
+use poise::serenity_prelude::{
+    ChannelId, Colour, CreateAllowedMentions, CreateEmbed, CreateMessage, CreateSelectMenu,
+    CreateSelectMenuKind, CreateSelectMenuOption, EditMessage, GuildChannel, GuildId, Http,
+    Mentionable, Message, RoleId,
+};
+
+use crate::{
+    database::{Connection, ServerRepo, TicketRepo},
+    handler::{Context, Error},
+    helper::embed::DEFAULT_COLOR,
+    i18n::{get_locale, tr, tr_args, Locale},
+};
+
+use super::{location::TicketLocation, staff::get_staff_roles};
+
+/// Custom ID of the select menu the author chooses the priority with
+pub(super) const PRIORITY_SELECT: &str = "priority_select";
+
+/// How urgent a ticket is
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, poise::ChoiceParameter,
+)]
+pub enum Priority {
+    #[name = "low"]
+    Low,
+    #[default]
+    #[name = "normal"]
+    Normal,
+    #[name = "high"]
+    High,
+}
+
+impl Priority {
+    /// Every priority, the least urgent first
+    pub const ALL: [Self; 3] = [Self::Low, Self::Normal, Self::High];
+
+    /// Returns the name of the priority, as stored in the database
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Low => "low",
+            Self::Normal => "normal",
+            Self::High => "high",
+        }
+    }
+
+    /// Returns the prefix of the ticket channels with this priority
+    pub fn channel_prefix(self) -> &'static str {
+        match self {
+            Self::Low => "⚪",
+            Self::Normal => "",
+            Self::High => "🔴",
+        }
+    }
+
+    /// Returns the colour of the greeting of the tickets with this priority
+    pub fn colour(self) -> Colour {
+        match self {
+            Self::Low => Colour::LIGHT_GREY,
+            Self::Normal => Colour::new(DEFAULT_COLOR),
+            Self::High => Colour::RED,
+        }
+    }
+
+    /// Returns whether the helpers are pinged for the tickets with this priority
+    pub fn pings_helpers(self) -> bool {
+        self == Self::High
+    }
+
+    /// Returns the name of a ticket channel with this priority, from its current name
+    pub fn channel_name(self, name: &str) -> String {
+        let name = Self::ALL
+            .into_iter()
+            .filter(|priority| !priority.channel_prefix().is_empty())
+            .find_map(|priority| name.strip_prefix(priority.channel_prefix()))
+            .unwrap_or(name);
+
+        format!("{}{name}", self.channel_prefix())
+    }
+
+    /// Returns the translated name of the priority
+    pub fn label(self, locale: Locale) -> String {
+        tr(locale, &format!("priority.{}", self.as_str()))
+    }
+}
+
+impl FromStr for Priority {
+    type Err = String;
+
+    fn from_str(priority: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|known| known.as_str() == priority)
+            .ok_or_else(|| format!("Unknown priority: {priority}"))
+    }
+}
+
+impl fmt::Display for Priority {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Returns the select menu the author of a ticket chooses its priority with
+pub(super) fn priority_select_menu(locale: Locale) -> CreateSelectMenu {
+    let options = Priority::ALL
+        .into_iter()
+        .rev()
+        .map(|priority| {
+            CreateSelectMenuOption::new(priority.label(locale), priority.as_str())
+                .default_selection(priority == Priority::default())
+        })
+        .collect();
+
+    CreateSelectMenu::new(PRIORITY_SELECT, CreateSelectMenuKind::String { options })
+        .placeholder(tr(locale, "priority.select_placeholder"))
+}
+
+/// Changes the priority of the ticket of the current channel
+pub async fn set_priority(ctx: &Context<'_>, priority: Priority) -> Result<(), Error> {
+    let locale = get_locale(*ctx).await;
+    let guild_id = ctx
+        .guild_id()
+        .ok_or_else(|| Error::user(tr(locale, "priority.guild_only")))?;
+    let mut pool = ctx.data().pool.acquire().await?;
+
+    if !get_staff_roles(ctx.http(), &mut pool, guild_id, ctx.author().id)
+        .await?
+        .any()
+    {
+        return Err(Error::permission(tr(locale, "priority.not_allowed")));
+    }
+
+    let ticket = TicketRepo::by_channel(&mut pool, ctx.channel_id())
+        .await?
+        .filter(|ticket| ticket.is_open)
+        .ok_or_else(|| Error::user(tr(locale, "priority.not_open_ticket")))?;
+
+    if ticket.priority == priority {
+        return Ok(());
+    }
+
+    TicketRepo::set_priority(&mut pool, ticket.id, priority).await?;
+
+    let channel = ticket.location.id().to_channel(ctx.http()).await?;
+    if let Some(channel) = channel.guild() {
+        ticket
+            .location
+            .rename(ctx.http(), priority.channel_name(&channel.name))
+            .await?;
+    }
+
+    if let Some(message_id) = ticket.greeting_message_id {
+        let mut greeting = ticket.location.id().message(ctx.http(), message_id).await?;
+        recolour_greeting(ctx.http(), &mut greeting, priority).await?;
+    }
+
+    // Claimed tickets are neither sorted nor announced again
+    if ticket.claimed_by.is_none() {
+        announce_priority(ctx.http(), &mut pool, guild_id, ticket.location, priority).await;
+    }
+
+    Ok(())
+}
+
+/// Shows the priority of a new (or unclaimed) ticket to the staff: the channel is sorted in its
+/// category and the helpers are pinged when it is urgent
+///
+/// The ticket is already usable, so failures are only logged.
+pub(super) async fn announce_priority(
+    http: &Http,
+    pool: &mut Connection,
+    guild_id: GuildId,
+    location: TicketLocation,
+    priority: Priority,
+) {
+    if let TicketLocation::Channel(channel_id) = location {
+        if let Err(error) = sort_category(http, pool, guild_id, channel_id).await {
+            warn!("Failed to sort the category of ticket {channel_id}: {error}");
+        }
+    }
+
+    if priority.pings_helpers() {
+        if let Err(error) = ping_helpers(http, pool, guild_id, location.id()).await {
+            warn!(
+                "Failed to ping the helpers in ticket {}: {error}",
+                location.id()
+            );
+        }
+    }
+}
+
+/// Changes the colour of the greeting embed of a ticket to the one of its priority
+pub(super) async fn recolour_greeting(
+    http: &Http,
+    greeting: &mut Message,
+    priority: Priority,
+) -> Result<(), Error> {
+    let Some(embed) = greeting.embeds.first().cloned() else {
+        return Ok(());
+    };
+
+    let embed = CreateEmbed::from(embed).colour(priority.colour());
+    greeting.edit(http, EditMessage::new().embed(embed)).await?;
+
+    Ok(())
+}
+
+/// Orders the ticket channels of the category of the given channel: the most urgent first, then
+/// the oldest first
+pub(super) async fn sort_category(
+    http: &Http,
+    pool: &mut Connection,
+    guild_id: GuildId,
+    channel_id: ChannelId,
+) -> Result<(), Error> {
+    let channels = guild_id.channels(http).await?;
+    let Some(category) = channels
+        .get(&channel_id)
+        .and_then(|channel| channel.parent_id)
+    else {
+        return Ok(());
+    };
+
+    // The tickets are listed the oldest first, the other channels are kept after them
+    let tickets: HashMap<ChannelId, (Priority, usize)> =
+        TicketRepo::open_priorities(pool, guild_id)
+            .await?
+            .into_iter()
+            .enumerate()
+            .map(|(age, (channel_id, priority))| (channel_id, (priority, age)))
+            .collect();
+
+    let mut current: Vec<&GuildChannel> = channels
+        .values()
+        .filter(|channel| channel.parent_id == Some(category))
+        .collect();
+    current.sort_by_key(|channel| (channel.position, channel.id));
+
+    let mut sorted = current.clone();
+    sorted.sort_by_key(|channel| {
+        let (priority, age) = tickets
+            .get(&channel.id)
+            .copied()
+            .unwrap_or((Priority::default(), usize::MAX));
+        (Reverse(priority), age, channel.id)
+    });
+
+    if sorted
+        .iter()
+        .map(|channel| channel.id)
+        .eq(current.iter().map(|channel| channel.id))
+    {
+        return Ok(());
+    }
+
+    let positions = sorted
+        .iter()
+        .enumerate()
+        .map(|(position, channel)| (channel.id, position as u64));
+    guild_id.reorder_channels(http, positions).await?;
+
+    Ok(())
+}
+
+/// Pings the helper role in the ticket
+async fn ping_helpers(
+    http: &Http,
+    pool: &mut Connection,
+    guild_id: GuildId,
+    channel_id: ChannelId,
+) -> Result<(), Error> {
+    let server = ServerRepo::get(pool, guild_id).await?;
+
+    let message = helper_ping(server.locale, server.helper_role_id);
+    channel_id.send_message(http, message).await?;
+
+    Ok(())
+}
+
+/// Returns the message pinging the helper role for an urgent ticket
+fn helper_ping(locale: Locale, role_id: RoleId) -> CreateMessage {
+    CreateMessage::new()
+        .content(tr_args(
+            locale,
+            "priority.helper_ping",
+            &[("role", &role_id.mention().to_string())],
+        ))
+        .allowed_mentions(CreateAllowedMentions::new().roles([role_id]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_channel_name() {
+        assert_eq!(Priority::High.channel_name("🎫-Maths"), "🔴🎫-Maths");
+        assert_eq!(Priority::Low.channel_name("🔴🎫-Maths"), "⚪🎫-Maths");
+        assert_eq!(Priority::Normal.channel_name("⚪🎫-Maths"), "🎫-Maths");
+    }
+
+    #[test]
+    fn test_priority_order() {
+        assert!(Priority::High > Priority::Normal);
+        assert!(Priority::Normal > Priority::Low);
+        assert_eq!("high".parse(), Ok(Priority::High));
+    }
+}
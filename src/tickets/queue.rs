@@ -0,0 +1,76 @@
+//! This module handles the queue of the unclaimed tickets, as seen by the staff
+
+use std::time::Duration;
+
+use crate::{
+    database::{QueuedTicket, TicketRepo},
+    handler::{Context, Error},
+    i18n::{get_locale, tr, tr_args, Locale},
+};
+
+use super::staff::get_staff_roles;
+
+/// Returns the unclaimed tickets of the server, in the order helpers should take them
+pub async fn queue(ctx: &Context<'_>) -> Result<Vec<QueuedTicket>, Error> {
+    let locale = get_locale(*ctx).await;
+    let guild_id = ctx
+        .guild_id()
+        .ok_or_else(|| Error::user(tr(locale, "queue.guild_only")))?;
+    let mut pool = ctx.data().pool.acquire().await?;
+
+    if !get_staff_roles(ctx.http(), &mut pool, guild_id, ctx.author().id)
+        .await?
+        .any()
+    {
+        return Err(Error::permission(tr(locale, "queue.not_allowed")));
+    }
+
+    TicketRepo::queue(&mut pool, guild_id).await
+}
+
+/// Formats the time a ticket has been waiting, to the minute
+pub fn format_wait(waiting: Duration, locale: Locale) -> String {
+    let minutes = waiting.as_secs() / 60;
+
+    match (minutes / 60, minutes % 60) {
+        (0, 0) => tr(locale, "queue.wait_under_minute"),
+        (0, minutes) => tr_args(
+            locale,
+            "queue.wait_minutes",
+            &[("minutes", &minutes.to_string())],
+        ),
+        (hours, minutes) => tr_args(
+            locale,
+            "queue.wait_hours",
+            &[
+                ("hours", &hours.to_string()),
+                ("minutes", &format!("{minutes:02}")),
+            ],
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_wait() {
+        assert_eq!(
+            format_wait(Duration::from_secs(42), Locale::En),
+            "less than a minute"
+        );
+        assert_eq!(
+            format_wait(Duration::from_secs(12 * 60 + 5), Locale::En),
+            "12 min"
+        );
+        assert_eq!(
+            format_wait(Duration::from_secs(3 * 3600 + 5 * 60), Locale::En),
+            "3 h 05 min"
+        );
+        assert_eq!(
+            format_wait(Duration::from_secs(42), Locale::Fr),
+            "moins d'une minute"
+        );
+    }
+}
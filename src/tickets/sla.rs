@@ -0,0 +1,178 @@
+//! This module handles the service level reminders of the tickets
+//!
+//! A ticket left unclaimed pings the helpers, then escalates to the moderators. In a claimed
+//! ticket, an author waiting for an answer reminds the helper who claimed it. The thresholds are
+//! chosen per server, and each reminder is sent once (once per unanswered message for the helper).
+
+use std::{sync::Arc, time::Duration};
+
+use poise::serenity_prelude::{
+    CreateAllowedMentions, CreateMessage, Http, Mentionable, RoleId, UserId,
+};
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    database::{Connection, Pool, Server, ServerRepo, Storage, Ticket},
+    handler::Error,
+    i18n::{tr_args, Locale},
+};
+
+use super::queue::format_wait;
+
+/// How often the overdue tickets are looked for
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// The reminders sent for the overdue tickets
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Reminder {
+    /// The helpers are pinged in a ticket left unclaimed
+    Unclaimed,
+    /// The moderators are pinged in a ticket still unclaimed
+    Escalation,
+    /// The helper who claimed a ticket is reminded that its author waits for an answer
+    Unanswered,
+}
+
+impl Reminder {
+    /// Every reminder, in the order they are sent
+    pub const ALL: [Self; 3] = [Self::Unclaimed, Self::Escalation, Self::Unanswered];
+}
+
+/// The delays after which the reminders of a server are sent, disabled when `None`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SlaThresholds {
+    /// How long a ticket can stay unclaimed before the helpers are pinged
+    pub unclaimed: Option<Duration>,
+    /// How long a ticket can stay unclaimed before the moderators are pinged
+    pub escalation: Option<Duration>,
+    /// How long the author of a claimed ticket can wait for an answer of the staff
+    pub reply: Option<Duration>,
+}
+
+impl SlaThresholds {
+    /// Returns the threshold of a reminder
+    pub fn get(&self, reminder: Reminder) -> Option<Duration> {
+        match reminder {
+            Reminder::Unclaimed => self.unclaimed,
+            Reminder::Escalation => self.escalation,
+            Reminder::Unanswered => self.reply,
+        }
+    }
+}
+
+/// Converts a threshold stored in minutes
+pub(crate) fn from_minutes(minutes: Option<i64>) -> Option<Duration> {
+    minutes.map(|minutes| Duration::from_secs(minutes.max(0) as u64 * 60))
+}
+
+/// Converts a threshold to the minutes it is stored in
+pub(crate) fn to_minutes(threshold: Option<Duration>) -> Option<i64> {
+    threshold.map(|threshold| (threshold.as_secs() / 60) as i64)
+}
+
+/// Sends the reminders of the overdue tickets every minute, until the bot stops
+pub async fn run(http: Arc<Http>, pool: Pool, token: CancellationToken) {
+    let mut interval = tokio::time::interval(CHECK_INTERVAL);
+    loop {
+        tokio::select! {
+            _ = token.cancelled() => return,
+            _ = interval.tick() => {}
+        }
+        if let Err(error) = check(&http, &pool).await {
+            error!("Failed to check the overdue tickets: {error}");
+        }
+    }
+}
+
+/// Sends the reminders of the tickets overdue now
+pub async fn check(http: &Http, pool: &Pool) -> Result<(), Error> {
+    let mut conn = pool.acquire().await?;
+
+    for reminder in Reminder::ALL {
+        for ticket in conn.overdue_tickets(reminder).await? {
+            if let Err(error) = remind(http, &mut conn, &ticket, reminder).await {
+                warn!(
+                    "Failed to send the {reminder:?} reminder of ticket {}: {error}",
+                    ticket.id
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Sends a reminder in the ticket
+async fn remind(
+    http: &Http,
+    pool: &mut Connection,
+    ticket: &Ticket,
+    reminder: Reminder,
+) -> Result<(), Error> {
+    // Recorded first, so that a failing ticket is not pinged again every minute
+    pool.record_reminder(ticket.id, reminder).await?;
+
+    let server = ServerRepo::get(pool, ticket.guild_id).await?;
+    let Some(message) = reminder_message(&server, ticket, reminder) else {
+        return Ok(());
+    };
+
+    ticket.location.id().send_message(http, message).await?;
+
+    Ok(())
+}
+
+/// Returns the message of a reminder, `None` if it is disabled or has nobody to ping
+fn reminder_message(server: &Server, ticket: &Ticket, reminder: Reminder) -> Option<CreateMessage> {
+    let wait = format_wait(server.sla.get(reminder)?, server.locale);
+
+    let message = match reminder {
+        Reminder::Unclaimed => {
+            role_ping(server.locale, "sla.unclaimed", server.helper_role_id, &wait)
+        }
+        Reminder::Escalation => role_ping(
+            server.locale,
+            "sla.escalation",
+            server.moderator_role_id,
+            &wait,
+        ),
+        Reminder::Unanswered => helper_ping(server.locale, ticket.claimed_by?, &wait),
+    };
+
+    Some(message)
+}
+
+/// Returns a reminder pinging a role
+fn role_ping(locale: Locale, key: &str, role_id: RoleId, wait: &str) -> CreateMessage {
+    CreateMessage::new()
+        .content(tr_args(
+            locale,
+            key,
+            &[("role", &role_id.mention().to_string()), ("wait", wait)],
+        ))
+        .allowed_mentions(CreateAllowedMentions::new().roles([role_id]))
+}
+
+/// Returns the reminder pinging the helper who claimed the ticket
+fn helper_ping(locale: Locale, helper_id: UserId, wait: &str) -> CreateMessage {
+    CreateMessage::new()
+        .content(tr_args(
+            locale,
+            "sla.unanswered",
+            &[("helper", &helper_id.mention().to_string()), ("wait", wait)],
+        ))
+        .allowed_mentions(CreateAllowedMentions::new().users([helper_id]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_threshold_minutes() {
+        let threshold = from_minutes(Some(90));
+        assert_eq!(threshold, Some(Duration::from_secs(90 * 60)));
+        assert_eq!(to_minutes(threshold), Some(90));
+        assert_eq!(to_minutes(from_minutes(None)), None);
+    }
+}
@@ -0,0 +1,66 @@
+//! This module handles the staff roles of a server (helpers and moderators)
+
+use poise::serenity_prelude::{GuildId, Http, RoleId, UserId};
+
+use crate::{
+    database::{Connection, Server, ServerRepo, Storage},
+    handler::Error,
+};
+
+/// The staff roles held by a member
+#[derive(Clone, Copy, Debug, Default)]
+pub struct StaffRoles {
+    pub helper: bool,
+    pub moderator: bool,
+}
+
+impl StaffRoles {
+    /// Returns whether the member holds any staff role
+    pub fn any(self) -> bool {
+        self.helper || self.moderator
+    }
+
+    /// Returns the staff roles of the server among the roles of a member
+    pub fn from_roles(server: &Server, roles: &[RoleId]) -> Self {
+        Self {
+            helper: roles.contains(&server.helper_role_id),
+            moderator: roles.contains(&server.moderator_role_id),
+        }
+    }
+}
+
+/// Returns the staff roles held by a member of the server
+pub async fn get_staff_roles(
+    http: &Http,
+    pool: &mut Connection,
+    guild_id: GuildId,
+    user_id: UserId,
+) -> Result<StaffRoles, Error> {
+    let server = ServerRepo::get(pool, guild_id).await?;
+
+    let member = guild_id.member(http, user_id).await?;
+
+    Ok(StaffRoles::from_roles(&server, &member.roles))
+}
+
+/// Returns whether a member with the given roles is part of the staff of a ticket: a helper, a
+/// moderator, or a holder of the helper role of its subject
+pub async fn is_ticket_staff(
+    pool: &mut Connection,
+    server: &Server,
+    subject_id: Option<u64>,
+    roles: &[RoleId],
+) -> Result<bool, Error> {
+    if StaffRoles::from_roles(server, roles).any() {
+        return Ok(true);
+    }
+
+    let Some(subject_id) = subject_id else {
+        return Ok(false);
+    };
+
+    Ok(pool
+        .subject_helper_role(server.id, subject_id)
+        .await?
+        .is_some_and(|role_id| roles.contains(&role_id)))
+}
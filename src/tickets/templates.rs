@@ -0,0 +1,123 @@
+//! This module handles the message templates a server can use to customize the bot messages
+//!
+//! Templates contain placeholders (such as `{user}`) replaced when the message is sent,
+//! the default text is used when a server did not set a template.
+
+use poise::serenity_prelude::GuildId;
+
+use crate::{
+    database::{Connection, Storage},
+    handler::Error,
+    helper::template::render,
+    i18n::{tr, Locale},
+};
+
+/// Maximum length of a template (the length of an embed field)
+pub const MAX_TEMPLATE_LENGTH: usize = 1024;
+
+/// The messages that can be customized
+#[derive(Clone, Copy, Debug, PartialEq, Eq, poise::ChoiceParameter)]
+pub enum TemplateKind {
+    /// Default description of the new panels
+    #[name = "panel"]
+    Panel,
+    /// Instructions sent in DM to the author when a ticket is opened
+    #[name = "open_dm"]
+    OpenDm,
+    /// Greeting sent in the ticket when it is opened
+    #[name = "greeting"]
+    Greeting,
+    /// Reason sent in DM when a ticket is closed
+    #[name = "close"]
+    Close,
+}
+
+impl TemplateKind {
+    /// Every kind of template
+    pub const ALL: [Self; 4] = [Self::Panel, Self::OpenDm, Self::Greeting, Self::Close];
+
+    /// Returns the name of the kind, as stored in the database
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Panel => "panel",
+            Self::OpenDm => "open_dm",
+            Self::Greeting => "greeting",
+            Self::Close => "close",
+        }
+    }
+
+    /// Returns the text used when the server did not set a template
+    pub fn default_text(self, locale: Locale) -> String {
+        tr(locale, &format!("templates.{}", self.as_str()))
+    }
+
+    /// Returns the placeholders available in the template
+    pub fn placeholders(self) -> &'static [&'static str] {
+        match self {
+            Self::Panel => &["server"],
+            Self::OpenDm | Self::Greeting => &["user", "channel", "server"],
+            Self::Close => &["user", "ticket_id", "subject", "reason", "server"],
+        }
+    }
+
+    /// Returns example values of the placeholders, used to preview the template
+    pub fn sample_values(self) -> Vec<(&'static str, String)> {
+        let samples = [
+            ("user", "@member"),
+            ("channel", "#ticket"),
+            ("server", "My Server"),
+            ("ticket_id", "42"),
+            ("subject", "Billing"),
+            ("reason", "Issue resolved"),
+        ];
+
+        samples
+            .into_iter()
+            .filter(|(name, _)| self.placeholders().contains(name))
+            .map(|(name, value)| (name, value.to_string()))
+            .collect()
+    }
+}
+
+/// Returns the template set by the server, if any
+pub async fn get_custom_template(
+    pool: &mut Connection,
+    guild_id: GuildId,
+    kind: TemplateKind,
+) -> Result<Option<String>, Error> {
+    pool.template(guild_id, kind).await
+}
+
+/// Renders the template of the server, or the default text when no template is set
+pub async fn render_template(
+    pool: &mut Connection,
+    guild_id: GuildId,
+    kind: TemplateKind,
+    locale: Locale,
+    values: &[(&str, &str)],
+) -> Result<String, Error> {
+    let template = get_custom_template(pool, guild_id, kind)
+        .await?
+        .unwrap_or_else(|| kind.default_text(locale));
+
+    Ok(render(&template, values))
+}
+
+/// Sets the template of the server
+pub async fn set_template(
+    pool: &mut Connection,
+    guild_id: GuildId,
+    kind: TemplateKind,
+    content: &str,
+) -> Result<(), Error> {
+    pool.set_template(guild_id, kind, content).await
+}
+
+/// Removes the template of the server, the default text is used again
+pub async fn reset_template(
+    pool: &mut Connection,
+    guild_id: GuildId,
+    kind: TemplateKind,
+) -> Result<(), Error> {
+    pool.delete_template(guild_id, kind).await
+}
@@ -0,0 +1,105 @@
+//! This module handles the transcripts of the tickets
+
+use std::fmt::Write;
+
+use poise::serenity_prelude::{ChannelId, CreateAttachment, GetMessages, Http, Message, UserId};
+
+use crate::handler::Error;
+
+/// Maximum number of messages fetched per request (Discord limit)
+const MESSAGES_PER_REQUEST: u8 = 100;
+
+/// Information written at the top of a transcript
+pub struct TranscriptHeader {
+    pub ticket_id: i32,
+    pub author: UserId,
+    pub subject: Option<String>,
+    pub participants: Vec<UserId>,
+}
+
+/// Builds the transcript of a ticket channel as a text attachment
+pub async fn build(
+    http: &Http,
+    channel_id: ChannelId,
+    header: &TranscriptHeader,
+) -> Result<CreateAttachment, Error> {
+    let messages = fetch_all_messages(http, channel_id).await?;
+
+    let mut transcript = String::new();
+    writeln!(transcript, "Ticket #{}", header.ticket_id)?;
+    writeln!(transcript, "Author: {}", header.author)?;
+    if let Some(subject) = &header.subject {
+        writeln!(transcript, "Subject: {subject}")?;
+    }
+    if !header.participants.is_empty() {
+        let participants = header
+            .participants
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<String>>()
+            .join(", ");
+        writeln!(transcript, "Participants: {participants}")?;
+    }
+    writeln!(transcript)?;
+
+    for message in &messages {
+        write_message(&mut transcript, message)?;
+    }
+
+    Ok(CreateAttachment::bytes(
+        transcript,
+        format!("transcript-{}.txt", header.ticket_id),
+    ))
+}
+
+/// Fetches every message of the channel, from the oldest to the newest
+async fn fetch_all_messages(http: &Http, channel_id: ChannelId) -> Result<Vec<Message>, Error> {
+    let mut messages = Vec::new();
+    let mut before = None;
+
+    loop {
+        let mut builder = GetMessages::new().limit(MESSAGES_PER_REQUEST);
+        if let Some(before) = before {
+            builder = builder.before(before);
+        }
+
+        let batch = channel_id.messages(http, builder).await?;
+        let Some(oldest) = batch.last() else {
+            break;
+        };
+        before = Some(oldest.id);
+
+        let is_last_batch = batch.len() < usize::from(MESSAGES_PER_REQUEST);
+        messages.extend(batch);
+        if is_last_batch {
+            break;
+        }
+    }
+
+    messages.reverse();
+    Ok(messages)
+}
+
+/// Writes a single message line (and its attachments) to the transcript
+fn write_message(transcript: &mut String, message: &Message) -> std::fmt::Result {
+    writeln!(
+        transcript,
+        "[{}] {} ({}): {}",
+        message.timestamp.format("%d/%m/%Y %H:%M:%S"),
+        message.author.name,
+        message.author.id,
+        message.content
+    )?;
+
+    for embed in &message.embeds {
+        if let Some(title) = &embed.title {
+            writeln!(transcript, "    [Embed] {title}")?;
+        }
+    }
+
+    for attachment in &message.attachments {
+        writeln!(transcript, "    [Attachment] {}", attachment.url)?;
+    }
+
+    Ok(())
+}
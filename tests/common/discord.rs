@@ -0,0 +1,645 @@
+//! A local fake of the Discord REST and gateway APIs
+//!
+//! The REST API keeps a small in-memory guild (channels, roles, members and messages) so that
+//! the bot sees the effects of its own requests, and records every request for the assertions.
+//! The gateway API accepts a single shard and lets the tests dispatch events to it.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+use axum::{
+    body::Bytes,
+    extract::{
+        ws::{Message as WsMessage, WebSocket},
+        Query, State, WebSocketUpgrade,
+    },
+    http::{HeaderMap, Method, StatusCode, Uri},
+    response::{IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use futures_util::{SinkExt, StreamExt};
+use poise::serenity_prelude::{
+    ChannelId, ChannelType, GuildChannel, Member, Message, Permissions, PrivateChannel, Role,
+};
+use serde_json::{json, Value};
+use tokio::sync::{mpsc, Notify};
+
+use super::models;
+
+/// ID of the guild the bot is tested in
+pub const GUILD_ID: u64 = 1000;
+/// ID of the bot user (and of its application)
+pub const BOT_ID: u64 = 1;
+/// ID of the owner of the guild, who has every permission
+pub const OWNER_ID: u64 = 2;
+/// ID of a member without any role
+pub const MEMBER_ID: u64 = 3;
+/// ID of a member with the helper role
+pub const HELPER_ID: u64 = 4;
+/// ID of the channel the commands are sent in
+pub const GENERAL_CHANNEL_ID: u64 = 100;
+/// ID of the channel the panels are sent in
+pub const TICKET_CHANNEL_ID: u64 = 101;
+/// ID of the channel the logs are sent in
+pub const LOG_CHANNEL_ID: u64 = 102;
+/// ID of the category of the unclaimed tickets
+pub const UNCLAIMED_CATEGORY_ID: u64 = 110;
+/// ID of the category of the claimed tickets
+pub const CLAIMED_CATEGORY_ID: u64 = 111;
+/// ID of the helper role
+pub const HELPER_ROLE_ID: u64 = 200;
+/// ID of the moderator role
+pub const MODERATOR_ROLE_ID: u64 = 201;
+/// ID of the role of the bot, with every permission
+pub const BOT_ROLE_ID: u64 = 202;
+
+/// How long the tests wait for the bot before failing
+const WAIT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A request made by the bot to the REST API
+#[derive(Clone, Debug)]
+pub struct Request {
+    pub method: Method,
+    /// The path of the request, without the `/api/v10` prefix
+    pub path: String,
+    /// The JSON body of the request, `Null` if there is none
+    pub body: Value,
+}
+
+impl Request {
+    /// Returns whether the request matches the method and the path
+    pub fn is(&self, method: Method, path: &str) -> bool {
+        self.method == method && self.path == path
+    }
+
+    /// Returns the text of a sent message: its content, or the description of its first embed
+    pub fn text(&self) -> String {
+        let text = |value: &Value| value.as_str().map(str::to_string);
+        text(&self.body["content"])
+            .filter(|content| !content.is_empty())
+            .or_else(|| text(&self.body["embeds"][0]["description"]))
+            .or_else(|| text(&self.body["data"]["content"]))
+            .unwrap_or_default()
+    }
+}
+
+/// The fake Discord API, running until it is dropped
+pub struct FakeDiscord {
+    /// The base URL of the fake, to be used as the proxy of the HTTP client
+    pub url: String,
+    state: Arc<FakeState>,
+    /// Index of the first request not yet returned by [`Self::wait_for`]
+    cursor: Mutex<usize>,
+    server: tokio::task::JoinHandle<()>,
+}
+
+struct FakeState {
+    url: String,
+    world: Mutex<World>,
+    requests: Mutex<Vec<Request>>,
+    request_made: Notify,
+    gateway: Mutex<Option<mpsc::UnboundedSender<WsMessage>>>,
+    sequence: AtomicU64,
+}
+
+/// The guild as seen by the fake API
+struct World {
+    next_id: u64,
+    channels: BTreeMap<u64, GuildChannel>,
+    roles: BTreeMap<u64, Role>,
+    members: BTreeMap<u64, Member>,
+    messages: BTreeMap<u64, Message>,
+}
+
+impl World {
+    fn new() -> Self {
+        let mut world = Self {
+            next_id: 10_000,
+            channels: BTreeMap::new(),
+            roles: BTreeMap::new(),
+            members: BTreeMap::new(),
+            messages: BTreeMap::new(),
+        };
+
+        for (id, name, kind) in [
+            (GENERAL_CHANNEL_ID, "general", ChannelType::Text),
+            (TICKET_CHANNEL_ID, "tickets", ChannelType::Text),
+            (LOG_CHANNEL_ID, "logs", ChannelType::Text),
+        ] {
+            world
+                .channels
+                .insert(id, models::channel(id, name, kind, None));
+        }
+
+        for (id, name) in [
+            (GUILD_ID, "@everyone"),
+            (HELPER_ROLE_ID, "Helper"),
+            (MODERATOR_ROLE_ID, "Moderator"),
+            (BOT_ROLE_ID, "Bot"),
+        ] {
+            world.roles.insert(id, models::role(id, name));
+        }
+        // The checks of the permissions of the bot always pass
+        world.roles.get_mut(&BOT_ROLE_ID).unwrap().permissions = Permissions::ADMINISTRATOR;
+
+        for (id, name, roles) in [
+            (BOT_ID, "bot", vec![BOT_ROLE_ID]),
+            (OWNER_ID, "owner", vec![]),
+            (MEMBER_ID, "member", vec![]),
+            (HELPER_ID, "helper", vec![HELPER_ROLE_ID]),
+        ] {
+            world.members.insert(id, models::member(id, name, &roles));
+        }
+
+        world
+    }
+
+    fn next_id(&mut self) -> u64 {
+        self.next_id += 1;
+        self.next_id
+    }
+}
+
+impl FakeDiscord {
+    /// Starts the fake API on a random local port
+    pub async fn start() -> Self {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("Failed to bind the fake Discord API");
+        let url = format!("http://{}", listener.local_addr().unwrap());
+
+        let state = Arc::new(FakeState {
+            url: url.clone(),
+            world: Mutex::new(World::new()),
+            requests: Mutex::new(Vec::new()),
+            request_made: Notify::new(),
+            gateway: Mutex::new(None),
+            sequence: AtomicU64::new(0),
+        });
+
+        let router = Router::new()
+            .route("/gateway", get(gateway))
+            .fallback(rest)
+            .with_state(Arc::clone(&state));
+
+        let server = tokio::spawn(async move {
+            axum::serve(listener, router).await.unwrap();
+        });
+
+        Self {
+            url,
+            state,
+            cursor: Mutex::new(0),
+            server,
+        }
+    }
+
+    /// Sends an event to the bot through the gateway
+    pub fn dispatch(&self, event: &str, data: Value) {
+        let payload = json!({
+            "op": 0,
+            "t": event,
+            "s": self.state.sequence.fetch_add(1, Ordering::SeqCst) + 1,
+            "d": data,
+        });
+
+        self.state
+            .gateway
+            .lock()
+            .unwrap()
+            .as_ref()
+            .expect("The bot is not connected to the gateway")
+            .send(WsMessage::Text(payload.to_string()))
+            .expect("The gateway connection is closed");
+    }
+
+    /// Waits for the next request of the bot matching the predicate
+    ///
+    /// The requests made before the previously matched one are ignored, so that a test can wait
+    /// for each step of a flow in order.
+    pub async fn wait_for(&self, predicate: impl Fn(&Request) -> bool) -> Request {
+        let find = || {
+            let requests = self.state.requests.lock().unwrap();
+            let mut cursor = self.cursor.lock().unwrap();
+            let index = requests[*cursor..].iter().position(&predicate)? + *cursor;
+            *cursor = index + 1;
+            Some(requests[index].clone())
+        };
+
+        tokio::time::timeout(WAIT_TIMEOUT, async {
+            loop {
+                let notified = self.state.request_made.notified();
+                if let Some(request) = find() {
+                    return request;
+                }
+                notified.await;
+            }
+        })
+        .await
+        .unwrap_or_else(|_| {
+            let requests = self.state.requests.lock().unwrap();
+            let paths: Vec<String> = requests
+                .iter()
+                .map(|request| format!("{} {}", request.method, request.path))
+                .collect();
+            panic!("The bot did not make the expected request, it made: {paths:#?}")
+        })
+    }
+
+    /// Waits for a message sent by the bot in the channel
+    pub async fn wait_for_message(&self, channel_id: u64) -> Request {
+        let path = format!("/channels/{channel_id}/messages");
+        self.wait_for(|request| request.is(Method::POST, &path))
+            .await
+    }
+
+    /// Returns every request made by the bot so far
+    pub fn requests(&self) -> Vec<Request> {
+        self.state.requests.lock().unwrap().clone()
+    }
+
+    /// Returns the channel with the given ID, if it exists
+    pub fn channel(&self, id: u64) -> Option<GuildChannel> {
+        self.state.world.lock().unwrap().channels.get(&id).cloned()
+    }
+
+    /// Returns every channel of the guild
+    pub fn channels(&self) -> Vec<GuildChannel> {
+        let world = self.state.world.lock().unwrap();
+        world.channels.values().cloned().collect()
+    }
+
+    /// Adds a channel to the guild, as if created by a member
+    pub fn add_channel(&self, id: u64, name: &str, kind: ChannelType, parent: Option<u64>) {
+        self.state
+            .world
+            .lock()
+            .unwrap()
+            .channels
+            .insert(id, models::channel(id, name, kind, parent));
+    }
+
+    /// Adds a member to the guild, with the given roles
+    pub fn add_member(&self, id: u64, name: &str, roles: &[u64]) {
+        self.state
+            .world
+            .lock()
+            .unwrap()
+            .members
+            .insert(id, models::member(id, name, roles));
+    }
+
+    /// Returns the role with the given ID, if it exists
+    pub fn role(&self, id: u64) -> Option<Role> {
+        self.state.world.lock().unwrap().roles.get(&id).cloned()
+    }
+
+    /// Returns the ID of the last message stored in the channel
+    pub fn last_message_id(&self, channel_id: u64) -> Option<u64> {
+        let world = self.state.world.lock().unwrap();
+        world
+            .messages
+            .values()
+            .filter(|message| message.channel_id.get() == channel_id)
+            .map(|message| message.id.get())
+            .max()
+    }
+
+    /// Stores a message sent by a user and returns it, as it is dispatched by the gateway
+    pub fn store_user_message(&self, author_id: u64, channel_id: u64, content: &str) -> Value {
+        let mut world = self.state.world.lock().unwrap();
+        let id = world.next_id();
+        let author = world.members[&author_id].user.clone();
+        let message = models::message(id, channel_id, &author, content);
+        world.messages.insert(id, message.clone());
+
+        let mut value = serde_json::to_value(&message).unwrap();
+        value["guild_id"] = json!(GUILD_ID.to_string());
+        value
+    }
+
+    /// Returns the guild as sent in the `GUILD_CREATE` event
+    pub fn guild_create(&self) -> Value {
+        let world = self.state.world.lock().unwrap();
+        models::guild_create(&world.channels, &world.roles, &world.members)
+    }
+
+    /// Returns the member with the given ID
+    pub fn member(&self, id: u64) -> Member {
+        self.state.world.lock().unwrap().members[&id].clone()
+    }
+
+    /// Returns whether the bot is connected to the gateway
+    pub fn is_connected(&self) -> bool {
+        self.state.gateway.lock().unwrap().is_some()
+    }
+}
+
+impl Drop for FakeDiscord {
+    fn drop(&mut self) {
+        self.server.abort();
+    }
+}
+
+/// Handles the gateway connection of the bot: hello, identify, ready and heartbeats
+async fn gateway(State(state): State<Arc<FakeState>>, upgrade: WebSocketUpgrade) -> Response {
+    upgrade.on_upgrade(move |socket| run_gateway(state, socket))
+}
+
+async fn run_gateway(state: Arc<FakeState>, socket: WebSocket) {
+    let (mut sink, mut stream) = socket.split();
+    let (sender, mut receiver) = mpsc::unbounded_channel();
+
+    let hello = json!({ "op": 10, "d": { "heartbeat_interval": 45_000 } });
+    if sink.send(WsMessage::Text(hello.to_string())).await.is_err() {
+        return;
+    }
+
+    // Messages to the bot are sent by a single task, whether they answer it or are dispatched
+    let writer = tokio::spawn(async move {
+        while let Some(message) = receiver.recv().await {
+            if sink.send(message).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    while let Some(Ok(message)) = stream.next().await {
+        let WsMessage::Text(text) = message else {
+            continue;
+        };
+        let payload: Value = serde_json::from_str(&text).unwrap_or_default();
+
+        match payload["op"].as_u64() {
+            // Heartbeat
+            Some(1) => {
+                let _ = sender.send(WsMessage::Text(json!({ "op": 11 }).to_string()));
+            }
+            // Identify
+            Some(2) => {
+                let ready = json!({
+                    "op": 0,
+                    "t": "READY",
+                    "s": state.sequence.fetch_add(1, Ordering::SeqCst) + 1,
+                    "d": models::ready(&state.url),
+                });
+                let _ = sender.send(WsMessage::Text(ready.to_string()));
+                *state.gateway.lock().unwrap() = Some(sender.clone());
+            }
+            _ => {}
+        }
+    }
+
+    *state.gateway.lock().unwrap() = None;
+    writer.abort();
+}
+
+/// Answers the REST requests of the bot
+async fn rest(
+    State(state): State<Arc<FakeState>>,
+    method: Method,
+    uri: Uri,
+    Query(query): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    let path = uri
+        .path()
+        .trim_start_matches("/api/v10")
+        .trim_end_matches('/')
+        .to_string();
+    let body = parse_body(&headers, &body);
+
+    let response = {
+        let mut world = state.world.lock().unwrap();
+        answer(&mut world, &state.url, &method, &path, &query, &body)
+    };
+
+    state
+        .requests
+        .lock()
+        .unwrap()
+        .push(Request { method, path, body });
+    state.request_made.notify_waiters();
+
+    response
+}
+
+/// Reads the JSON body of a request, from the `payload_json` part of multipart requests
+fn parse_body(headers: &HeaderMap, body: &Bytes) -> Value {
+    let content_type = headers
+        .get("content-type")
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+
+    if content_type.starts_with("multipart/form-data") {
+        let text = String::from_utf8_lossy(body);
+        return text
+            .split("name=\"payload_json\"")
+            .nth(1)
+            .and_then(|part| part.split("\r\n\r\n").nth(1))
+            .and_then(|part| part.split("\r\n--").next())
+            .and_then(|json| serde_json::from_str(json).ok())
+            .unwrap_or(Value::Null);
+    }
+
+    serde_json::from_slice(body).unwrap_or(Value::Null)
+}
+
+fn no_content() -> Response {
+    StatusCode::NO_CONTENT.into_response()
+}
+
+fn not_found() -> Response {
+    (
+        StatusCode::NOT_FOUND,
+        Json(json!({ "message": "Unknown", "code": 10003 })),
+    )
+        .into_response()
+}
+
+fn ok(value: impl serde::Serialize) -> Response {
+    Json(serde_json::to_value(value).unwrap()).into_response()
+}
+
+fn answer(
+    world: &mut World,
+    url: &str,
+    method: &Method,
+    path: &str,
+    query: &HashMap<String, String>,
+    body: &Value,
+) -> Response {
+    let segments: Vec<&str> = path.trim_start_matches('/').split('/').collect();
+    let id = |index: usize| -> u64 { segments[index].parse().unwrap_or_default() };
+
+    match (method.as_str(), segments.as_slice()) {
+        ("GET", ["gateway"]) => {
+            ok(json!({ "url": format!("{}/gateway", url.replace("http", "ws")) }))
+        }
+        ("GET", ["gateway", "bot"]) => ok(json!({
+            "url": format!("{}/gateway", url.replace("http", "ws")),
+            "shards": 1,
+            "session_start_limit": {
+                "total": 1000, "remaining": 1000, "reset_after": 0, "max_concurrency": 1
+            }
+        })),
+        ("GET", ["oauth2", "applications", "@me"]) => ok(json!({
+            "id": BOT_ID.to_string(),
+            "name": "bot",
+            "icon": null,
+            "description": "",
+            "bot_public": false,
+            "bot_require_code_grant": false,
+            "owner": models::user(OWNER_ID, "owner"),
+            "verify_key": "",
+            "flags": 0,
+        })),
+        ("GET", ["users", "@me"]) => ok(json!(models::user(BOT_ID, "bot"))),
+        ("PUT", ["applications", _, "commands"]) => ok(json!([])),
+        ("GET", ["guilds", _]) => ok(models::partial_guild(&world.roles)),
+        ("GET", ["guilds", _, "channels"]) => ok(world.channels.values().collect::<Vec<_>>()),
+        ("POST", ["guilds", _, "channels"]) => {
+            let id = world.next_id();
+            let kind = body["type"].as_u64().unwrap_or(0) as u8;
+            let parent = body["parent_id"]
+                .as_str()
+                .and_then(|parent| parent.parse().ok())
+                .or_else(|| body["parent_id"].as_u64());
+            let mut channel = models::channel(
+                id,
+                body["name"].as_str().unwrap_or_default(),
+                ChannelType::from(kind),
+                parent,
+            );
+            channel.topic = body["topic"].as_str().map(str::to_string);
+            world.channels.insert(id, channel.clone());
+            ok(channel)
+        }
+        ("PATCH", ["guilds", _, "channels"]) => {
+            for entry in body.as_array().into_iter().flatten() {
+                let channel = entry["id"]
+                    .as_str()
+                    .and_then(|id| id.parse().ok())
+                    .and_then(|id: u64| world.channels.get_mut(&id));
+                if let (Some(channel), Some(position)) = (channel, entry["position"].as_u64()) {
+                    channel.position = position as u16;
+                }
+            }
+            no_content()
+        }
+        ("GET", ["guilds", _, "roles"]) => ok(world.roles.values().collect::<Vec<_>>()),
+        ("POST", ["guilds", _, "roles"]) => {
+            let id = world.next_id();
+            let role = models::role(id, body["name"].as_str().unwrap_or("new role"));
+            world.roles.insert(id, role.clone());
+            ok(role)
+        }
+        ("DELETE", ["guilds", _, "roles", _]) => {
+            world.roles.remove(&id(3));
+            no_content()
+        }
+        ("GET", ["guilds", _, "members", _]) => match world.members.get(&id(3)) {
+            Some(member) => ok(member),
+            None => not_found(),
+        },
+        ("PUT" | "DELETE", ["guilds", _, "members", _, "roles", _]) => no_content(),
+        ("GET", ["channels", _]) => match world.channels.get(&id(1)) {
+            Some(channel) => ok(channel),
+            None => not_found(),
+        },
+        ("PATCH", ["channels", _]) => {
+            let Some(channel) = world.channels.get_mut(&id(1)) else {
+                return not_found();
+            };
+            if let Some(name) = body["name"].as_str() {
+                channel.name = name.to_string();
+            }
+            if let Some(parent) = body["parent_id"].as_str() {
+                channel.parent_id = parent.parse::<u64>().ok().map(ChannelId::new);
+            }
+            ok(channel.clone())
+        }
+        ("DELETE", ["channels", _]) => match world.channels.remove(&id(1)) {
+            Some(channel) => ok(channel),
+            None => not_found(),
+        },
+        ("PUT" | "DELETE", ["channels", _, "permissions", _]) => no_content(),
+        ("PUT" | "DELETE", ["channels", _, "thread-members", _]) => no_content(),
+        ("POST", ["channels", _, "threads"]) => {
+            let id = world.next_id();
+            let name = body["name"].as_str().unwrap_or_default();
+            let mut thread = models::channel(id, name, ChannelType::PrivateThread, None);
+            thread.parent_id = Some(ChannelId::new(segments[1].parse().unwrap()));
+            world.channels.insert(id, thread.clone());
+            ok(thread)
+        }
+        ("GET", ["channels", _, "messages"]) => {
+            let channel_id = id(1);
+            let before: u64 = query
+                .get("before")
+                .and_then(|before| before.parse().ok())
+                .unwrap_or(u64::MAX);
+            let limit: usize = query
+                .get("limit")
+                .and_then(|limit| limit.parse().ok())
+                .unwrap_or(50);
+            let messages: Vec<&Message> = world
+                .messages
+                .values()
+                .rev()
+                .filter(|message| message.channel_id.get() == channel_id)
+                .filter(|message| message.id.get() < before)
+                .take(limit)
+                .collect();
+            ok(messages)
+        }
+        ("POST", ["channels", _, "messages"]) => {
+            let id = world.next_id();
+            let bot = world.members[&BOT_ID].user.clone();
+            let message = models::bot_message(id, segments[1].parse().unwrap(), &bot, body);
+            world.messages.insert(id, message.clone());
+            ok(message)
+        }
+        ("GET" | "PATCH", ["channels", _, "messages", _]) => match world.messages.get(&id(3)) {
+            Some(message) => ok(message),
+            None => not_found(),
+        },
+        ("PUT" | "DELETE", ["channels", _, "messages", _, "reactions", ..]) => no_content(),
+        ("DELETE", ["channels", _, "messages", _]) => no_content(),
+        ("POST", ["users", "@me", "channels"]) => {
+            let user_id: u64 = body["recipient_id"]
+                .as_str()
+                .and_then(|id| id.parse().ok())
+                .or_else(|| body["recipient_id"].as_u64())
+                .unwrap_or_default();
+            let Some(member) = world.members.get(&user_id) else {
+                return not_found();
+            };
+            let user = member.user.clone();
+            let mut channel = PrivateChannel::default();
+            // A user has a single DM channel, its ID is derived from theirs
+            channel.id = ChannelId::new(user_id + 1_000_000);
+            channel.recipient = user;
+            ok(channel)
+        }
+        ("GET", ["users", _]) => match world.members.get(&id(1)) {
+            Some(member) => ok(&member.user),
+            None => not_found(),
+        },
+        ("POST", ["interactions", _, _, "callback"]) => no_content(),
+        ("POST", ["webhooks", _, _]) | ("PATCH", ["webhooks", _, _, "messages", _]) => {
+            let id = world.next_id();
+            let bot = world.members[&BOT_ID].user.clone();
+            ok(models::bot_message(id, GENERAL_CHANNEL_ID, &bot, body))
+        }
+        _ => not_found(),
+    }
+}
@@ -0,0 +1,298 @@
+//! Harness of the integration tests
+//!
+//! Each test gets a throwaway database from `sqlx::test` (with the migrations applied) and a bot
+//! connected to a local fake of the Discord API. The tests play the members by dispatching
+//! gateway events, and check the requests of the bot and the database.
+
+// Every test binary only uses a part of the harness
+#![allow(dead_code)]
+
+pub mod discord;
+pub mod models;
+
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+use desquestion::{
+    cache::ConfigCache,
+    config::Timeouts,
+    database::Pool,
+    handler::{get_framework, Data},
+    metrics::Metrics,
+    shutdown::Shutdown,
+};
+use poise::serenity_prelude::{
+    self as serenity, async_trait, ChannelId, ChannelType, ClientBuilder, EventHandler,
+    GatewayIntents, GuildId, HttpBuilder, Ready, ShardManager,
+};
+use serde_json::Value;
+use tokio::sync::oneshot;
+
+use discord::{
+    FakeDiscord, CLAIMED_CATEGORY_ID, GUILD_ID, HELPER_ROLE_ID, LOG_CHANNEL_ID, MODERATOR_ROLE_ID,
+    TICKET_CHANNEL_ID, UNCLAIMED_CATEGORY_ID,
+};
+
+/// Time given to the bot to start waiting for an answer after sending its question
+///
+/// The request of the bot is recorded before it receives the response, so an event dispatched
+/// right away could reach the bot before it listens for it.
+const SETTLE_DELAY: Duration = Duration::from_millis(200);
+
+/// The bot connected to the fake Discord API
+pub struct TestBot {
+    pub discord: FakeDiscord,
+    pub ctx: serenity::Context,
+    pub pool: Pool,
+    shutdown: Arc<Shutdown>,
+    metrics: Arc<Metrics>,
+    cache: Arc<ConfigCache>,
+    shards: Arc<ShardManager>,
+    next_id: AtomicU64,
+}
+
+/// Sends the context of the bot to the test once it is ready
+struct ReadyHandler(Mutex<Option<oneshot::Sender<serenity::Context>>>);
+
+#[async_trait]
+impl EventHandler for ReadyHandler {
+    async fn ready(&self, ctx: serenity::Context, _: Ready) {
+        if let Some(sender) = self.0.lock().unwrap().take() {
+            let _ = sender.send(ctx);
+        }
+    }
+}
+
+impl TestBot {
+    /// Starts the bot and waits for it to know the test guild
+    pub async fn start(pool: Pool) -> Self {
+        // The logs of the bot are shown along with the output of failing tests
+        let _ = tracing_subscriber::fmt()
+            .with_env_filter("warn")
+            .with_test_writer()
+            .try_init();
+        let discord = FakeDiscord::start().await;
+        let shutdown = Arc::new(Shutdown::default());
+        let metrics = Arc::new(Metrics::default());
+        let cache = Arc::new(ConfigCache::default());
+
+        let http = HttpBuilder::new("token")
+            .proxy(&discord.url)
+            .ratelimiter_disabled(true)
+            .build();
+
+        let (sender, receiver) = oneshot::channel();
+        let data = Data {
+            pool: pool.clone(),
+            shutdown: Arc::clone(&shutdown),
+            timeouts: timeouts(),
+            metrics: Arc::clone(&metrics),
+            cache: Arc::clone(&cache),
+        };
+
+        let intents = GatewayIntents::non_privileged() | GatewayIntents::MESSAGE_CONTENT;
+        let mut client = ClientBuilder::new_with_http(http, intents)
+            .framework(get_framework(data))
+            .event_handler(ReadyHandler(Mutex::new(Some(sender))))
+            .await
+            .expect("Failed to create the client");
+        let shards = Arc::clone(&client.shard_manager);
+
+        tokio::spawn(async move {
+            let _ = client.start().await;
+        });
+
+        let ctx = tokio::time::timeout(Duration::from_secs(20), receiver)
+            .await
+            .expect("The bot did not connect to the fake gateway")
+            .unwrap();
+
+        let bot = Self {
+            discord,
+            ctx,
+            pool,
+            shutdown,
+            metrics,
+            cache,
+            shards,
+            next_id: AtomicU64::new(500_000),
+        };
+
+        bot.discord
+            .dispatch("GUILD_CREATE", bot.discord.guild_create());
+        while bot.ctx.cache.guild(GuildId::new(GUILD_ID)).is_none() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        bot
+    }
+
+    /// Returns the data of the running bot, to call the bot functions directly
+    pub fn data(&self) -> Data {
+        Data {
+            pool: self.pool.clone(),
+            shutdown: Arc::clone(&self.shutdown),
+            timeouts: timeouts(),
+            metrics: Arc::clone(&self.metrics),
+            cache: Arc::clone(&self.cache),
+        }
+    }
+
+    /// Registers the test guild as set up, with the default channels, categories and roles
+    pub async fn setup_server(&self) {
+        self.discord.add_channel(
+            UNCLAIMED_CATEGORY_ID,
+            "Unclaimed",
+            ChannelType::Category,
+            None,
+        );
+        self.discord
+            .add_channel(CLAIMED_CATEGORY_ID, "Claimed", ChannelType::Category, None);
+
+        sqlx::query(
+            "INSERT INTO servers (id, ticket_channel_id, unclaimed_category_id, claimed_category_id,
+                helper_role_id, moderator_role_id, log_channel_id)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)",
+        )
+        .bind(GUILD_ID as i64)
+        .bind(TICKET_CHANNEL_ID as i64)
+        .bind(UNCLAIMED_CATEGORY_ID as i64)
+        .bind(CLAIMED_CATEGORY_ID as i64)
+        .bind(HELPER_ROLE_ID as i64)
+        .bind(MODERATOR_ROLE_ID as i64)
+        .bind(LOG_CHANNEL_ID as i64)
+        .execute(&self.pool)
+        .await
+        .unwrap();
+        self.cache.invalidate(GuildId::new(GUILD_ID));
+    }
+
+    /// Adds a panel to the test guild, displayed by the given message of the ticket channel
+    pub async fn add_panel(&self, message_id: u64) {
+        sqlx::query(
+            "INSERT INTO panels (server_id, name, channel_id, message_id, title, description,
+                button_label)
+            VALUES ($1, 'default', $2, $3, 'Open a ticket', '', 'Open a ticket')",
+        )
+        .bind(GUILD_ID as i64)
+        .bind(TICKET_CHANNEL_ID as i64)
+        .bind(message_id as i64)
+        .execute(&self.pool)
+        .await
+        .unwrap();
+        self.cache.invalidate(GuildId::new(GUILD_ID));
+    }
+
+    /// Adds a subject to the test guild and returns its ID
+    pub async fn add_subject(&self, name: &str) -> i64 {
+        sqlx::query_scalar(
+            "INSERT INTO subjects (server_id, name, channel_id) VALUES ($1, $2, $3) RETURNING id",
+        )
+        .bind(GUILD_ID as i64)
+        .bind(name)
+        .bind(TICKET_CHANNEL_ID as i64)
+        .fetch_one(&self.pool)
+        .await
+        .unwrap()
+    }
+
+    /// Opens a ticket of the member in a new channel of the unclaimed category, without the bot,
+    /// and returns the ID of the channel
+    pub async fn add_ticket(&self, author_id: u64) -> u64 {
+        let channel_id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        self.discord.add_channel(
+            channel_id,
+            "🎫-ticket",
+            ChannelType::Text,
+            Some(UNCLAIMED_CATEGORY_ID),
+        );
+
+        // SQLite has no default opening time, it is stamped like the bot does
+        sqlx::query(
+            "INSERT INTO tickets (channel_id, server_id, author_id, opened_at)
+            VALUES ($1, $2, $3, CURRENT_TIMESTAMP)",
+        )
+        .bind(channel_id as i64)
+        .bind(GUILD_ID as i64)
+        .bind(author_id as i64)
+        .execute(&self.pool)
+        .await
+        .unwrap();
+        self.cache
+            .add_ticket_channel(GuildId::new(GUILD_ID), ChannelId::new(channel_id));
+
+        channel_id
+    }
+
+    /// Sends a message as a member, returns the message as dispatched
+    pub async fn send(&self, author_id: u64, channel_id: u64, content: &str) -> Value {
+        tokio::time::sleep(SETTLE_DELAY).await;
+        let message = self
+            .discord
+            .store_user_message(author_id, channel_id, content);
+        self.discord.dispatch("MESSAGE_CREATE", message.clone());
+        message
+    }
+
+    /// Reacts to a message as a member
+    pub async fn react(&self, user_id: u64, channel_id: u64, message_id: u64, emoji: &str) {
+        tokio::time::sleep(SETTLE_DELAY).await;
+        self.discord.dispatch(
+            "MESSAGE_REACTION_ADD",
+            models::reaction(user_id, channel_id, message_id, emoji),
+        );
+    }
+
+    /// Clicks on a button (or chooses values in a select menu) of the last message of the channel
+    pub async fn click(
+        &self,
+        user_id: u64,
+        channel_id: u64,
+        custom_id: &str,
+        values: Option<&[&str]>,
+    ) {
+        tokio::time::sleep(SETTLE_DELAY).await;
+        let message_id = self
+            .discord
+            .last_message_id(channel_id)
+            .expect("There is no message to click on");
+
+        let mut message = serde_json::to_value(models::message(
+            message_id,
+            channel_id,
+            &self.discord.member(discord::BOT_ID).user,
+            "",
+        ))
+        .unwrap();
+        message["guild_id"] = Value::String(GUILD_ID.to_string());
+
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let member = self.discord.member(user_id);
+        self.discord.dispatch(
+            "INTERACTION_CREATE",
+            models::component_interaction(id, &member, &message, custom_id, values),
+        );
+    }
+}
+
+impl Drop for TestBot {
+    fn drop(&mut self) {
+        let shards = Arc::clone(&self.shards);
+        tokio::spawn(async move { shards.shutdown_all().await });
+    }
+}
+
+/// Short timeouts, so that the tests do not wait for the delays meant for the members
+fn timeouts() -> Timeouts {
+    Timeouts {
+        prompt: Duration::from_secs(5),
+        close_delay: Duration::from_millis(300),
+        close_request: Duration::from_secs(5),
+        shutdown: Duration::from_secs(5),
+    }
+}
@@ -0,0 +1,161 @@
+//! The Discord objects served by the fake API
+//!
+//! They are built from the serenity models so that they always deserialize on the bot side.
+
+use std::collections::BTreeMap;
+
+use poise::serenity_prelude::{
+    ChannelId, ChannelType, Guild, GuildChannel, GuildId, Member, Message, MessageId, Role, RoleId,
+    Timestamp, User, UserId,
+};
+use serde_json::{json, Value};
+
+use super::discord::{BOT_ID, GUILD_ID, OWNER_ID};
+
+pub fn user(id: u64, name: &str) -> User {
+    let mut user = User::default();
+    user.id = UserId::new(id);
+    user.name = name.to_string();
+    user.bot = id == BOT_ID;
+    user
+}
+
+pub fn member(id: u64, name: &str, roles: &[u64]) -> Member {
+    let mut member = Member::default();
+    member.user = user(id, name);
+    member.guild_id = GuildId::new(GUILD_ID);
+    member.roles = roles.iter().map(|role| RoleId::new(*role)).collect();
+    member.joined_at = Some(Timestamp::now());
+    member
+}
+
+pub fn role(id: u64, name: &str) -> Role {
+    let mut role = Role::default();
+    role.id = RoleId::new(id);
+    role.guild_id = GuildId::new(GUILD_ID);
+    role.name = name.to_string();
+    role
+}
+
+pub fn channel(id: u64, name: &str, kind: ChannelType, parent: Option<u64>) -> GuildChannel {
+    let mut channel = GuildChannel::default();
+    channel.id = ChannelId::new(id);
+    channel.guild_id = GuildId::new(GUILD_ID);
+    channel.name = name.to_string();
+    channel.kind = kind;
+    channel.parent_id = parent.map(ChannelId::new);
+    channel
+}
+
+pub fn message(id: u64, channel_id: u64, author: &User, content: &str) -> Message {
+    let mut message = Message::default();
+    message.id = MessageId::new(id);
+    message.channel_id = ChannelId::new(channel_id);
+    message.author = author.clone();
+    message.content = content.to_string();
+    message.timestamp = Timestamp::now();
+    message
+}
+
+/// Returns the message created by a request of the bot
+pub fn bot_message(id: u64, channel_id: u64, bot: &User, body: &Value) -> Message {
+    let content = body["content"].as_str().unwrap_or_default();
+    let mut message = message(id, channel_id, bot, content);
+    message.guild_id = Some(GuildId::new(GUILD_ID));
+    message.embeds = serde_json::from_value(body["embeds"].clone()).unwrap_or_default();
+    message
+}
+
+fn guild(roles: &BTreeMap<u64, Role>) -> Guild {
+    let mut guild = Guild::default();
+    guild.id = GuildId::new(GUILD_ID);
+    guild.name = "Test server".to_string();
+    guild.owner_id = UserId::new(OWNER_ID);
+    guild.preferred_locale = "en-US".to_string();
+    guild.roles = roles.values().map(|role| (role.id, role.clone())).collect();
+    guild
+}
+
+/// Returns the guild as answered by `GET /guilds/{id}`
+pub fn partial_guild(roles: &BTreeMap<u64, Role>) -> Value {
+    serde_json::to_value(guild(roles)).unwrap()
+}
+
+/// Returns the guild as sent in the `GUILD_CREATE` event, with its channels and members
+pub fn guild_create(
+    channels: &BTreeMap<u64, GuildChannel>,
+    roles: &BTreeMap<u64, Role>,
+    members: &BTreeMap<u64, Member>,
+) -> Value {
+    let mut guild = guild(roles);
+    guild.joined_at = Timestamp::now();
+    guild.member_count = members.len() as u64;
+    guild.channels = channels
+        .values()
+        .map(|channel| (channel.id, channel.clone()))
+        .collect();
+    guild.members = members
+        .values()
+        .map(|member| (member.user.id, member.clone()))
+        .collect();
+    serde_json::to_value(guild).unwrap()
+}
+
+/// Returns the `READY` event sent when the bot identifies
+pub fn ready(url: &str) -> Value {
+    json!({
+        "v": 10,
+        "user": serde_json::to_value(user(BOT_ID, "bot")).unwrap(),
+        "guilds": [{ "id": GUILD_ID.to_string(), "unavailable": true }],
+        "session_id": "session",
+        "resume_gateway_url": format!("{}/gateway", url.replace("http", "ws")),
+        "shard": [0, 1],
+        "application": { "id": BOT_ID.to_string(), "flags": 0 },
+    })
+}
+
+/// Returns a click on a button or a choice in a select menu of a message
+pub fn component_interaction(
+    id: u64,
+    user: &Member,
+    message: &Value,
+    custom_id: &str,
+    values: Option<&[&str]>,
+) -> Value {
+    let data = match values {
+        Some(values) => json!({ "custom_id": custom_id, "component_type": 3, "values": values }),
+        None => json!({ "custom_id": custom_id, "component_type": 2 }),
+    };
+
+    let mut member = serde_json::to_value(user).unwrap();
+    member["permissions"] = json!("0");
+
+    json!({
+        "id": id.to_string(),
+        "application_id": BOT_ID.to_string(),
+        "type": 3,
+        "data": data,
+        "guild_id": message["guild_id"],
+        "channel_id": message["channel_id"],
+        "member": member,
+        "token": format!("token-{id}"),
+        "version": 1,
+        "message": message,
+        "app_permissions": "0",
+        "locale": "en-US",
+        "entitlements": [],
+    })
+}
+
+/// Returns a reaction of a user to a message
+pub fn reaction(user_id: u64, channel_id: u64, message_id: u64, emoji: &str) -> Value {
+    json!({
+        "user_id": user_id.to_string(),
+        "channel_id": channel_id.to_string(),
+        "message_id": message_id.to_string(),
+        "guild_id": GUILD_ID.to_string(),
+        "emoji": { "id": null, "name": emoji },
+        "burst": false,
+        "type": 0,
+    })
+}
@@ -0,0 +1,138 @@
+//! Integration tests of the server commands: setup and reset
+
+mod common;
+
+use axum::http::Method;
+use common::{
+    discord::{
+        GENERAL_CHANNEL_ID, GUILD_ID, HELPER_ROLE_ID, LOG_CHANNEL_ID, MODERATOR_ROLE_ID, OWNER_ID,
+        TICKET_CHANNEL_ID, UNCLAIMED_CATEGORY_ID,
+    },
+    TestBot,
+};
+use desquestion::database::Pool;
+
+impl TestBot {
+    /// Reacts to the last message of the bot in the general channel
+    async fn answer(&self, emoji: &str) {
+        self.discord.wait_for_message(GENERAL_CHANNEL_ID).await;
+        let message_id = self.discord.last_message_id(GENERAL_CHANNEL_ID).unwrap();
+        self.react(OWNER_ID, GENERAL_CHANNEL_ID, message_id, emoji)
+            .await;
+    }
+}
+
+#[sqlx::test(migrator = "desquestion::database::MIGRATOR")]
+async fn test_setup(pool: Pool) {
+    let bot = TestBot::start(pool).await;
+
+    bot.send(OWNER_ID, GENERAL_CHANNEL_ID, "$setup").await;
+
+    for channel_id in [TICKET_CHANNEL_ID, LOG_CHANNEL_ID] {
+        bot.discord.wait_for_message(GENERAL_CHANNEL_ID).await;
+        let url = format!("https://discord.com/channels/{GUILD_ID}/{channel_id}");
+        bot.send(OWNER_ID, GENERAL_CHANNEL_ID, &url).await;
+    }
+
+    // A new helper role, and the existing moderator role
+    bot.answer("🆕").await;
+    let created_role = bot
+        .discord
+        .wait_for(|request| request.is(Method::POST, &format!("/guilds/{GUILD_ID}/roles")))
+        .await;
+    bot.answer("🔗").await;
+    bot.discord.wait_for_message(GENERAL_CHANNEL_ID).await;
+    bot.send(
+        OWNER_ID,
+        GENERAL_CHANNEL_ID,
+        &format!("<@&{MODERATOR_ROLE_ID}>"),
+    )
+    .await;
+
+    // The default panel is posted in the ticket channel
+    bot.discord.wait_for_message(TICKET_CHANNEL_ID).await;
+
+    let server: (i64, i64, i64) = sqlx::query_as(
+        "SELECT ticket_channel_id, log_channel_id, moderator_role_id FROM servers WHERE id = $1",
+    )
+    .bind(GUILD_ID as i64)
+    .fetch_one(&bot.pool)
+    .await
+    .unwrap();
+    assert_eq!(
+        server,
+        (
+            TICKET_CHANNEL_ID as i64,
+            LOG_CHANNEL_ID as i64,
+            MODERATOR_ROLE_ID as i64
+        )
+    );
+    assert_eq!(created_role.body["name"], "Helper");
+
+    let panels: i64 = sqlx::query_scalar("SELECT count(*) FROM panels WHERE channel_id = $1")
+        .bind(TICKET_CHANNEL_ID as i64)
+        .fetch_one(&bot.pool)
+        .await
+        .unwrap();
+    assert_eq!(panels, 1);
+}
+
+#[sqlx::test(migrator = "desquestion::database::MIGRATOR")]
+async fn test_setup_with_invalid_channel(pool: Pool) {
+    let bot = TestBot::start(pool).await;
+
+    bot.send(OWNER_ID, GENERAL_CHANNEL_ID, "$setup").await;
+    bot.discord.wait_for_message(GENERAL_CHANNEL_ID).await;
+    bot.send(OWNER_ID, GENERAL_CHANNEL_ID, "#tickets").await;
+
+    let reply = bot.discord.wait_for_message(GENERAL_CHANNEL_ID).await;
+    assert_eq!(reply.text(), "Invalid channel");
+
+    let servers: i64 = sqlx::query_scalar("SELECT count(*) FROM servers")
+        .fetch_one(&bot.pool)
+        .await
+        .unwrap();
+    assert_eq!(servers, 0);
+}
+
+#[sqlx::test(migrator = "desquestion::database::MIGRATOR")]
+async fn test_reset(pool: Pool) {
+    let bot = TestBot::start(pool).await;
+    bot.setup_server().await;
+
+    bot.send(OWNER_ID, GENERAL_CHANNEL_ID, "$reset").await;
+    bot.discord.wait_for_message(GENERAL_CHANNEL_ID).await;
+    bot.send(OWNER_ID, GENERAL_CHANNEL_ID, "CONFIRM").await;
+
+    // Only the helper role and the unclaimed category are deleted
+    bot.answer("✅").await;
+    bot.discord
+        .wait_for(|request| {
+            request.is(
+                Method::DELETE,
+                &format!("/guilds/{GUILD_ID}/roles/{HELPER_ROLE_ID}"),
+            )
+        })
+        .await;
+    bot.answer("❌").await;
+    bot.answer("✅").await;
+    bot.discord
+        .wait_for(|request| {
+            request.is(
+                Method::DELETE,
+                &format!("/channels/{UNCLAIMED_CATEGORY_ID}"),
+            )
+        })
+        .await;
+    bot.answer("❌").await;
+    let reply = bot.discord.wait_for_message(GENERAL_CHANNEL_ID).await;
+    assert_eq!(reply.text(), "Reset successful");
+
+    let servers: i64 = sqlx::query_scalar("SELECT count(*) FROM servers")
+        .fetch_one(&bot.pool)
+        .await
+        .unwrap();
+    assert_eq!(servers, 0);
+    assert!(bot.discord.role(HELPER_ROLE_ID).is_none());
+    assert!(bot.discord.role(MODERATOR_ROLE_ID).is_some());
+}
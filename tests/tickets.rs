@@ -0,0 +1,877 @@
+//! Integration tests of the ticket lifecycle: creation, claiming and closing
+
+mod common;
+
+use axum::http::Method;
+use chrono::{TimeDelta, Utc};
+use common::{
+    discord::{
+        CLAIMED_CATEGORY_ID, GENERAL_CHANNEL_ID, GUILD_ID, HELPER_ID, HELPER_ROLE_ID,
+        LOG_CHANNEL_ID, MEMBER_ID, MODERATOR_ROLE_ID, OWNER_ID, TICKET_CHANNEL_ID,
+    },
+    TestBot,
+};
+use desquestion::{
+    database::{Pool, Subject},
+    tickets::{self, activity, office_hours::Scheduler, priority::Priority, sla, CreateOptions},
+};
+use poise::serenity_prelude::ChannelType;
+use std::time::Duration;
+
+/// Returns the channel ID of the open ticket of the member
+async fn open_ticket_channel(pool: &Pool, author_id: u64) -> Option<i64> {
+    sqlx::query_scalar("SELECT channel_id FROM tickets WHERE author_id = $1 AND is_open")
+        .bind(author_id as i64)
+        .fetch_optional(pool)
+        .await
+        .unwrap()
+}
+
+/// Moves the opening of the tickets of the member back by the given minutes
+async fn backdate_tickets(pool: &Pool, author_id: u64, minutes: i64) {
+    #[cfg(not(feature = "sqlite"))]
+    let query = "UPDATE tickets SET opened_at = opened_at - $2 * interval '1 minute'
+        WHERE author_id = $1";
+    #[cfg(feature = "sqlite")]
+    let query = "UPDATE tickets SET opened_at = datetime(opened_at, '-' || $2 || ' minutes')
+        WHERE author_id = $1";
+
+    sqlx::query(query)
+        .bind(author_id as i64)
+        .bind(minutes)
+        .execute(pool)
+        .await
+        .unwrap();
+}
+
+/// Moves the last messages of the author and of the staff in the ticket back by the given minutes
+async fn backdate_messages(pool: &Pool, channel_id: u64, author_minutes: i64, staff_minutes: i64) {
+    #[cfg(not(feature = "sqlite"))]
+    let query = "UPDATE tickets
+        SET last_author_message_at = last_author_message_at - $2 * interval '1 minute',
+            last_staff_message_at = last_staff_message_at - $3 * interval '1 minute'
+        WHERE channel_id = $1";
+    #[cfg(feature = "sqlite")]
+    let query = "UPDATE tickets
+        SET last_author_message_at = datetime(last_author_message_at, '-' || $2 || ' minutes'),
+            last_staff_message_at = datetime(last_staff_message_at, '-' || $3 || ' minutes')
+        WHERE channel_id = $1";
+
+    sqlx::query(query)
+        .bind(channel_id as i64)
+        .bind(author_minutes)
+        .bind(staff_minutes)
+        .execute(pool)
+        .await
+        .unwrap();
+}
+
+#[sqlx::test(migrator = "desquestion::database::MIGRATOR")]
+async fn test_create_ticket_with_subject(pool: Pool) {
+    let bot = TestBot::start(pool).await;
+    bot.setup_server().await;
+    let subject_id = bot.add_subject("Maths").await;
+
+    let member = bot.discord.member(MEMBER_ID);
+    let options = CreateOptions {
+        subject: Some(Subject {
+            id: Some(subject_id as u64),
+            name: "Maths".to_string(),
+        }),
+        ..Default::default()
+    };
+    tickets::create_ticket(&bot.ctx, &bot.data(), &member, options)
+        .await
+        .unwrap();
+
+    let created = bot
+        .discord
+        .wait_for(|request| request.is(Method::POST, &format!("/guilds/{GUILD_ID}/channels")))
+        .await;
+    assert_eq!(created.body["parent_id"], "110");
+
+    let channel_id = open_ticket_channel(&bot.pool, MEMBER_ID)
+        .await
+        .expect("The ticket was not stored");
+    let channel = bot.discord.channel(channel_id as u64).unwrap();
+    assert_eq!(channel.name, "🎫-Maths");
+
+    // The member is told where their ticket is
+    bot.discord.wait_for_message(1_000_000 + MEMBER_ID).await;
+}
+
+#[sqlx::test(migrator = "desquestion::database::MIGRATOR")]
+async fn test_create_ticket_prompts_for_subject(pool: Pool) {
+    let bot = TestBot::start(pool).await;
+    bot.setup_server().await;
+    bot.add_subject("Maths").await;
+    bot.add_subject("Physics").await;
+
+    let ctx = bot.ctx.clone();
+    let data = bot.data();
+    let member = bot.discord.member(MEMBER_ID);
+    let creation = tokio::spawn(async move {
+        tickets::create_ticket(&ctx, &data, &member, CreateOptions::default()).await
+    });
+
+    let created = bot
+        .discord
+        .wait_for(|request| request.is(Method::POST, &format!("/guilds/{GUILD_ID}/channels")))
+        .await;
+    let channel_id = bot
+        .discord
+        .channels()
+        .into_iter()
+        .find(|channel| channel.name == created.body["name"])
+        .unwrap()
+        .id
+        .get();
+
+    // The welcome message is sent before the member is asked for the subject
+    bot.discord.wait_for_message(channel_id).await;
+    bot.send(MEMBER_ID, channel_id, "physic").await;
+
+    let menu = bot.discord.wait_for_message(channel_id).await;
+    let options = &menu.body["components"][0]["components"][0]["options"];
+    assert_eq!(options[0]["label"], "Physics");
+
+    bot.click(MEMBER_ID, channel_id, "option_select", Some(&["0"]))
+        .await;
+    creation.await.unwrap().unwrap();
+
+    let subject: Option<String> = sqlx::query_scalar(
+        "SELECT s.name FROM tickets t JOIN subjects s ON s.id = t.subject_id
+        WHERE t.channel_id = $1",
+    )
+    .bind(channel_id as i64)
+    .fetch_one(&bot.pool)
+    .await
+    .unwrap();
+    assert_eq!(subject.as_deref(), Some("Physics"));
+}
+
+#[sqlx::test(migrator = "desquestion::database::MIGRATOR")]
+async fn test_reaction_on_panel(pool: Pool) {
+    let bot = TestBot::start(pool).await;
+    bot.setup_server().await;
+    bot.add_panel(900).await;
+
+    // Reactions on other messages are ignored without a single request
+    let before = bot.discord.requests().len();
+    bot.react(MEMBER_ID, TICKET_CHANNEL_ID, 901, tickets::TICKET_EMOJI)
+        .await;
+    bot.react(MEMBER_ID, TICKET_CHANNEL_ID, 900, "👍").await;
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert_eq!(bot.discord.requests().len(), before);
+
+    bot.react(MEMBER_ID, TICKET_CHANNEL_ID, 900, tickets::TICKET_EMOJI)
+        .await;
+    bot.discord
+        .wait_for(|request| request.is(Method::POST, &format!("/guilds/{GUILD_ID}/channels")))
+        .await;
+    bot.discord
+        .wait_for(|request| {
+            request.method == Method::DELETE && request.path.contains("/messages/900/reactions/")
+        })
+        .await;
+}
+
+#[sqlx::test(migrator = "desquestion::database::MIGRATOR")]
+async fn test_refused_reaction_answered_in_dm(pool: Pool) {
+    let bot = TestBot::start(pool).await;
+    bot.setup_server().await;
+    bot.add_panel(900).await;
+    bot.data().shutdown.stop();
+
+    // A reaction cannot be answered, the member learns why in direct message
+    bot.react(MEMBER_ID, TICKET_CHANNEL_ID, 900, tickets::TICKET_EMOJI)
+        .await;
+    let dm_channel_id = MEMBER_ID + 1_000_000;
+    let refusal = bot.discord.wait_for_message(dm_channel_id).await;
+    assert!(refusal.text().contains("The bot is restarting"));
+    assert!(open_ticket_channel(&bot.pool, MEMBER_ID).await.is_none());
+}
+
+#[sqlx::test(migrator = "desquestion::database::MIGRATOR")]
+async fn test_full_panel_category_overflows(pool: Pool) {
+    const PANEL_CATEGORY_ID: u64 = 700;
+
+    let bot = TestBot::start(pool).await;
+    bot.setup_server().await;
+    bot.discord
+        .add_channel(PANEL_CATEGORY_ID, "Support", ChannelType::Category, None);
+    for id in 0..50 {
+        bot.discord
+            .add_channel(800 + id, "old", ChannelType::Text, Some(PANEL_CATEGORY_ID));
+    }
+    bot.add_panel(900).await;
+    sqlx::query("UPDATE panels SET category_id = $1")
+        .bind(PANEL_CATEGORY_ID as i64)
+        .execute(&bot.pool)
+        .await
+        .unwrap();
+
+    bot.react(MEMBER_ID, TICKET_CHANNEL_ID, 900, tickets::TICKET_EMOJI)
+        .await;
+
+    // The full category of the panel gets an overflow category, like the ones of the server
+    let overflow = bot
+        .discord
+        .wait_for(|request| {
+            request.is(Method::POST, &format!("/guilds/{GUILD_ID}/channels"))
+                && request.body["name"] == "Support 2"
+        })
+        .await;
+    assert_eq!(overflow.body["type"], 4);
+    let overflow_id = bot
+        .discord
+        .channels()
+        .into_iter()
+        .find(|channel| channel.name == "Support 2")
+        .unwrap()
+        .id;
+
+    let created = bot
+        .discord
+        .wait_for(|request| {
+            request.is(Method::POST, &format!("/guilds/{GUILD_ID}/channels"))
+                && request.body["type"] == 0
+        })
+        .await;
+    assert_eq!(created.body["parent_id"], overflow_id.to_string());
+}
+
+#[sqlx::test(migrator = "desquestion::database::MIGRATOR")]
+async fn test_ticket_priority(pool: Pool) {
+    let bot = TestBot::start(pool).await;
+    bot.setup_server().await;
+    let subject_id = bot.add_subject("Maths").await;
+    let waiting_id = bot.add_ticket(HELPER_ID).await;
+
+    let member = bot.discord.member(MEMBER_ID);
+    let options = CreateOptions {
+        subject: Some(Subject {
+            id: Some(subject_id as u64),
+            name: "Maths".to_string(),
+        }),
+        priority: Some(Priority::High),
+        ..Default::default()
+    };
+    tickets::create_ticket(&bot.ctx, &bot.data(), &member, options)
+        .await
+        .unwrap();
+
+    let channel_id = open_ticket_channel(&bot.pool, MEMBER_ID)
+        .await
+        .expect("The ticket was not stored") as u64;
+    assert_eq!(bot.discord.channel(channel_id).unwrap().name, "🔴🎫-Maths");
+
+    // Only urgent tickets ping the helpers
+    let ping = bot
+        .discord
+        .wait_for(|request| request.text().contains(&format!("<@&{HELPER_ROLE_ID}>")))
+        .await;
+    assert_eq!(ping.path, format!("/channels/{channel_id}/messages"));
+
+    // Once less urgent, the ticket goes after the one waiting before it
+    bot.send(HELPER_ID, channel_id, "$ticket priority low")
+        .await;
+    bot.discord
+        .wait_for(|request| request.is(Method::PATCH, &format!("/guilds/{GUILD_ID}/channels")))
+        .await;
+    assert_eq!(bot.discord.wait_for_message(channel_id).await.text(), "✅");
+
+    let ticket = bot.discord.channel(channel_id).unwrap();
+    let waiting = bot.discord.channel(waiting_id).unwrap();
+    assert_eq!(ticket.name, "⚪🎫-Maths");
+    assert!(waiting.position < ticket.position);
+}
+
+#[sqlx::test(migrator = "desquestion::database::MIGRATOR")]
+async fn test_ticket_assigned_to_helper_on_duty(pool: Pool) {
+    let bot = TestBot::start(pool).await;
+    bot.setup_server().await;
+    let subject_id = bot.add_subject("Maths").await;
+
+    bot.send(
+        OWNER_ID,
+        GENERAL_CHANNEL_ID,
+        "$config assignmode round_robin",
+    )
+    .await;
+    bot.discord.wait_for_message(GENERAL_CHANNEL_ID).await;
+    bot.send(HELPER_ID, GENERAL_CHANNEL_ID, "$duty on").await;
+    let reply = bot.discord.wait_for_message(GENERAL_CHANNEL_ID).await;
+    assert_eq!(
+        reply.text(),
+        "✅ - You are on duty, new tickets can be assigned to you"
+    );
+
+    let member = bot.discord.member(MEMBER_ID);
+    let options = CreateOptions {
+        subject: Some(Subject {
+            id: Some(subject_id as u64),
+            name: "Maths".to_string(),
+        }),
+        ..Default::default()
+    };
+    tickets::create_ticket(&bot.ctx, &bot.data(), &member, options)
+        .await
+        .unwrap();
+
+    let channel_id = open_ticket_channel(&bot.pool, MEMBER_ID)
+        .await
+        .expect("The ticket was not stored") as u64;
+    let assigned = bot
+        .discord
+        .wait_for(|request| request.text().contains("will take care of your ticket"))
+        .await;
+    assert_eq!(assigned.path, format!("/channels/{channel_id}/messages"));
+    assert_eq!(
+        assigned.text(),
+        format!("<@{HELPER_ID}> will take care of your ticket")
+    );
+    assert_eq!(
+        bot.discord.channel(channel_id).unwrap().parent_id,
+        Some(CLAIMED_CATEGORY_ID.into())
+    );
+
+    let claimed_by: Option<i64> =
+        sqlx::query_scalar("SELECT claimed_by FROM tickets WHERE channel_id = $1")
+            .bind(channel_id as i64)
+            .fetch_one(&bot.pool)
+            .await
+            .unwrap();
+    assert_eq!(claimed_by, Some(HELPER_ID as i64));
+}
+
+#[sqlx::test(migrator = "desquestion::database::MIGRATOR")]
+async fn test_pending_ticket_released_when_office_hours_start(pool: Pool) {
+    let bot = TestBot::start(pool).await;
+    bot.setup_server().await;
+    let subject_id = bot.add_subject("Maths").await;
+    let member = bot.discord.member(MEMBER_ID);
+    let options = || CreateOptions {
+        subject: Some(Subject {
+            id: Some(subject_id as u64),
+            name: "Maths".to_string(),
+        }),
+        ..Default::default()
+    };
+
+    // The server only opens tomorrow
+    let tomorrow = Utc::now() + TimeDelta::days(1);
+    let day = tomorrow.format("%a").to_string().to_lowercase();
+    bot.send(
+        OWNER_ID,
+        GENERAL_CHANNEL_ID,
+        &format!("$config officehours {day} 00:00-23:59"),
+    )
+    .await;
+    bot.discord.wait_for_message(GENERAL_CHANNEL_ID).await;
+
+    let refused = tickets::create_ticket(&bot.ctx, &bot.data(), &member, options())
+        .await
+        .unwrap_err();
+    assert!(refused.to_string().contains("during office hours"));
+
+    bot.send(OWNER_ID, GENERAL_CHANNEL_ID, "$config closedmode pending")
+        .await;
+    bot.discord.wait_for_message(GENERAL_CHANNEL_ID).await;
+
+    tickets::create_ticket(&bot.ctx, &bot.data(), &member, options())
+        .await
+        .unwrap();
+    let channel_id = open_ticket_channel(&bot.pool, MEMBER_ID)
+        .await
+        .expect("The pending ticket was not stored") as u64;
+    bot.discord
+        .wait_for(|request| request.text().contains("when office hours start"))
+        .await;
+
+    // The helpers are pinged once the office hours start
+    let opening = tomorrow
+        .date_naive()
+        .and_hms_opt(12, 0, 0)
+        .unwrap()
+        .and_utc();
+    Scheduler::new(bot.data().metrics)
+        .check(&bot.ctx.http, &bot.pool, opening)
+        .await
+        .unwrap();
+
+    let ping = bot
+        .discord
+        .wait_for(|request| request.text().contains(&format!("<@&{HELPER_ROLE_ID}>")))
+        .await;
+    assert_eq!(ping.path, format!("/channels/{channel_id}/messages"));
+
+    let pending: bool = sqlx::query_scalar("SELECT pending FROM tickets WHERE channel_id = $1")
+        .bind(channel_id as i64)
+        .fetch_one(&bot.pool)
+        .await
+        .unwrap();
+    assert!(!pending);
+}
+
+#[sqlx::test(migrator = "desquestion::database::MIGRATOR")]
+async fn test_unclaimed_ticket_reminders(pool: Pool) {
+    let bot = TestBot::start(pool).await;
+    bot.setup_server().await;
+    bot.send(OWNER_ID, GENERAL_CHANNEL_ID, "$config sla 10 30")
+        .await;
+    bot.discord.wait_for_message(GENERAL_CHANNEL_ID).await;
+    let channel_id = bot.add_ticket(MEMBER_ID).await;
+
+    // The helpers are pinged first
+    backdate_tickets(&bot.pool, MEMBER_ID, 15).await;
+    sla::check(&bot.ctx.http, &bot.pool).await.unwrap();
+    let ping = bot.discord.wait_for_message(channel_id).await;
+    assert!(ping.text().contains(&format!("<@&{HELPER_ROLE_ID}>")));
+
+    // Then the moderators, while the ticket is still unclaimed
+    backdate_tickets(&bot.pool, MEMBER_ID, 20).await;
+    sla::check(&bot.ctx.http, &bot.pool).await.unwrap();
+    let escalation = bot.discord.wait_for_message(channel_id).await;
+    assert!(escalation
+        .text()
+        .contains(&format!("<@&{MODERATOR_ROLE_ID}>")));
+
+    let pinged: (bool, bool) = sqlx::query_as(
+        "SELECT helpers_pinged_at IS NOT NULL, moderators_pinged_at IS NOT NULL
+        FROM tickets WHERE channel_id = $1",
+    )
+    .bind(channel_id as i64)
+    .fetch_one(&bot.pool)
+    .await
+    .unwrap();
+    assert_eq!(pinged, (true, true));
+}
+
+#[sqlx::test(migrator = "desquestion::database::MIGRATOR")]
+async fn test_released_ticket_not_escalated(pool: Pool) {
+    let bot = TestBot::start(pool).await;
+    bot.setup_server().await;
+    bot.send(OWNER_ID, GENERAL_CHANNEL_ID, "$config sla 10 30")
+        .await;
+    bot.discord.wait_for_message(GENERAL_CHANNEL_ID).await;
+
+    // The ticket waited an hour for the office hours
+    let channel_id = bot.add_ticket(MEMBER_ID).await;
+    sqlx::query("UPDATE tickets SET pending = true WHERE channel_id = $1")
+        .bind(channel_id as i64)
+        .execute(&bot.pool)
+        .await
+        .unwrap();
+    backdate_tickets(&bot.pool, MEMBER_ID, 60).await;
+
+    Scheduler::new(bot.data().metrics)
+        .check(&bot.ctx.http, &bot.pool, Utc::now())
+        .await
+        .unwrap();
+    let release = bot.discord.wait_for_message(channel_id).await;
+    assert!(release.text().contains(&format!("<@&{HELPER_ROLE_ID}>")));
+
+    // The moderators are only pinged once the ticket waited too long since its release
+    sla::check(&bot.ctx.http, &bot.pool).await.unwrap();
+    let moderator_ping = format!("<@&{MODERATOR_ROLE_ID}>");
+    assert!(!bot
+        .discord
+        .requests()
+        .iter()
+        .any(|request| request.text().contains(&moderator_ping)));
+
+    let escalated: bool = sqlx::query_scalar(
+        "SELECT moderators_pinged_at IS NOT NULL FROM tickets WHERE channel_id = $1",
+    )
+    .bind(channel_id as i64)
+    .fetch_one(&bot.pool)
+    .await
+    .unwrap();
+    assert!(!escalated);
+}
+
+#[sqlx::test(migrator = "desquestion::database::MIGRATOR")]
+async fn test_unanswered_ticket_reminder(pool: Pool) {
+    let bot = TestBot::start(pool).await;
+    bot.setup_server().await;
+    bot.send(OWNER_ID, GENERAL_CHANNEL_ID, "$config sla 10 30 5")
+        .await;
+    bot.discord.wait_for_message(GENERAL_CHANNEL_ID).await;
+    let channel_id = bot.add_ticket(MEMBER_ID).await;
+
+    bot.send(HELPER_ID, channel_id, "$claim").await;
+    assert_eq!(bot.discord.wait_for_message(channel_id).await.text(), "✅");
+    bot.send(MEMBER_ID, channel_id, "Are you there?").await;
+
+    // The message of the author is recorded in the background
+    let last_author_message = "SELECT last_author_message_at IS NOT NULL FROM tickets
+        WHERE channel_id = $1";
+    let recorded = async {
+        while !sqlx::query_scalar::<_, bool>(last_author_message)
+            .bind(channel_id as i64)
+            .fetch_one(&bot.pool)
+            .await
+            .unwrap()
+        {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    };
+    tokio::time::timeout(Duration::from_secs(10), recorded)
+        .await
+        .expect("The message of the author was not recorded");
+
+    // The helper answered when claiming the ticket, before the author wrote again
+    backdate_messages(&bot.pool, channel_id, 10, 20).await;
+    sla::check(&bot.ctx.http, &bot.pool).await.unwrap();
+    let reminder = bot.discord.wait_for_message(channel_id).await;
+    assert!(reminder.text().contains(&format!("<@{HELPER_ID}>")));
+
+    // The reminder is recorded, so that the same message is not reminded twice
+    let reminded: bool = sqlx::query_scalar(
+        "SELECT helper_reminded_at IS NOT NULL FROM tickets WHERE channel_id = $1",
+    )
+    .bind(channel_id as i64)
+    .fetch_one(&bot.pool)
+    .await
+    .unwrap();
+    assert!(reminded);
+}
+
+#[sqlx::test(migrator = "desquestion::database::MIGRATOR")]
+async fn test_claim_ticket(pool: Pool) {
+    let bot = TestBot::start(pool).await;
+    bot.setup_server().await;
+    let channel_id = bot.add_ticket(MEMBER_ID).await;
+
+    bot.send(HELPER_ID, channel_id, "$claim").await;
+
+    let moved = bot
+        .discord
+        .wait_for(|request| request.is(Method::PATCH, &format!("/channels/{channel_id}")))
+        .await;
+    assert_eq!(moved.body["parent_id"], CLAIMED_CATEGORY_ID.to_string());
+    assert_eq!(bot.discord.wait_for_message(channel_id).await.text(), "✅");
+
+    let claimed_by: Option<i64> =
+        sqlx::query_scalar("SELECT claimed_by FROM tickets WHERE channel_id = $1")
+            .bind(channel_id as i64)
+            .fetch_one(&bot.pool)
+            .await
+            .unwrap();
+    assert_eq!(claimed_by, Some(HELPER_ID as i64));
+}
+
+#[sqlx::test(migrator = "desquestion::database::MIGRATOR")]
+async fn test_queue(pool: Pool) {
+    let bot = TestBot::start(pool).await;
+    bot.setup_server().await;
+    let oldest_id = bot.add_ticket(MEMBER_ID).await;
+    let urgent_id = bot.add_ticket(OWNER_ID).await;
+    sqlx::query("UPDATE tickets SET priority = 'high' WHERE channel_id = $1")
+        .bind(urgent_id as i64)
+        .execute(&bot.pool)
+        .await
+        .unwrap();
+
+    // Tickets waiting for the office hours are not in the queue yet
+    let pending_id = bot.add_ticket(HELPER_ID).await;
+    sqlx::query("UPDATE tickets SET pending = true WHERE channel_id = $1")
+        .bind(pending_id as i64)
+        .execute(&bot.pool)
+        .await
+        .unwrap();
+
+    bot.send(HELPER_ID, GENERAL_CHANNEL_ID, "$queue").await;
+
+    let queue = bot
+        .discord
+        .wait_for_message(GENERAL_CHANNEL_ID)
+        .await
+        .text();
+    let lines: Vec<&str> = queue.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].starts_with(&format!(
+        "**1.** <#{urgent_id}> - No subject - High priority"
+    )));
+    assert!(lines[1].starts_with(&format!("**2.** <#{oldest_id}>")));
+}
+
+#[sqlx::test(migrator = "desquestion::database::MIGRATOR")]
+async fn test_next_ticket_respects_subject_roles(pool: Pool) {
+    let bot = TestBot::start(pool).await;
+    bot.setup_server().await;
+    let subject_id = bot.add_subject("Physics").await;
+    let restricted_id = bot.add_ticket(MEMBER_ID).await;
+    let open_id = bot.add_ticket(OWNER_ID).await;
+
+    // The oldest ticket is about a subject the helper cannot take
+    sqlx::query("UPDATE subjects SET helper_role_id = 999 WHERE id = $1")
+        .bind(subject_id)
+        .execute(&bot.pool)
+        .await
+        .unwrap();
+    sqlx::query("UPDATE tickets SET subject_id = $1 WHERE channel_id = $2")
+        .bind(subject_id)
+        .bind(restricted_id as i64)
+        .execute(&bot.pool)
+        .await
+        .unwrap();
+
+    bot.send(HELPER_ID, GENERAL_CHANNEL_ID, "$next").await;
+
+    let moved = bot
+        .discord
+        .wait_for(|request| request.is(Method::PATCH, &format!("/channels/{open_id}")))
+        .await;
+    assert_eq!(moved.body["parent_id"], CLAIMED_CATEGORY_ID.to_string());
+    let reply = bot.discord.wait_for_message(GENERAL_CHANNEL_ID).await;
+    assert_eq!(reply.text(), format!("✅ - <#{open_id}> is yours"));
+
+    // Nothing is left for the helper
+    bot.send(HELPER_ID, GENERAL_CHANNEL_ID, "$next").await;
+    let reply = bot.discord.wait_for_message(GENERAL_CHANNEL_ID).await;
+    assert_eq!(reply.text(), "❌ - No ticket is waiting for you");
+
+    let claimed_by: Option<i64> =
+        sqlx::query_scalar("SELECT claimed_by FROM tickets WHERE channel_id = $1")
+            .bind(restricted_id as i64)
+            .fetch_one(&bot.pool)
+            .await
+            .unwrap();
+    assert_eq!(claimed_by, None);
+}
+
+#[sqlx::test(migrator = "desquestion::database::MIGRATOR")]
+async fn test_claim_ticket_without_helper_role(pool: Pool) {
+    let bot = TestBot::start(pool).await;
+    bot.setup_server().await;
+    let channel_id = bot.add_ticket(MEMBER_ID).await;
+
+    bot.send(MEMBER_ID, channel_id, "$claim").await;
+
+    let reply = bot.discord.wait_for_message(channel_id).await;
+    assert_eq!(reply.text(), "❌ - Only helpers can claim a ticket");
+
+    let claimed_by: Option<i64> =
+        sqlx::query_scalar("SELECT claimed_by FROM tickets WHERE channel_id = $1")
+            .bind(channel_id as i64)
+            .fetch_one(&bot.pool)
+            .await
+            .unwrap();
+    assert_eq!(claimed_by, None);
+}
+
+#[sqlx::test(migrator = "desquestion::database::MIGRATOR")]
+async fn test_ticket_messages_recorded(pool: Pool) {
+    let bot = TestBot::start(pool).await;
+    bot.setup_server().await;
+    let channel_id = bot.add_ticket(MEMBER_ID).await;
+
+    bot.send(MEMBER_ID, channel_id, "My exercise does not compile")
+        .await;
+    bot.send(HELPER_ID, channel_id, "Can you show me the error?")
+        .await;
+
+    // The roles of the helper are checked before their message is recorded
+    bot.discord
+        .wait_for(|request| {
+            request.is(
+                Method::GET,
+                &format!("/guilds/{GUILD_ID}/members/{HELPER_ID}"),
+            )
+        })
+        .await;
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let recorded: (bool, bool, bool) = sqlx::query_as(
+        "SELECT last_author_message_at IS NOT NULL, last_staff_message_at IS NOT NULL,
+            first_response_at IS NOT NULL
+        FROM tickets WHERE channel_id = $1",
+    )
+    .bind(channel_id as i64)
+    .fetch_one(&bot.pool)
+    .await
+    .unwrap();
+    assert_eq!(recorded, (true, true, true));
+}
+
+#[sqlx::test(migrator = "desquestion::database::MIGRATOR")]
+async fn test_subject_helper_message_recorded(pool: Pool) {
+    const TUTOR_ID: u64 = 6;
+    const TUTOR_ROLE_ID: u64 = 202;
+
+    let bot = TestBot::start(pool).await;
+    bot.setup_server().await;
+    bot.discord.add_member(TUTOR_ID, "tutor", &[TUTOR_ROLE_ID]);
+    let subject_id = bot.add_subject("Physics").await;
+    let channel_id = bot.add_ticket(MEMBER_ID).await;
+
+    // The tutor only holds the helper role of the subject of the ticket
+    sqlx::query("UPDATE subjects SET helper_role_id = $1 WHERE id = $2")
+        .bind(TUTOR_ROLE_ID as i64)
+        .bind(subject_id)
+        .execute(&bot.pool)
+        .await
+        .unwrap();
+    sqlx::query("UPDATE tickets SET subject_id = $1 WHERE channel_id = $2")
+        .bind(subject_id)
+        .bind(channel_id as i64)
+        .execute(&bot.pool)
+        .await
+        .unwrap();
+
+    bot.send(TUTOR_ID, channel_id, "Which formula did you use?")
+        .await;
+
+    let first_response = "SELECT first_response_at IS NOT NULL FROM tickets
+        WHERE channel_id = $1";
+    let recorded = async {
+        while !sqlx::query_scalar::<_, bool>(first_response)
+            .bind(channel_id as i64)
+            .fetch_one(&bot.pool)
+            .await
+            .unwrap()
+        {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    };
+    tokio::time::timeout(Duration::from_secs(10), recorded)
+        .await
+        .expect("The answer of the tutor was not recorded");
+}
+
+#[sqlx::test(migrator = "desquestion::database::MIGRATOR")]
+async fn test_inactive_ticket_closed(pool: Pool) {
+    let bot = TestBot::start(pool).await;
+    bot.setup_server().await;
+    bot.send(OWNER_ID, GENERAL_CHANNEL_ID, "$config inactivity 60")
+        .await;
+    bot.discord.wait_for_message(GENERAL_CHANNEL_ID).await;
+    let channel_id = bot.add_ticket(MEMBER_ID).await;
+
+    // A ticket still active is kept
+    activity::close_inactive(&bot.ctx.http, &bot.data())
+        .await
+        .unwrap();
+    assert!(open_ticket_channel(&bot.pool, MEMBER_ID).await.is_some());
+
+    backdate_tickets(&bot.pool, MEMBER_ID, 90).await;
+    activity::close_inactive(&bot.ctx.http, &bot.data())
+        .await
+        .unwrap();
+
+    bot.discord
+        .wait_for(|request| request.is(Method::DELETE, &format!("/channels/{channel_id}")))
+        .await;
+    let reason: Option<String> =
+        sqlx::query_scalar("SELECT close_reason FROM tickets WHERE channel_id = $1")
+            .bind(channel_id as i64)
+            .fetch_one(&bot.pool)
+            .await
+            .unwrap();
+    assert_eq!(
+        reason.as_deref(),
+        Some("Ticket closed after a period of inactivity")
+    );
+}
+
+#[sqlx::test(migrator = "desquestion::database::MIGRATOR")]
+async fn test_released_ticket_kept_open(pool: Pool) {
+    let bot = TestBot::start(pool).await;
+    bot.setup_server().await;
+    bot.send(OWNER_ID, GENERAL_CHANNEL_ID, "$config inactivity 60")
+        .await;
+    bot.discord.wait_for_message(GENERAL_CHANNEL_ID).await;
+
+    // A pending ticket is never closed, however long it waits for the office hours
+    let channel_id = bot.add_ticket(MEMBER_ID).await;
+    sqlx::query("UPDATE tickets SET pending = true WHERE channel_id = $1")
+        .bind(channel_id as i64)
+        .execute(&bot.pool)
+        .await
+        .unwrap();
+    backdate_tickets(&bot.pool, MEMBER_ID, 90).await;
+    activity::close_inactive(&bot.ctx.http, &bot.data())
+        .await
+        .unwrap();
+    assert!(open_ticket_channel(&bot.pool, MEMBER_ID).await.is_some());
+
+    // Nor right after its release
+    Scheduler::new(bot.data().metrics)
+        .check(&bot.ctx.http, &bot.pool, Utc::now())
+        .await
+        .unwrap();
+    bot.discord.wait_for_message(channel_id).await;
+    activity::close_inactive(&bot.ctx.http, &bot.data())
+        .await
+        .unwrap();
+    assert!(open_ticket_channel(&bot.pool, MEMBER_ID).await.is_some());
+}
+
+#[sqlx::test(migrator = "desquestion::database::MIGRATOR")]
+async fn test_concurrent_claims(pool: Pool) {
+    const OTHER_HELPER_ID: u64 = 5;
+
+    let bot = TestBot::start(pool).await;
+    bot.setup_server().await;
+    bot.discord
+        .add_member(OTHER_HELPER_ID, "other helper", &[HELPER_ROLE_ID]);
+    let channel_id = bot.add_ticket(MEMBER_ID).await;
+
+    tokio::join!(
+        bot.send(HELPER_ID, channel_id, "$claim"),
+        bot.send(OTHER_HELPER_ID, channel_id, "$claim"),
+    );
+
+    // Only one of the helpers gets the ticket, the other one is told it is taken
+    let mut replies = vec![
+        bot.discord.wait_for_message(channel_id).await.text(),
+        bot.discord.wait_for_message(channel_id).await.text(),
+    ];
+    replies.sort();
+    assert_eq!(
+        replies,
+        ["✅", "❌ - This ticket is already claimed or closed"]
+    );
+
+    let claimed_by: Option<i64> =
+        sqlx::query_scalar("SELECT claimed_by FROM tickets WHERE channel_id = $1")
+            .bind(channel_id as i64)
+            .fetch_one(&bot.pool)
+            .await
+            .unwrap();
+    assert!(matches!(
+        claimed_by,
+        Some(id) if id == HELPER_ID as i64 || id == OTHER_HELPER_ID as i64
+    ));
+}
+
+#[sqlx::test(migrator = "desquestion::database::MIGRATOR")]
+async fn test_close_ticket(pool: Pool) {
+    let bot = TestBot::start(pool).await;
+    bot.setup_server().await;
+    let channel_id = bot.add_ticket(MEMBER_ID).await;
+
+    let command = bot.send(MEMBER_ID, channel_id, "$close Solved").await;
+
+    // The closing is confirmed, then happens after the countdown
+    bot.discord.wait_for_message(channel_id).await;
+    let confirm_id = format!("{}confirm", command["id"].as_str().unwrap());
+    bot.click(MEMBER_ID, channel_id, &confirm_id, None).await;
+
+    bot.discord.wait_for_message(LOG_CHANNEL_ID).await;
+    bot.discord
+        .wait_for(|request| request.is(Method::DELETE, &format!("/channels/{channel_id}")))
+        .await;
+
+    let reason: Option<String> = sqlx::query_scalar(
+        "SELECT close_reason FROM tickets WHERE channel_id = $1 AND NOT is_open",
+    )
+    .bind(channel_id as i64)
+    .fetch_one(&bot.pool)
+    .await
+    .unwrap();
+    assert_eq!(reason.as_deref(), Some("Solved"));
+    assert!(bot.discord.channel(channel_id).is_none());
+}